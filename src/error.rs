@@ -0,0 +1,81 @@
+use core::fmt;
+
+/// Errors reported when reading malformed transport stream data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum TsError {
+    /// The first byte was not the sync byte (0x47)
+    BadSync(u8),
+    /// The buffer was not the expected length
+    BadLength(usize),
+    /// The adaptation field length runs past the end of the packet
+    AdaptationFieldOverflow(u8),
+    /// The adaptation field is too short to hold a field its flags announce
+    TruncatedAdaptationField(u8),
+    /// An adaptation field was required but the packet has none
+    MissingAdaptationField,
+    /// A field holds a value reserved by ISO-13818-1
+    Reserved(&'static str),
+    /// A Reed-Solomon protected packet has too many errors to correct
+    Uncorrectable,
+    /// A PID was above 8191 (0x1fff)
+    PidOutOfRange(u16),
+    /// The pointer_field points past the end of the payload
+    PointerFieldOverflow(u8),
+    /// A section is longer than `SECTION_MAX_SIZE`
+    SectionTooLong(usize),
+    /// A new section started before the previous one was complete
+    TruncatedSection(usize),
+    /// A section's CRC_32 does not match its contents
+    BadCrc { expected: u32, found: u32 },
+    /// A section has the wrong table_id for the table being parsed
+    BadTableId(u8),
+    /// A table has more entries than the given capacity
+    TooManyEntries(usize),
+    /// A section does not belong to the same table version as the others
+    InconsistentSection,
+    /// A section's section_number is past its last_section_number
+    BadSectionNumber(u8),
+    /// A descriptor with the given tag runs past the end of its loop
+    TruncatedDescriptor(u8),
+    /// A descriptor has the wrong descriptor_tag for the type being decoded
+    BadDescriptorTag(u8),
+}
+
+impl fmt::Display for TsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            TsError::BadSync(byte) => write!(f, "bad sync byte 0x{:02x}", byte),
+            TsError::BadLength(len) => write!(f, "bad buffer length {}", len),
+            TsError::AdaptationFieldOverflow(len) => {
+                write!(f, "adaptation field length {} overflows packet", len)
+            }
+            TsError::TruncatedAdaptationField(len) => {
+                write!(f, "adaptation field length {} is too short for its flags", len)
+            }
+            TsError::MissingAdaptationField => write!(f, "no adaptation field present"),
+            TsError::Reserved(field) => write!(f, "reserved value in {}", field),
+            TsError::Uncorrectable => write!(f, "too many errors to correct"),
+            TsError::PidOutOfRange(pid) => write!(f, "PID 0x{:04x} out of range", pid),
+            TsError::PointerFieldOverflow(pointer) => {
+                write!(f, "pointer_field {} overflows payload", pointer)
+            }
+            TsError::SectionTooLong(len) => write!(f, "section length {} is too long", len),
+            TsError::TruncatedSection(len) => write!(f, "section truncated after {} bytes", len),
+            TsError::BadCrc { expected, found } => {
+                write!(f, "bad CRC_32 0x{:08x}, expected 0x{:08x}", found, expected)
+            }
+            TsError::BadTableId(table_id) => write!(f, "unexpected table_id 0x{:02x}", table_id),
+            TsError::TooManyEntries(capacity) => write!(f, "more than {} table entries", capacity),
+            TsError::InconsistentSection => write!(f, "section does not match the rest of the table"),
+            TsError::BadSectionNumber(number) => {
+                write!(f, "section_number {} is past last_section_number", number)
+            }
+            TsError::TruncatedDescriptor(tag) => write!(f, "descriptor 0x{:02x} truncated", tag),
+            TsError::BadDescriptorTag(tag) => write!(f, "unexpected descriptor_tag 0x{:02x}", tag),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TsError {}
@@ -1,2 +1,8 @@
 #![no_std]
+#[cfg(any(test, feature = "std"))]
+extern crate std;
+
+pub mod error;
 pub mod mpeg;
+
+pub use error::TsError;
@@ -0,0 +1,202 @@
+use super::descriptor::{write_descriptor, Descriptor, Descriptors, DESCRIPTOR_MAX_DATA};
+use crate::error::TsError;
+
+/// descriptor_tag of a CA_descriptor
+pub const CA_DESCRIPTOR_TAG: u8 = 0x09;
+
+// CA_system_ID and CA_PID
+const CA_FIXED_SIZE: usize = 4;
+
+/// Well-known CA_system_ID ranges from the DVB registry, as (first, last, name)
+pub const CA_SYSTEMS: [(u16, u16, &str); 14] = [
+    (0x0100, 0x01ff, "Seca Mediaguard"),
+    (0x0500, 0x05ff, "Viaccess"),
+    (0x0600, 0x06ff, "Irdeto"),
+    (0x0700, 0x07ff, "DigiCipher 2"),
+    (0x0900, 0x09ff, "NDS Videoguard"),
+    (0x0b00, 0x0bff, "Conax"),
+    (0x0d00, 0x0dff, "CryptoWorks"),
+    (0x0e00, 0x0eff, "PowerVu"),
+    (0x1000, 0x10ff, "Tandberg"),
+    (0x1700, 0x17ff, "BetaCrypt"),
+    (0x1800, 0x18ff, "Nagravision"),
+    (0x2600, 0x26ff, "BISS"),
+    (0x4ae0, 0x4ae1, "DRE-Crypt"),
+    (0x5601, 0x5604, "Verimatrix"),
+];
+
+/// Get the name of a well-known CA system
+pub fn ca_system_name(ca_system_id: u16) -> Option<&'static str> {
+    CA_SYSTEMS
+        .iter()
+        .find(|&&(first, last, _)| (first..=last).contains(&ca_system_id))
+        .map(|&(_, _, name)| name)
+}
+
+/// A CA_descriptor, naming the PID of the EMMs (in the CAT) or ECMs (in a
+/// PMT) for one CA system.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CaDescriptor<'a> {
+    ca_system_id: u16,
+    ca_pid: u16,
+    private_data: &'a [u8],
+}
+
+impl<'a> CaDescriptor<'a> {
+    /// Make a CA_descriptor
+    pub fn new(ca_system_id: u16, ca_pid: u16, private_data: &'a [u8]) -> Self {
+        CaDescriptor {
+            ca_system_id,
+            ca_pid: ca_pid & 0x1fff,
+            private_data,
+        }
+    }
+
+    /// Decode a CA_descriptor from a descriptor loop
+    pub fn parse(descriptor: Descriptor<'a>) -> Result<Self, TsError> {
+        if descriptor.tag() != CA_DESCRIPTOR_TAG {
+            return Err(TsError::BadDescriptorTag(descriptor.tag()));
+        }
+        let data = descriptor.data();
+        if data.len() < CA_FIXED_SIZE {
+            return Err(TsError::TruncatedDescriptor(CA_DESCRIPTOR_TAG));
+        }
+        Ok(CaDescriptor {
+            ca_system_id: u16::from_be_bytes([data[0], data[1]]),
+            ca_pid: u16::from_be_bytes([data[2], data[3]]) & 0x1fff,
+            private_data: &data[CA_FIXED_SIZE..],
+        })
+    }
+
+    /// Get the CA_system_ID
+    pub fn ca_system_id(&self) -> u16 {
+        self.ca_system_id
+    }
+
+    /// Get the name of the CA system, if it is well known
+    pub fn ca_system_name(&self) -> Option<&'static str> {
+        ca_system_name(self.ca_system_id)
+    }
+
+    /// Get the CA_PID
+    pub fn ca_pid(&self) -> u16 {
+        self.ca_pid
+    }
+
+    /// Get the private_data_bytes
+    pub fn private_data(&self) -> &'a [u8] {
+        self.private_data
+    }
+
+    /// Write the descriptor to the start of `out`, returning its length
+    pub fn write(&self, out: &mut [u8]) -> Result<usize, TsError> {
+        let len = CA_FIXED_SIZE + self.private_data.len();
+        if len > DESCRIPTOR_MAX_DATA {
+            return Err(TsError::BadLength(len));
+        }
+        let mut data = [0u8; DESCRIPTOR_MAX_DATA];
+        data[0..2].copy_from_slice(&self.ca_system_id.to_be_bytes());
+        data[2..4].copy_from_slice(&(0xe000 | self.ca_pid).to_be_bytes());
+        data[CA_FIXED_SIZE..len].copy_from_slice(self.private_data);
+        write_descriptor(out, CA_DESCRIPTOR_TAG, &data[..len])
+    }
+}
+
+/// Iterator over the CA_descriptors of a descriptor loop, skipping any too
+/// short to hold a CA_PID.
+#[derive(Clone, Copy, Debug)]
+pub struct CaDescriptors<'a> {
+    descriptors: Descriptors<'a>,
+}
+
+impl<'a> CaDescriptors<'a> {
+    /// Pick the CA_descriptors out of a descriptor loop
+    pub fn new(descriptors: Descriptors<'a>) -> Self {
+        CaDescriptors { descriptors }
+    }
+}
+
+impl<'a> Iterator for CaDescriptors<'a> {
+    type Item = CaDescriptor<'a>;
+
+    fn next(&mut self) -> Option<CaDescriptor<'a>> {
+        self.descriptors.find_map(|d| CaDescriptor::parse(d).ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let bytes = [0x09, 0x06, 0x0b, 0x00, 0xe1, 0x23, 0xaa, 0xbb];
+        let descriptor = Descriptors::new(&bytes).unwrap().next().unwrap();
+        let ca = CaDescriptor::parse(descriptor).unwrap();
+        assert_eq!(ca.ca_system_id(), 0x0b00);
+        assert_eq!(ca.ca_system_name(), Some("Conax"));
+        assert_eq!(ca.ca_pid(), 0x0123);
+        assert_eq!(ca.private_data(), &[0xaa, 0xbb]);
+
+        let mut out = [0u8; 16];
+        assert_eq!(ca.write(&mut out), Ok(bytes.len()));
+        assert_eq!(&out[..bytes.len()], &bytes);
+    }
+
+    #[test]
+    fn test_bad_descriptors() {
+        let bytes = [0x0a, 0x04, b'e', b'n', b'g', 0x00, 0x09, 0x03, 0x18, 0x00, 0xe1];
+        let mut descriptors = Descriptors::new(&bytes).unwrap();
+        assert_eq!(
+            CaDescriptor::parse(descriptors.next().unwrap()),
+            Err(TsError::BadDescriptorTag(0x0a))
+        );
+        assert_eq!(
+            CaDescriptor::parse(descriptors.next().unwrap()),
+            Err(TsError::TruncatedDescriptor(CA_DESCRIPTOR_TAG))
+        );
+        assert_eq!(CaDescriptors::new(Descriptors::new(&bytes).unwrap()).count(), 0);
+        let private_data = [0u8; 252];
+        assert_eq!(
+            CaDescriptor::new(0x0500, 0x100, &private_data).write(&mut [0u8; 300]),
+            Err(TsError::BadLength(256))
+        );
+    }
+
+    #[test]
+    fn test_iterate() {
+        let bytes = [
+            0x09, 0x04, 0x18, 0x00, 0xe1, 0x00, 0x52, 0x01, 0x07, 0x09, 0x04, 0x06, 0x04, 0xe1, 0x01,
+        ];
+        let mut cas = CaDescriptors::new(Descriptors::new(&bytes).unwrap());
+        assert_eq!(cas.next(), Some(CaDescriptor::new(0x1800, 0x100, &[])));
+        assert_eq!(cas.next().unwrap().ca_system_name(), Some("Irdeto"));
+        assert!(cas.next().is_none());
+    }
+
+    #[test]
+    fn test_system_names() {
+        assert_eq!(ca_system_name(0x0100), Some("Seca Mediaguard"));
+        assert_eq!(ca_system_name(0x09cd), Some("NDS Videoguard"));
+        assert_eq!(ca_system_name(0x2600), Some("BISS"));
+        assert_eq!(ca_system_name(0x5602), Some("Verimatrix"));
+        assert_eq!(ca_system_name(0x0000), None);
+        assert_eq!(ca_system_name(0xffff), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let bytes = [0x09, 0x05, 0x0b, 0x00, 0xe1, 0x23, 0xaa];
+        let descriptor = Descriptors::new(&bytes).unwrap().next().unwrap();
+        assert_eq!(
+            serde_json::to_string(&descriptor).unwrap(),
+            r#"{"tag":9,"data":[11,0,225,35,170]}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&CaDescriptor::parse(descriptor).unwrap()).unwrap(),
+            r#"{"ca_system_id":2816,"ca_pid":291,"private_data":[170]}"#
+        );
+    }
+}
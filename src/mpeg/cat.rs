@@ -0,0 +1,241 @@
+use super::ca::{CaDescriptor, CaDescriptors};
+use super::descriptor::{Descriptors, DESCRIPTOR_HEADER_SIZE, DESCRIPTOR_MAX_DATA};
+use super::section_builder::SectionBuffer;
+use super::table::{DescriptorTable, RESERVED_TABLE_ID_EXTENSION, TABLE_MAX_DESCRIPTORS};
+use crate::error::TsError;
+
+/// table_id of a CA_section
+pub const CAT_TABLE_ID: u8 = 0x01;
+/// Most descriptor bytes a `Cat` can hold
+pub const CAT_MAX_DESCRIPTORS: usize = TABLE_MAX_DESCRIPTORS;
+
+/// A conditional access table: one descriptor loop, possibly spread over
+/// several sections.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Cat {
+    table: DescriptorTable,
+}
+
+impl Cat {
+    /// Start an empty, current CAT at version 0
+    pub fn new() -> Self {
+        Cat {
+            table: DescriptorTable::new(),
+        }
+    }
+
+    /// Parse one CA_section, checking its CRC_32 and descriptor loop. If it
+    /// is one of several, add the others with `add_section`.
+    pub fn parse(bytes: &[u8]) -> Result<Self, TsError> {
+        Ok(Cat {
+            table: DescriptorTable::parse(bytes, CAT_TABLE_ID)?,
+        })
+    }
+
+    /// Add another section of the same table, ignoring one already seen
+    pub fn add_section(&mut self, bytes: &[u8]) -> Result<(), TsError> {
+        self.table.add_section(bytes, CAT_TABLE_ID, None)
+    }
+
+    /// Have all the sections of the table been seen?
+    pub fn is_complete(&self) -> bool {
+        self.table.is_complete()
+    }
+
+    /// Get the version_number
+    pub fn version_number(&self) -> u8 {
+        self.table.version_number()
+    }
+
+    /// Set the version_number. Max: 31
+    pub fn set_version_number(&mut self, version: u8) {
+        self.table.set_version_number(version);
+    }
+
+    /// Get the current_next_indicator
+    pub fn current_next_indicator(&self) -> bool {
+        self.table.current_next_indicator()
+    }
+
+    /// Set the current_next_indicator
+    pub fn set_current_next_indicator(&mut self, current: bool) {
+        self.table.set_current_next_indicator(current);
+    }
+
+    /// Get the raw descriptor loop
+    pub fn descriptor_loop(&self) -> &[u8] {
+        self.table.descriptor_loop()
+    }
+
+    /// Iterate over the descriptors
+    pub fn descriptors(&self) -> Descriptors<'_> {
+        self.table.descriptors()
+    }
+
+    /// Iterate over the CA_descriptors, each naming an EMM PID
+    pub fn ca_descriptors(&self) -> CaDescriptors<'_> {
+        CaDescriptors::new(self.descriptors())
+    }
+
+    /// Append a descriptor
+    pub fn add_descriptor(&mut self, tag: u8, data: &[u8]) -> Result<(), TsError> {
+        self.table.add_descriptor(tag, data)
+    }
+
+    /// Append a CA_descriptor
+    pub fn add_ca_descriptor(&mut self, ca: &CaDescriptor<'_>) -> Result<(), TsError> {
+        let mut descriptor = [0u8; DESCRIPTOR_HEADER_SIZE + DESCRIPTOR_MAX_DATA];
+        let len = ca.write(&mut descriptor)?;
+        self.table.add_descriptor_loop(&descriptor[..len])
+    }
+
+    /// Remove every descriptor
+    pub fn clear_descriptors(&mut self) {
+        self.table.clear_descriptors();
+    }
+
+    /// Get the number of sections needed to carry the table
+    pub fn section_count(&self) -> usize {
+        self.table.section_count()
+    }
+
+    /// Write section `number` of the table to the start of `out`, returning
+    /// its length
+    pub fn write_section(&self, number: usize, out: &mut [u8]) -> Result<usize, TsError> {
+        self.table.write_section(CAT_TABLE_ID, RESERVED_TABLE_ID_EXTENSION, number, out)
+    }
+
+    /// Build section `number` of the table, returning it with its length
+    pub fn build_section(&self, number: usize) -> Result<(SectionBuffer, usize), TsError> {
+        self.table.build_section(CAT_TABLE_ID, RESERVED_TABLE_ID_EXTENSION, number)
+    }
+}
+
+impl Default for Cat {
+    fn default() -> Self {
+        Cat::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpeg::filter::PidFilter;
+    use crate::mpeg::pmt::Pmt;
+    use crate::mpeg::section_builder::SectionBuilder;
+    use crate::mpeg::stream_type::StreamType;
+
+    #[test]
+    fn test_build_and_parse() {
+        let mut cat = Cat::new();
+        cat.set_version_number(3);
+        cat.add_ca_descriptor(&CaDescriptor::new(0x0b00, 0x1c0, &[])).unwrap();
+        cat.add_descriptor(0x80, &[1, 2, 3]).unwrap();
+        cat.add_ca_descriptor(&CaDescriptor::new(0x1802, 0x1c1, &[0x11, 0x22])).unwrap();
+        assert_eq!(cat.section_count(), 1);
+
+        let (section, len) = cat.build_section(0).unwrap();
+        assert_eq!(&section[..8], &[0x01, 0xb0, len as u8 - 3, 0xff, 0xff, 0xc7, 0x00, 0x00]);
+        let parsed = Cat::parse(&section[..len]).unwrap();
+        assert!(parsed.is_complete());
+        assert_eq!(parsed, cat);
+        assert_eq!(parsed.descriptors().count(), 3);
+
+        let mut cas = parsed.ca_descriptors();
+        let conax = cas.next().unwrap();
+        assert_eq!((conax.ca_system_name(), conax.ca_pid()), (Some("Conax"), 0x1c0));
+        let nagra = cas.next().unwrap();
+        assert_eq!((nagra.ca_system_name(), nagra.ca_pid()), (Some("Nagravision"), 0x1c1));
+        assert_eq!(nagra.private_data(), &[0x11, 0x22]);
+        assert!(cas.next().is_none());
+    }
+
+    #[test]
+    fn test_multi_section() {
+        let mut cat = Cat::new();
+        let private_data = [0x5a; 196];
+        for n in 0..10 {
+            cat.add_ca_descriptor(&CaDescriptor::new(0x0600 + n, 0x200 + n, &private_data))
+                .unwrap();
+        }
+        // five 202-byte descriptors fit in a section's 1012 bytes
+        assert_eq!(cat.section_count(), 2);
+        let (first, first_len) = cat.build_section(0).unwrap();
+        let (second, second_len) = cat.build_section(1).unwrap();
+        assert_eq!(cat.build_section(2).err(), Some(TsError::TooManyEntries(2)));
+
+        let mut parsed = Cat::parse(&second[..second_len]).unwrap();
+        assert!(!parsed.is_complete());
+        parsed.add_section(&second[..second_len]).unwrap();
+        parsed.add_section(&first[..first_len]).unwrap();
+        assert!(parsed.is_complete());
+        assert_eq!(parsed, cat);
+
+        cat.set_version_number(1);
+        let (other, other_len) = cat.build_section(0).unwrap();
+        assert_eq!(parsed.add_section(&other[..other_len]), Err(TsError::InconsistentSection));
+        cat.clear_descriptors();
+        assert_eq!(cat.section_count(), 1);
+    }
+
+    #[test]
+    fn test_bad_sections() {
+        let (section, len) = SectionBuilder::new(CAT_TABLE_ID).data(&[0x09, 0x04, 0x01]).build().unwrap();
+        assert_eq!(Cat::parse(&section[..len]), Err(TsError::TruncatedDescriptor(0x09)));
+        let (section, len) = SectionBuilder::new(0x02).build().unwrap();
+        assert_eq!(Cat::parse(&section[..len]), Err(TsError::BadTableId(0x02)));
+    }
+
+    #[test]
+    fn test_reserved_extension() {
+        // the table_id_extension is reserved, so whatever a multiplexer puts
+        // there is not kept
+        let (section, len) = SectionBuilder::new(CAT_TABLE_ID).table_id_extension(0).build().unwrap();
+        assert_eq!(Cat::parse(&section[..len]).unwrap(), Cat::new());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let mut cat = Cat::new();
+        cat.add_ca_descriptor(&CaDescriptor::new(0x0b00, 0x1c0, &[])).unwrap();
+        let json = serde_json::to_string(&cat).unwrap();
+        assert_eq!(
+            json,
+            r#"{"version_number":0,"current_next_indicator":true,"descriptors":[9,4,11,0,225,192]}"#
+        );
+        assert_eq!(serde_json::from_str::<Cat>(&json).unwrap(), cat);
+
+        for descriptors in ["[9]", "[9,5,0]", "[9,4,11]"].iter() {
+            let json = std::format!(
+                r#"{{"version_number":0,"current_next_indicator":true,"descriptors":{}}}"#,
+                descriptors
+            );
+            let err = serde_json::from_str::<Cat>(&json).unwrap_err();
+            assert!(std::format!("{}", err).contains("descriptor 0x09 truncated"));
+        }
+    }
+
+    #[test]
+    fn test_ca_pids() {
+        // keep the EMM and ECM PIDs alongside a program's own PIDs
+        let mut cat = Cat::new();
+        cat.add_ca_descriptor(&CaDescriptor::new(0x0500, 0x1c0, &[])).unwrap();
+        let mut pmt = Pmt::new(1, 0x100);
+        let mut ecm = [0u8; 6];
+        CaDescriptor::new(0x0500, 0x1c8, &[]).write(&mut ecm).unwrap();
+        pmt.set_program_info(&ecm).unwrap();
+        CaDescriptor::new(0x0500, 0x1c9, &[]).write(&mut ecm).unwrap();
+        pmt.add_stream(StreamType::Avc, 0x100, &ecm).unwrap();
+
+        assert_eq!(pmt.stream(0x100).unwrap().ca_descriptors().next().unwrap().ca_pid(), 0x1c9);
+        let mut filter = PidFilter::from_pids(&[0x100]);
+        for ca in cat.ca_descriptors().chain(pmt.ca_descriptors()) {
+            filter.insert(ca.ca_pid());
+        }
+        assert_eq!(filter.len(), 4);
+        assert!(filter.contains(0x1c0) && filter.contains(0x1c8) && filter.contains(0x1c9));
+    }
+}
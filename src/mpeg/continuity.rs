@@ -0,0 +1,565 @@
+use core::fmt;
+
+use super::packet::{self, Packet, MAX_PID, NULL_PACKET_PID};
+use super::packet_info::PacketInfo;
+
+/// Number of PIDs, and so of states a `ContinuityChecker` or
+/// `ContinuityStamper` needs
+pub const PID_COUNT: usize = MAX_PID as usize + 1;
+
+/// The result of checking one packet's continuity counter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ContinuityEvent {
+    /// The counter followed on from the previous packet
+    Ok,
+    /// The first packet seen on this PID
+    First,
+    /// Not checked: a null packet or one with the transport error indicator set
+    Ignored,
+    /// A repeat of the previous packet, which is allowed once
+    Duplicate,
+    /// The previous packet was repeated more than once
+    ExcessDuplicate,
+    /// Packets were lost between the previous packet and this one
+    Gap { expected: u8, found: u8, missing: u8 },
+    /// A late packet filling part of the most recent gap
+    OutOfOrder { expected: u8, found: u8 },
+    /// An adaptation-field-only packet changed the counter
+    Unexpected { expected: u8, found: u8 },
+    /// The counter jumped on a packet with the discontinuity indicator set
+    Reset { expected: u8, found: u8 },
+}
+
+impl ContinuityEvent {
+    /// Is this a continuity error?
+    pub fn is_error(self) -> bool {
+        matches!(
+            self,
+            ContinuityEvent::ExcessDuplicate
+                | ContinuityEvent::Gap { .. }
+                | ContinuityEvent::OutOfOrder { .. }
+                | ContinuityEvent::Unexpected { .. }
+        )
+    }
+}
+
+/// What a `ContinuityChecker` remembers about one PID.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ContinuityState {
+    seen: bool,
+    duplicated: bool,
+    last: u8,
+    missing_start: u8,
+    missing_count: u8,
+}
+
+impl ContinuityState {
+    /// The state of a PID with no packets seen
+    pub const UNSEEN: ContinuityState = ContinuityState {
+        seen: false,
+        duplicated: false,
+        last: 0,
+        missing_start: 0,
+        missing_count: 0,
+    };
+}
+
+impl Default for ContinuityState {
+    fn default() -> Self {
+        ContinuityState::UNSEEN
+    }
+}
+
+/// Follows the continuity counter of every PID according to ISO-13818-1,
+/// keeping its state in storage the caller provides.
+pub struct ContinuityChecker<'a> {
+    pids: &'a mut [ContinuityState; PID_COUNT],
+}
+
+impl<'a> ContinuityChecker<'a> {
+    /// Make a checker that has seen no packets, clearing `pids` for its state
+    pub fn new(pids: &'a mut [ContinuityState; PID_COUNT]) -> Self {
+        let mut checker = ContinuityChecker { pids };
+        checker.reset();
+        checker
+    }
+
+    /// Forget everything seen so far
+    pub fn reset(&mut self) {
+        self.pids.fill(ContinuityState::UNSEEN);
+    }
+
+    /// Forget everything seen so far on one PID
+    pub fn forget(&mut self, pid: u16) {
+        self.pids[(pid & MAX_PID) as usize] = ContinuityState::UNSEEN;
+    }
+
+    /// Get the last continuity counter seen on a PID
+    pub fn last_continuity_counter(&self, pid: u16) -> Option<u8> {
+        let state = &self.pids[(pid & MAX_PID) as usize];
+        if state.seen {
+            Some(state.last)
+        } else {
+            None
+        }
+    }
+
+    /// Check the next packet in the stream
+    pub fn check(&mut self, pkt: &Packet) -> ContinuityEvent {
+        let pid = packet::pid(pkt);
+        if pid == NULL_PACKET_PID || packet::has_transport_error(pkt) {
+            return ContinuityEvent::Ignored;
+        }
+        let found = packet::continuity_counter(pkt);
+        let has_payload = packet::has_payload(pkt);
+        let discontinuity = packet::try_has_discontinuity(pkt).unwrap_or(false);
+        let state = &mut self.pids[pid as usize];
+
+        if !state.seen {
+            *state = ContinuityState {
+                seen: true,
+                last: found,
+                ..ContinuityState::UNSEEN
+            };
+            return ContinuityEvent::First;
+        }
+
+        let expected = if has_payload {
+            (state.last + 1) & 0x0f
+        } else {
+            state.last
+        };
+        if found == expected {
+            state.last = found;
+            state.duplicated = false;
+            state.missing_count = 0;
+            return ContinuityEvent::Ok;
+        }
+        if discontinuity {
+            *state = ContinuityState {
+                seen: true,
+                last: found,
+                ..ContinuityState::UNSEEN
+            };
+            return ContinuityEvent::Reset { expected, found };
+        }
+        if !has_payload {
+            return ContinuityEvent::Unexpected { expected, found };
+        }
+        if found == state.last {
+            if state.duplicated {
+                return ContinuityEvent::ExcessDuplicate;
+            }
+            state.duplicated = true;
+            state.missing_count = 0;
+            return ContinuityEvent::Duplicate;
+        }
+        // late packets are only expected straight after the gap they fill
+        let late = found.wrapping_sub(state.missing_start) & 0x0f;
+        if late < state.missing_count {
+            return ContinuityEvent::OutOfOrder { expected, found };
+        }
+        let missing = found.wrapping_sub(expected) & 0x0f;
+        state.missing_start = expected;
+        state.missing_count = missing;
+        state.last = found;
+        state.duplicated = false;
+        ContinuityEvent::Gap {
+            expected,
+            found,
+            missing,
+        }
+    }
+}
+
+/// Lists only the PIDs seen so far
+impl<'a> fmt::Debug for ContinuityChecker<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ContinuityChecker")
+            .field("pids", &ChangedPids(&*self.pids, ContinuityState::UNSEEN))
+            .finish()
+    }
+}
+
+/// What a `ContinuityStamper` remembers about one PID.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StampState {
+    last: u8,
+    force_discontinuity: bool,
+}
+
+impl StampState {
+    /// The state of a PID whose next packet gets counter 0
+    pub const INITIAL: StampState = StampState {
+        last: 0x0f,
+        force_discontinuity: false,
+    };
+}
+
+impl Default for StampState {
+    fn default() -> Self {
+        StampState::INITIAL
+    }
+}
+
+/// Rewrites continuity counters on generated or remultiplexed output so that
+/// every PID counts up from zero, keeping its state in storage the caller
+/// provides.
+pub struct ContinuityStamper<'a> {
+    pids: &'a mut [StampState; PID_COUNT],
+    preserve_discontinuities: bool,
+}
+
+impl<'a> ContinuityStamper<'a> {
+    /// Make a stamper that starts every PID at zero, clearing `pids` for its
+    /// state
+    pub fn new(pids: &'a mut [StampState; PID_COUNT]) -> Self {
+        let mut stamper = ContinuityStamper {
+            pids,
+            preserve_discontinuities: false,
+        };
+        stamper.reset();
+        stamper
+    }
+
+    /// Keep the input counter on packets with the discontinuity indicator set,
+    /// and count on from it
+    pub fn preserve_discontinuities(mut self, preserve: bool) -> Self {
+        self.preserve_discontinuities = preserve;
+        self
+    }
+
+    /// Set the discontinuity indicator on the next packet of a PID that has
+    /// room for the adaptation field flags
+    pub fn force_discontinuity(&mut self, pid: u16) {
+        self.pids[(pid & MAX_PID) as usize].force_discontinuity = true;
+    }
+
+    /// Start every PID at zero again
+    pub fn reset(&mut self) {
+        self.pids.fill(StampState::INITIAL);
+    }
+
+    /// Start one PID at zero again
+    pub fn forget(&mut self, pid: u16) {
+        self.pids[(pid & MAX_PID) as usize] = StampState::INITIAL;
+    }
+
+    /// Stamp the next packet in the stream, returning the counter written
+    pub fn stamp(&mut self, pkt: &mut Packet) -> u8 {
+        let state = &mut self.pids[packet::pid(pkt) as usize];
+        let has_flags = packet::try_adaptation_field(pkt).is_ok_and(|len| len > 0);
+        if state.force_discontinuity && has_flags {
+            packet::set_discontinuity(pkt);
+            state.force_discontinuity = false;
+        }
+        let cc = if self.preserve_discontinuities && has_flags && packet::has_discontinuity(pkt) {
+            packet::continuity_counter(pkt)
+        } else if packet::has_payload(pkt) {
+            (state.last + 1) & 0x0f
+        } else {
+            state.last
+        };
+        state.last = cc;
+        packet::set_continuity_counter(pkt, cc);
+        cc
+    }
+
+    /// Stamp the next packet in the stream, returning the counter written
+    pub fn stamp_info(&mut self, info: &mut PacketInfo) -> u8 {
+        self.stamp(info.as_packet_mut())
+    }
+}
+
+/// Lists only the PIDs stamped so far
+impl<'a> fmt::Debug for ContinuityStamper<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ContinuityStamper")
+            .field("pids", &ChangedPids(&*self.pids, StampState::INITIAL))
+            .field("preserve_discontinuities", &self.preserve_discontinuities)
+            .finish()
+    }
+}
+
+/// Formats the per-PID states that differ from the initial one as a map
+struct ChangedPids<'a, T>(&'a [T; PID_COUNT], T);
+
+impl<'a, T: fmt::Debug + PartialEq> fmt::Debug for ChangedPids<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let changed = self.0.iter().enumerate().filter(|(_, state)| **state != self.1);
+        f.debug_map().entries(changed).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload_packet(pid: u16, cc: u8) -> Packet {
+        let mut pkt = packet::null_packet();
+        packet::set_pid(&mut pkt, pid);
+        packet::set_continuity_counter(&mut pkt, cc);
+        pkt
+    }
+
+    fn adaptation_packet(pid: u16, cc: u8) -> Packet {
+        let mut pkt = payload_packet(pid, cc);
+        pkt[3] &= !0x10;
+        packet::set_adaptation_field(&mut pkt, packet::MAX_ADAPTATION_FIELD_LENGTH);
+        pkt
+    }
+
+    #[test]
+    fn test_sequence_with_wrap() {
+        let mut pids = [ContinuityState::UNSEEN; PID_COUNT];
+        let mut checker = ContinuityChecker::new(&mut pids);
+        assert_eq!(checker.check(&payload_packet(0x100, 14)), ContinuityEvent::First);
+        assert_eq!(checker.check(&payload_packet(0x100, 15)), ContinuityEvent::Ok);
+        assert_eq!(checker.check(&payload_packet(0x100, 0)), ContinuityEvent::Ok);
+        assert_eq!(checker.last_continuity_counter(0x100), Some(0));
+        assert_eq!(checker.last_continuity_counter(0x101), None);
+    }
+
+    #[test]
+    fn test_pids_independent() {
+        let mut pids = [ContinuityState::UNSEEN; PID_COUNT];
+        let mut checker = ContinuityChecker::new(&mut pids);
+        checker.check(&payload_packet(0x100, 0));
+        assert_eq!(checker.check(&payload_packet(0x200, 7)), ContinuityEvent::First);
+        assert_eq!(checker.check(&payload_packet(0x100, 1)), ContinuityEvent::Ok);
+        assert_eq!(checker.check(&payload_packet(0x200, 8)), ContinuityEvent::Ok);
+    }
+
+    #[test]
+    fn test_gap() {
+        let mut pids = [ContinuityState::UNSEEN; PID_COUNT];
+        let mut checker = ContinuityChecker::new(&mut pids);
+        checker.check(&payload_packet(0x100, 3));
+        let event = checker.check(&payload_packet(0x100, 7));
+        assert_eq!(
+            event,
+            ContinuityEvent::Gap {
+                expected: 4,
+                found: 7,
+                missing: 3
+            }
+        );
+        assert!(event.is_error());
+        assert_eq!(checker.check(&payload_packet(0x100, 8)), ContinuityEvent::Ok);
+    }
+
+    #[test]
+    fn test_duplicates() {
+        let mut pids = [ContinuityState::UNSEEN; PID_COUNT];
+        let mut checker = ContinuityChecker::new(&mut pids);
+        checker.check(&payload_packet(0x100, 3));
+        assert_eq!(checker.check(&payload_packet(0x100, 3)), ContinuityEvent::Duplicate);
+        assert!(!ContinuityEvent::Duplicate.is_error());
+        assert_eq!(checker.check(&payload_packet(0x100, 3)), ContinuityEvent::ExcessDuplicate);
+        assert_eq!(checker.check(&payload_packet(0x100, 4)), ContinuityEvent::Ok);
+        assert_eq!(checker.check(&payload_packet(0x100, 4)), ContinuityEvent::Duplicate);
+    }
+
+    #[test]
+    fn test_out_of_order() {
+        let mut pids = [ContinuityState::UNSEEN; PID_COUNT];
+        let mut checker = ContinuityChecker::new(&mut pids);
+        checker.check(&payload_packet(0x100, 4));
+        checker.check(&payload_packet(0x100, 6));
+        assert_eq!(
+            checker.check(&payload_packet(0x100, 5)),
+            ContinuityEvent::OutOfOrder {
+                expected: 7,
+                found: 5
+            }
+        );
+        assert_eq!(checker.check(&payload_packet(0x100, 7)), ContinuityEvent::Ok);
+    }
+
+    #[test]
+    fn test_stale_gap() {
+        let mut pids = [ContinuityState::UNSEEN; PID_COUNT];
+        let mut checker = ContinuityChecker::new(&mut pids);
+        checker.check(&payload_packet(0x100, 4));
+        checker.check(&payload_packet(0x100, 6));
+        for cc in (7..16).chain(0..3) {
+            assert_eq!(checker.check(&payload_packet(0x100, cc)), ContinuityEvent::Ok);
+        }
+        // 5 was missing long ago, so this is a new gap rather than a late packet
+        assert_eq!(
+            checker.check(&payload_packet(0x100, 5)),
+            ContinuityEvent::Gap {
+                expected: 3,
+                found: 5,
+                missing: 2
+            }
+        );
+        assert_eq!(checker.check(&payload_packet(0x100, 6)), ContinuityEvent::Ok);
+
+        // a duplicate also ends the window
+        checker.check(&payload_packet(0x100, 9));
+        assert_eq!(checker.check(&payload_packet(0x100, 9)), ContinuityEvent::Duplicate);
+        assert_eq!(
+            checker.check(&payload_packet(0x100, 8)),
+            ContinuityEvent::Gap {
+                expected: 10,
+                found: 8,
+                missing: 14
+            }
+        );
+    }
+
+    #[test]
+    fn test_adaptation_only() {
+        let mut pids = [ContinuityState::UNSEEN; PID_COUNT];
+        let mut checker = ContinuityChecker::new(&mut pids);
+        checker.check(&payload_packet(0x100, 9));
+        assert_eq!(checker.check(&adaptation_packet(0x100, 9)), ContinuityEvent::Ok);
+        assert_eq!(
+            checker.check(&adaptation_packet(0x100, 10)),
+            ContinuityEvent::Unexpected {
+                expected: 9,
+                found: 10
+            }
+        );
+        assert_eq!(checker.check(&payload_packet(0x100, 10)), ContinuityEvent::Ok);
+    }
+
+    #[test]
+    fn test_discontinuity_reset() {
+        let mut pids = [ContinuityState::UNSEEN; PID_COUNT];
+        let mut checker = ContinuityChecker::new(&mut pids);
+        checker.check(&payload_packet(0x100, 9));
+        let mut pkt = payload_packet(0x100, 2);
+        packet::set_adaptation_field(&mut pkt, 1);
+        packet::set_discontinuity(&mut pkt);
+        assert_eq!(
+            checker.check(&pkt),
+            ContinuityEvent::Reset {
+                expected: 10,
+                found: 2
+            }
+        );
+        assert_eq!(checker.check(&payload_packet(0x100, 3)), ContinuityEvent::Ok);
+    }
+
+    #[test]
+    fn test_ignored() {
+        let mut pids = [ContinuityState::UNSEEN; PID_COUNT];
+        let mut checker = ContinuityChecker::new(&mut pids);
+        assert_eq!(checker.check(&packet::null_packet()), ContinuityEvent::Ignored);
+        let mut pkt = payload_packet(0x100, 1);
+        packet::set_transport_error(&mut pkt);
+        assert_eq!(checker.check(&pkt), ContinuityEvent::Ignored);
+        assert_eq!(checker.last_continuity_counter(0x100), None);
+    }
+
+    #[test]
+    fn test_forget() {
+        let mut pids = [ContinuityState::UNSEEN; PID_COUNT];
+        let mut checker = ContinuityChecker::new(&mut pids);
+        checker.check(&payload_packet(0x100, 1));
+        checker.forget(0x100);
+        assert_eq!(checker.check(&payload_packet(0x100, 9)), ContinuityEvent::First);
+        checker.reset();
+        assert_eq!(checker.check(&payload_packet(0x100, 3)), ContinuityEvent::First);
+
+        // a new checker starts from nothing, whatever the storage held
+        let mut checker = ContinuityChecker::new(&mut pids);
+        assert_eq!(checker.last_continuity_counter(0x100), None);
+        assert_eq!(checker.check(&payload_packet(0x100, 4)), ContinuityEvent::First);
+    }
+
+    #[test]
+    fn test_debug() {
+        let mut pids = [ContinuityState::UNSEEN; PID_COUNT];
+        let mut checker = ContinuityChecker::new(&mut pids);
+        assert_eq!(std::format!("{:?}", checker), "ContinuityChecker { pids: {} }");
+        checker.check(&payload_packet(0x100, 1));
+        let debug = std::format!("{:?}", checker);
+        assert!(debug.starts_with("ContinuityChecker { pids: {256: ContinuityState { seen: true,"), "{}", debug);
+
+        let mut states = [StampState::INITIAL; PID_COUNT];
+        let mut stamper = ContinuityStamper::new(&mut states);
+        stamper.stamp(&mut payload_packet(0x100, 7));
+        assert_eq!(
+            std::format!("{:?}", stamper),
+            "ContinuityStamper { pids: {256: StampState { last: 0, force_discontinuity: false }}, preserve_discontinuities: false }"
+        );
+    }
+
+    #[test]
+    fn test_stamp_sequence() {
+        let mut states = [StampState::INITIAL; PID_COUNT];
+        let mut stamper = ContinuityStamper::new(&mut states);
+        let mut pids = [ContinuityState::UNSEEN; PID_COUNT];
+        let mut checker = ContinuityChecker::new(&mut pids);
+        for i in 0..20u8 {
+            let mut pkt = payload_packet(0x100, 7);
+            assert_eq!(stamper.stamp(&mut pkt), i & 0x0f);
+            assert!(!checker.check(&pkt).is_error());
+        }
+        let mut pkt = payload_packet(0x200, 7);
+        assert_eq!(stamper.stamp(&mut pkt), 0);
+    }
+
+    #[test]
+    fn test_stamp_adaptation_only() {
+        let mut states = [StampState::INITIAL; PID_COUNT];
+        let mut stamper = ContinuityStamper::new(&mut states);
+        let mut pkt = adaptation_packet(0x100, 9);
+        assert_eq!(stamper.stamp(&mut pkt), 0x0f);
+        let mut pkt = payload_packet(0x100, 9);
+        assert_eq!(stamper.stamp(&mut pkt), 0);
+        let mut pkt = adaptation_packet(0x100, 9);
+        assert_eq!(stamper.stamp(&mut pkt), 0);
+        assert_eq!(packet::continuity_counter(&pkt), 0);
+    }
+
+    #[test]
+    fn test_stamp_discontinuity() {
+        let mut input = payload_packet(0x100, 9);
+        packet::set_adaptation_field(&mut input, 1);
+        packet::set_discontinuity(&mut input);
+
+        let mut states = [StampState::INITIAL; PID_COUNT];
+        let mut stamper = ContinuityStamper::new(&mut states);
+        let mut pkt = input;
+        assert_eq!(stamper.stamp(&mut pkt), 0);
+
+        let mut stamper = ContinuityStamper::new(&mut states).preserve_discontinuities(true);
+        let mut pkt = input;
+        assert_eq!(stamper.stamp(&mut pkt), 9);
+        let mut pkt = payload_packet(0x100, 0);
+        assert_eq!(stamper.stamp(&mut pkt), 10);
+    }
+
+    #[test]
+    fn test_stamp_force_discontinuity() {
+        let mut states = [StampState::INITIAL; PID_COUNT];
+        let mut stamper = ContinuityStamper::new(&mut states);
+        stamper.force_discontinuity(0x100);
+        let mut pkt = payload_packet(0x100, 0);
+        stamper.stamp(&mut pkt);
+        assert!(!packet::has_adaptation_field(&pkt));
+        let mut pkt = adaptation_packet(0x100, 0);
+        stamper.stamp(&mut pkt);
+        assert_eq!(packet::try_has_discontinuity(&pkt), Ok(true));
+        let mut pkt = adaptation_packet(0x100, 0);
+        stamper.stamp(&mut pkt);
+        assert_eq!(packet::try_has_discontinuity(&pkt), Ok(false));
+    }
+
+    #[test]
+    fn test_stamp_info() {
+        let mut states = [StampState::INITIAL; PID_COUNT];
+        let mut stamper = ContinuityStamper::new(&mut states);
+        let mut info = PacketInfo::from(payload_packet(0x100, 5));
+        assert_eq!(stamper.stamp_info(&mut info), 0);
+        assert_eq!(stamper.stamp_info(&mut info), 1);
+        assert_eq!(info.continuity_counter(), 1);
+        stamper.reset();
+        assert_eq!(stamper.stamp_info(&mut info), 0);
+    }
+}
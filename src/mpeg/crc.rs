@@ -0,0 +1,116 @@
+use super::packet::SECTION_MAX_SIZE;
+use super::section::{section_total_length, SECTION_HEADER_SIZE};
+use crate::error::TsError;
+
+/// Size of the CRC_32 at the end of a long-form section
+pub const CRC_SIZE: usize = 4;
+
+// CRC-32/MPEG-2: x^32 + x^26 + x^23 + x^22 + x^16 + x^12 + x^11 + x^10 + x^8
+// + x^7 + x^5 + x^4 + x^2 + x + 1, not reflected, initial value all ones
+const CRC_POLY: u32 = 0x04c1_1db7;
+const CRC_INIT: u32 = 0xffff_ffff;
+
+const fn crc_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = (i as u32) << 24;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x8000_0000 != 0 {
+                (crc << 1) ^ CRC_POLY
+            } else {
+                crc << 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+const CRC_TABLE: [u32; 256] = crc_table();
+
+/// Continue a CRC-32/MPEG-2 over more data
+pub fn crc32_update(crc: u32, data: &[u8]) -> u32 {
+    data.iter().fold(crc, |crc, &byte| {
+        (crc << 8) ^ CRC_TABLE[((crc >> 24) as u8 ^ byte) as usize]
+    })
+}
+
+/// Compute the CRC-32/MPEG-2 of some data
+pub fn crc32(data: &[u8]) -> u32 {
+    crc32_update(CRC_INIT, data)
+}
+
+/// Check the length and CRC_32 of a complete long-form section
+pub fn verify_section(section: &[u8]) -> Result<(), TsError> {
+    if section.len() < SECTION_HEADER_SIZE + CRC_SIZE {
+        return Err(TsError::BadLength(section.len()));
+    }
+    if section_total_length(section) != section.len() {
+        return Err(TsError::BadLength(section.len()));
+    }
+    let end = section.len() - CRC_SIZE;
+    let expected = crc32(&section[..end]);
+    let found = u32::from_be_bytes([section[end], section[end + 1], section[end + 2], section[end + 3]]);
+    if expected != found {
+        return Err(TsError::BadCrc { expected, found });
+    }
+    Ok(())
+}
+
+/// Set the section_length to fit the buffer and write the CRC_32 into its
+/// last four bytes
+pub fn finalize_section(section: &mut [u8]) -> Result<(), TsError> {
+    let len = section.len();
+    if !(SECTION_HEADER_SIZE + CRC_SIZE..=SECTION_MAX_SIZE).contains(&len) {
+        return Err(TsError::BadLength(len));
+    }
+    let section_length = len - SECTION_HEADER_SIZE;
+    section[1] = (section[1] & 0xf0) | (section_length >> 8) as u8;
+    section[2] = section_length as u8;
+    let end = len - CRC_SIZE;
+    let crc = crc32(&section[..end]);
+    section[end..].copy_from_slice(&crc.to_be_bytes());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_value() {
+        assert_eq!(crc32(b"123456789"), 0x0376_e6e7);
+        assert_eq!(crc32(&[]), CRC_INIT);
+        assert_eq!(crc32_update(crc32(b"1234"), b"56789"), 0x0376_e6e7);
+    }
+
+    #[test]
+    fn test_known_pat() {
+        // PAT: transport_stream_id 1, program 1 on PMT PID 0x1000
+        let pat = [
+            0x00, 0xb0, 0x0d, 0x00, 0x01, 0xc1, 0x00, 0x00, 0x00, 0x01, 0xf0, 0x00, 0x2a, 0xb1, 0x04, 0xb2,
+        ];
+        assert_eq!(verify_section(&pat), Ok(()));
+        assert_eq!(crc32(&pat), 0);
+    }
+
+    #[test]
+    fn test_finalize_and_verify() {
+        let mut section = [0u8; 20];
+        section[0] = 0x02;
+        section[1] = 0xb0;
+        section[5] = 0xc1;
+        finalize_section(&mut section).unwrap();
+        assert_eq!(section[1], 0xb0);
+        assert_eq!(section[2], 17);
+        assert_eq!(verify_section(&section), Ok(()));
+        section[7] ^= 0x01;
+        assert!(matches!(verify_section(&section), Err(TsError::BadCrc { .. })));
+        assert_eq!(verify_section(&section[..19]), Err(TsError::BadLength(19)));
+        assert_eq!(finalize_section(&mut section[..6]), Err(TsError::BadLength(6)));
+    }
+}
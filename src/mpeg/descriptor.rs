@@ -0,0 +1,127 @@
+use crate::error::TsError;
+
+/// Bytes before a descriptor's data: descriptor_tag and descriptor_length
+pub const DESCRIPTOR_HEADER_SIZE: usize = 2;
+/// Largest descriptor data
+pub const DESCRIPTOR_MAX_DATA: usize = 255;
+
+/// A borrowed view of one descriptor from a descriptor loop.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Descriptor<'a> {
+    tag: u8,
+    data: &'a [u8],
+}
+
+impl<'a> Descriptor<'a> {
+    /// Get the descriptor_tag
+    pub fn tag(&self) -> u8 {
+        self.tag
+    }
+
+    /// Get the bytes after descriptor_length
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+}
+
+/// Iterator over the descriptors of a loop whose lengths have been checked.
+#[derive(Clone, Copy, Debug)]
+pub struct Descriptors<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Descriptors<'a> {
+    /// Check every descriptor in the loop fits, then iterate over them
+    pub fn new(bytes: &'a [u8]) -> Result<Self, TsError> {
+        let mut rest = bytes;
+        while !rest.is_empty() {
+            if rest.len() < DESCRIPTOR_HEADER_SIZE {
+                return Err(TsError::TruncatedDescriptor(rest[0]));
+            }
+            let end = DESCRIPTOR_HEADER_SIZE + rest[1] as usize;
+            if end > rest.len() {
+                return Err(TsError::TruncatedDescriptor(rest[0]));
+            }
+            rest = &rest[end..];
+        }
+        Ok(Descriptors { bytes })
+    }
+
+    /// Iterate over a loop already checked by `new`
+    pub(crate) fn new_unchecked(bytes: &'a [u8]) -> Self {
+        Descriptors { bytes }
+    }
+
+    /// Find the first descriptor with the given tag
+    pub fn find_tag(mut self, tag: u8) -> Option<Descriptor<'a>> {
+        Iterator::find(&mut self, |d| d.tag == tag)
+    }
+}
+
+impl<'a> Iterator for Descriptors<'a> {
+    type Item = Descriptor<'a>;
+
+    fn next(&mut self) -> Option<Descriptor<'a>> {
+        if self.bytes.len() < DESCRIPTOR_HEADER_SIZE {
+            return None;
+        }
+        let end = (DESCRIPTOR_HEADER_SIZE + self.bytes[1] as usize).min(self.bytes.len());
+        let descriptor = Descriptor {
+            tag: self.bytes[0],
+            data: &self.bytes[DESCRIPTOR_HEADER_SIZE..end],
+        };
+        self.bytes = &self.bytes[end..];
+        Some(descriptor)
+    }
+}
+
+/// Write a descriptor to the start of `out`, returning its length
+pub fn write_descriptor(out: &mut [u8], tag: u8, data: &[u8]) -> Result<usize, TsError> {
+    if data.len() > DESCRIPTOR_MAX_DATA {
+        return Err(TsError::BadLength(data.len()));
+    }
+    let size = DESCRIPTOR_HEADER_SIZE + data.len();
+    if size > out.len() {
+        return Err(TsError::BadLength(out.len()));
+    }
+    out[0] = tag;
+    out[1] = data.len() as u8;
+    out[DESCRIPTOR_HEADER_SIZE..size].copy_from_slice(data);
+    Ok(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_iterate() {
+        let bytes = [0x0a, 0x04, b'e', b'n', b'g', 0x00, 0x52, 0x01, 0x07, 0x09, 0x00];
+        let mut descriptors = Descriptors::new(&bytes).unwrap();
+        let language = descriptors.next().unwrap();
+        assert_eq!(language.tag(), 0x0a);
+        assert_eq!(language.data(), b"eng\0");
+        assert_eq!(descriptors.next().unwrap().data(), &[0x07]);
+        assert!(descriptors.next().unwrap().data().is_empty());
+        assert!(descriptors.next().is_none());
+        assert_eq!(Descriptors::new(&bytes).unwrap().find_tag(0x52).unwrap().data(), &[0x07]);
+        assert!(Descriptors::new(&bytes).unwrap().find_tag(0x05).is_none());
+        assert_eq!(Descriptors::new(&[]).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_truncated() {
+        assert_eq!(Descriptors::new(&[0x0a, 0x04, 1, 2, 3]).err(), Some(TsError::TruncatedDescriptor(0x0a)));
+        assert_eq!(Descriptors::new(&[0x52, 0x01, 0x07, 0x09]).err(), Some(TsError::TruncatedDescriptor(0x09)));
+    }
+
+    #[test]
+    fn test_write() {
+        let mut out = [0u8; 8];
+        assert_eq!(write_descriptor(&mut out, 0x52, &[0x07]), Ok(3));
+        assert_eq!(&out[..3], &[0x52, 0x01, 0x07]);
+        assert_eq!(write_descriptor(&mut out, 0x0a, &[0; 7]), Err(TsError::BadLength(8)));
+        assert_eq!(write_descriptor(&mut [0u8; 300], 0x0a, &[0; 256]), Err(TsError::BadLength(256)));
+    }
+}
@@ -0,0 +1,270 @@
+use core::fmt;
+
+use super::packet::{self, Packet, ScramblingControl};
+use super::pcr::Pcr;
+
+const HEXDUMP_WIDTH: usize = 16;
+
+/// Formats a packet: `Display` gives a one-line summary and `Debug` the
+/// decoded header and adaptation field.
+#[derive(Clone, Copy)]
+pub struct PacketDisplay<'a> {
+    packet: &'a Packet,
+}
+
+impl<'a> PacketDisplay<'a> {
+    /// Format the given packet
+    pub fn new(packet: &'a Packet) -> Self {
+        PacketDisplay { packet }
+    }
+}
+
+impl<'a> fmt::Display for PacketDisplay<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_summary(self.packet, f)
+    }
+}
+
+impl<'a> fmt::Debug for PacketDisplay<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_debug("Packet", self.packet, f)
+    }
+}
+
+/// Formats bytes as a hexdump: offset, 16 hex bytes and their ASCII.
+#[derive(Clone, Copy)]
+pub struct Hexdump<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Hexdump<'a> {
+    /// Format the given bytes
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Hexdump { bytes }
+    }
+}
+
+impl<'a> fmt::Display for Hexdump<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (line, chunk) in self.bytes.chunks(HEXDUMP_WIDTH).enumerate() {
+            if line > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{:04x}: ", line * HEXDUMP_WIDTH)?;
+            for i in 0..HEXDUMP_WIDTH {
+                match chunk.get(i) {
+                    Some(byte) => write!(f, "{:02x} ", byte)?,
+                    None => write!(f, "   ")?,
+                }
+            }
+            write!(f, "|")?;
+            for &byte in chunk {
+                let c = if byte.is_ascii_graphic() || byte == b' ' { byte as char } else { '.' };
+                write!(f, "{}", c)?;
+            }
+            write!(f, "|")?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> fmt::Debug for Hexdump<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+/// Write a PCR as seconds with microsecond precision
+struct Seconds(Pcr);
+
+impl fmt::Display for Seconds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ticks = self.0.as_27mhz();
+        let rate = packet::PCR_RATE;
+        write!(f, "{}.{:06}s", ticks / rate, (ticks % rate) / (rate / 1_000_000))
+    }
+}
+
+impl fmt::Debug for Seconds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+/// Write the one-line summary of a packet
+pub(crate) fn fmt_summary(pkt: &Packet, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "PID 0x{:04x} CC {:2}", packet::pid(pkt), packet::continuity_counter(pkt))?;
+    if packet::has_transport_error(pkt) {
+        write!(f, " TEI")?;
+    }
+    if packet::has_unit_start(pkt) {
+        write!(f, " PUSI")?;
+    }
+    if packet::has_transport_priority(pkt) {
+        write!(f, " PRIO")?;
+    }
+    match packet::scrambling_control(pkt) {
+        ScramblingControl::Clear => {}
+        ScramblingControl::Reserved => write!(f, " SC reserved")?,
+        ScramblingControl::Even => write!(f, " SC even")?,
+        ScramblingControl::Odd => write!(f, " SC odd")?,
+    }
+    if packet::has_adaptation_field(pkt) {
+        match packet::try_adaptation_field(pkt) {
+            Ok(length) => write!(f, " AF {}", length)?,
+            Err(_) => return write!(f, " AF invalid {}", packet::adaptation_field(pkt)),
+        }
+        if packet::try_has_discontinuity(pkt).unwrap_or(false) {
+            write!(f, " DI")?;
+        }
+        if packet::try_has_random_access(pkt).unwrap_or(false) {
+            write!(f, " RAI")?;
+        }
+        if let Ok(Some(pcr)) = packet::try_pcr_clock(pkt) {
+            write!(f, " PCR {}", Seconds(pcr))?;
+        }
+    }
+    if let Ok(Some(payload)) = packet::try_payload(pkt) {
+        write!(f, " payload {}", payload.len())?;
+    }
+    Ok(())
+}
+
+/// Write the decoded fields of a packet as a struct named `name`
+pub(crate) fn fmt_debug(name: &str, pkt: &Packet, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let mut s = f.debug_struct(name);
+    s.field("pid", &packet::pid(pkt))
+        .field("transport_error", &packet::has_transport_error(pkt))
+        .field("unit_start", &packet::has_unit_start(pkt))
+        .field("transport_priority", &packet::has_transport_priority(pkt))
+        .field("scrambling", &packet::scrambling_control(pkt))
+        .field("continuity_counter", &packet::continuity_counter(pkt));
+    if packet::has_adaptation_field(pkt) {
+        s.field("adaptation_field", &AdaptationFieldDebug(pkt));
+    }
+    match packet::try_payload(pkt) {
+        Ok(Some(payload)) => s.field("payload_length", &payload.len()),
+        _ => s.field("payload_length", &0),
+    };
+    s.finish()
+}
+
+struct AdaptationFieldDebug<'a>(&'a Packet);
+
+impl<'a> fmt::Debug for AdaptationFieldDebug<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pkt = self.0;
+        let length = match packet::try_adaptation_field(pkt) {
+            Ok(length) => length,
+            Err(e) => return write!(f, "Err({})", e),
+        };
+        let mut s = f.debug_struct("AdaptationField");
+        s.field("length", &length);
+        if length == 0 {
+            return s.finish();
+        }
+        s.field("discontinuity", &packet::has_discontinuity(pkt))
+            .field("random_access", &packet::has_random_access(pkt))
+            .field("stream_priority", &packet::has_stream_priority(pkt));
+        if let Ok(Some(pcr)) = packet::try_pcr_clock(pkt) {
+            s.field("pcr", &Seconds(pcr));
+        }
+        if let Ok(Some(opcr)) = packet::try_opcr_clock(pkt) {
+            s.field("opcr", &Seconds(opcr));
+        }
+        if let Ok(Some(countdown)) = packet::try_splice_countdown(pkt) {
+            s.field("splice_countdown", &countdown);
+        }
+        if let Ok(Some(data)) = packet::try_transport_private_data(pkt) {
+            s.field("private_data_length", &data.len());
+        }
+        if packet::has_adaptation_field_extension(pkt) {
+            if let Ok(Some(offset)) = packet::try_ltw_offset(pkt) {
+                s.field("ltw_valid", &packet::ltw_valid(pkt).unwrap_or(false))
+                    .field("ltw_offset", &offset);
+            }
+            if let Ok(Some(rate)) = packet::try_piecewise_rate(pkt) {
+                s.field("piecewise_rate", &rate);
+            }
+            if let Ok(Some(splice_type)) = packet::try_splice_type(pkt) {
+                s.field("splice_type", &splice_type)
+                    .field("dts_next_au", &packet::dts_next_au(pkt).unwrap_or(0));
+            }
+        }
+        if let Err(e) = packet::validate(pkt) {
+            s.field("error", &e);
+        }
+        s.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::format;
+
+    fn pcr_packet() -> Packet {
+        let mut pkt = packet::null_packet();
+        packet::set_pid(&mut pkt, 0x100);
+        packet::set_unit_start(&mut pkt);
+        packet::set_continuity_counter(&mut pkt, 7);
+        packet::set_adaptation_field(&mut pkt, 7);
+        packet::set_random_access(&mut pkt);
+        packet::set_pcr_clock(&mut pkt, Pcr::from_27mhz(27_000_000 * 3 + 13_500_000));
+        pkt
+    }
+
+    #[test]
+    fn test_summary() {
+        let pkt = pcr_packet();
+        assert_eq!(
+            format!("{}", PacketDisplay::new(&pkt)),
+            "PID 0x0100 CC  7 PUSI AF 7 RAI PCR 3.500000s payload 176"
+        );
+        let pkt = packet::null_packet();
+        assert_eq!(format!("{}", PacketDisplay::new(&pkt)), "PID 0x1fff CC  0 payload 184");
+    }
+
+    #[test]
+    fn test_summary_bad_adaptation_field() {
+        let mut pkt = packet::null_packet();
+        pkt[3] |= 0x20;
+        pkt[4] = 200;
+        assert_eq!(format!("{}", PacketDisplay::new(&pkt)), "PID 0x1fff CC  0 AF invalid 200");
+    }
+
+    #[test]
+    fn test_debug() {
+        let pkt = pcr_packet();
+        let text = format!("{:?}", PacketDisplay::new(&pkt));
+        assert!(text.starts_with("Packet { pid: 256, transport_error: false, unit_start: true"));
+        assert!(text.contains("adaptation_field: AdaptationField { length: 7, discontinuity: false, random_access: true"));
+        assert!(text.contains("pcr: 3.500000s"));
+        assert!(text.ends_with("payload_length: 176 }"));
+        let text = format!("{:?}", PacketDisplay::new(&packet::null_packet()));
+        assert!(!text.contains("adaptation_field"));
+    }
+
+    #[test]
+    fn test_debug_truncated_extension() {
+        // the extension length byte is the last in the packet, with no flags
+        let mut pkt = packet::null_packet();
+        packet::set_adaptation_field(&mut pkt, 183);
+        pkt[5] = 0x03;
+        pkt[6] = 180;
+        pkt[packet::PACKET_SIZE - 1] = 0;
+        let text = format!("{:?}", PacketDisplay::new(&pkt));
+        assert!(text.contains("adaptation_field"));
+    }
+
+    #[test]
+    fn test_hexdump() {
+        let text = format!("{}", Hexdump::new(b"GA\x00\x10 0123456789abcdefXY"));
+        assert_eq!(
+            text,
+            "0000: 47 41 00 10 20 30 31 32 33 34 35 36 37 38 39 61 |GA.. 0123456789a|\n\
+             0010: 62 63 64 65 66 58 59                            |bcdefXY|"
+        );
+        assert_eq!(format!("{}", Hexdump::new(&[])), "");
+    }
+}
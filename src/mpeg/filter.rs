@@ -0,0 +1,256 @@
+use core::convert::TryFrom;
+use core::fmt;
+use core::iter::FromIterator;
+use core::ops::RangeInclusive;
+
+use super::packet::{self, Packet, MAX_PID, PACKET_SIZE};
+
+const WORD_COUNT: usize = (MAX_PID as usize + 1) / 32;
+
+/// What to do with packets whose PID is in the filter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FilterMode {
+    /// Pass only packets whose PID is in the set
+    Keep,
+    /// Pass only packets whose PID is not in the set
+    Drop,
+}
+
+/// A set of PIDs, stored as 256 32-bit words, for filtering packets.
+#[derive(Clone, PartialEq, Eq)]
+pub struct PidFilter {
+    words: [u32; WORD_COUNT],
+    mode: FilterMode,
+    substitute_nulls: bool,
+}
+
+impl PidFilter {
+    /// Make a filter with no PIDs that keeps matching packets
+    pub fn new() -> Self {
+        PidFilter {
+            words: [0; WORD_COUNT],
+            mode: FilterMode::Keep,
+            substitute_nulls: false,
+        }
+    }
+
+    /// Make a filter holding the given PIDs
+    pub fn from_pids(pids: &[u16]) -> Self {
+        pids.iter().copied().collect()
+    }
+
+    /// Make a filter holding a range of PIDs
+    pub fn from_range(range: RangeInclusive<u16>) -> Self {
+        let mut filter = PidFilter::new();
+        filter.insert_range(range);
+        filter
+    }
+
+    /// Set whether PIDs in the set are kept or dropped
+    pub fn mode(mut self, mode: FilterMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Replace rejected packets with null packets instead of removing them,
+    /// keeping the bitrate constant
+    pub fn substitute_nulls(mut self, substitute: bool) -> Self {
+        self.substitute_nulls = substitute;
+        self
+    }
+
+    /// Add a PID to the set
+    pub fn insert(&mut self, pid: u16) {
+        let pid = (pid & MAX_PID) as usize;
+        self.words[pid / 32] |= 1 << (pid % 32);
+    }
+
+    /// Add a range of PIDs to the set
+    pub fn insert_range(&mut self, range: RangeInclusive<u16>) {
+        let start = *range.start();
+        let end = (*range.end()).min(MAX_PID);
+        for pid in start..=end {
+            self.insert(pid);
+        }
+    }
+
+    /// Remove a PID from the set
+    pub fn remove(&mut self, pid: u16) {
+        let pid = (pid & MAX_PID) as usize;
+        self.words[pid / 32] &= !(1 << (pid % 32));
+    }
+
+    /// Remove every PID from the set
+    pub fn clear(&mut self) {
+        self.words = [0; WORD_COUNT];
+    }
+
+    /// Is the PID in the set?
+    pub fn contains(&self, pid: u16) -> bool {
+        let pid = (pid & MAX_PID) as usize;
+        self.words[pid / 32] & (1 << (pid % 32)) != 0
+    }
+
+    /// Get the number of PIDs in the set
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    /// Is the set empty?
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&word| word == 0)
+    }
+
+    fn pids(&self) -> impl Iterator<Item = u16> + '_ {
+        (0..=MAX_PID).filter(move |&pid| self.contains(pid))
+    }
+
+    /// Does the packet pass the filter?
+    pub fn matches(&self, pkt: &Packet) -> bool {
+        self.contains(packet::pid(pkt)) == (self.mode == FilterMode::Keep)
+    }
+
+    /// Filter the whole packets at the start of `buffer` in place.
+    ///
+    /// Passing packets are moved to the front, or rejected packets are
+    /// overwritten with null packets when substituting. Returns the number of
+    /// bytes of output; a trailing partial packet is ignored.
+    pub fn apply(&self, buffer: &mut [u8]) -> usize {
+        let count = buffer.len() / PACKET_SIZE;
+        let mut out = 0;
+        for i in 0..count {
+            let start = i * PACKET_SIZE;
+            let slot = &mut buffer[start..start + PACKET_SIZE];
+            let pkt = <&mut Packet>::try_from(slot).unwrap();
+            if self.matches(pkt) {
+                if out != start {
+                    buffer.copy_within(start..start + PACKET_SIZE, out);
+                }
+            } else if self.substitute_nulls {
+                *pkt = packet::null_packet();
+            } else {
+                continue;
+            }
+            out += PACKET_SIZE;
+        }
+        out
+    }
+}
+
+impl FromIterator<u16> for PidFilter {
+    fn from_iter<I: IntoIterator<Item = u16>>(iter: I) -> Self {
+        let mut filter = PidFilter::new();
+        for pid in iter {
+            filter.insert(pid);
+        }
+        filter
+    }
+}
+
+/// Lists the PIDs in the set rather than the words holding them
+impl fmt::Debug for PidFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        struct Pids<'a>(&'a PidFilter);
+
+        impl<'a> fmt::Debug for Pids<'a> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_set().entries(self.0.pids()).finish()
+            }
+        }
+
+        f.debug_struct("PidFilter")
+            .field("pids", &Pids(self))
+            .field("mode", &self.mode)
+            .field("substitute_nulls", &self.substitute_nulls)
+            .finish()
+    }
+}
+
+impl Default for PidFilter {
+    fn default() -> Self {
+        PidFilter::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buffer(pids: &[u16]) -> [u8; PACKET_SIZE * 4 + 7] {
+        let mut buf = [0xaa; PACKET_SIZE * 4 + 7];
+        for (chunk, &pid) in buf.chunks_exact_mut(PACKET_SIZE).zip(pids) {
+            let mut pkt = packet::null_packet();
+            packet::set_pid(&mut pkt, pid);
+            packet::set_continuity_counter(&mut pkt, 5);
+            chunk.copy_from_slice(&pkt);
+        }
+        buf
+    }
+
+    fn pid_at(buf: &[u8], index: usize) -> u16 {
+        let start = index * PACKET_SIZE;
+        packet::pid(<&Packet>::try_from(&buf[start..start + PACKET_SIZE]).unwrap())
+    }
+
+    #[test]
+    fn test_set() {
+        let mut filter = PidFilter::from_pids(&[0, 31, 32, MAX_PID]);
+        assert!(filter.contains(0));
+        assert!(filter.contains(31));
+        assert!(filter.contains(32));
+        assert!(filter.contains(MAX_PID));
+        assert!(!filter.contains(33));
+        assert_eq!(filter.len(), 4);
+        assert_eq!(
+            std::format!("{:?}", filter),
+            "PidFilter { pids: {0, 31, 32, 8191}, mode: Keep, substitute_nulls: false }"
+        );
+        filter.remove(31);
+        assert!(!filter.contains(31));
+        filter.clear();
+        assert!(filter.is_empty());
+    }
+
+    #[test]
+    fn test_range() {
+        let filter = PidFilter::from_range(0x100..=0x10f);
+        assert_eq!(filter.len(), 16);
+        assert!(filter.contains(0x100));
+        assert!(filter.contains(0x10f));
+        assert!(!filter.contains(0x110));
+        assert_eq!(PidFilter::from_range(0x1ff0..=0xffff).len(), 16);
+    }
+
+    #[test]
+    fn test_apply_keep() {
+        let mut buf = buffer(&[0x100, 0x200, 0x101, 0x100]);
+        let filter = PidFilter::from_range(0x100..=0x101);
+        assert_eq!(filter.apply(&mut buf), PACKET_SIZE * 3);
+        assert_eq!(pid_at(&buf, 0), 0x100);
+        assert_eq!(pid_at(&buf, 1), 0x101);
+        assert_eq!(pid_at(&buf, 2), 0x100);
+        assert_eq!(buf[PACKET_SIZE * 4], 0xaa);
+    }
+
+    #[test]
+    fn test_apply_drop() {
+        let mut buf = buffer(&[0x100, 0x200, 0x101, 0x200]);
+        let filter = PidFilter::from_pids(&[0x100, 0x101]).mode(FilterMode::Drop);
+        assert_eq!(filter.apply(&mut buf), PACKET_SIZE * 2);
+        assert_eq!(pid_at(&buf, 0), 0x200);
+        assert_eq!(pid_at(&buf, 1), 0x200);
+    }
+
+    #[test]
+    fn test_apply_substitute_nulls() {
+        let mut buf = buffer(&[0x100, 0x200, 0x101, 0x200]);
+        let filter = PidFilter::from_pids(&[0x200]).substitute_nulls(true);
+        assert_eq!(filter.apply(&mut buf), PACKET_SIZE * 4);
+        assert_eq!(pid_at(&buf, 0), packet::NULL_PACKET_PID);
+        assert_eq!(pid_at(&buf, 1), 0x200);
+        assert_eq!(pid_at(&buf, 2), packet::NULL_PACKET_PID);
+        assert_eq!(pid_at(&buf, 3), 0x200);
+        assert_eq!(buf[PACKET_SIZE + 3] & 0x0f, 5);
+    }
+}
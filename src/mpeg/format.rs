@@ -0,0 +1,276 @@
+use core::convert::TryFrom;
+
+use super::packet::{Packet, PACKET_SIZE, SYNC_BYTE};
+use super::reed_solomon::rs_encode;
+use crate::error::TsError;
+
+pub const TP_EXTRA_HEADER_SIZE: usize = 4;
+pub const M2TS_PACKET_SIZE: usize = TP_EXTRA_HEADER_SIZE + PACKET_SIZE;
+pub const RS_PARITY_SIZE: usize = 16;
+pub const RS_PACKET_SIZE: usize = PACKET_SIZE + RS_PARITY_SIZE;
+pub const ARRIVAL_TIME_STAMP_MAX: u32 = 0x3fff_ffff;
+
+/// A Blu-ray/AVCHD packet: TP_extra_header followed by a transport stream packet
+pub type M2tsPacket = [u8; M2TS_PACKET_SIZE];
+
+/// A DVB packet: transport stream packet followed by Reed-Solomon parity
+pub type RsPacket = [u8; RS_PACKET_SIZE];
+
+/// The framing around each 188-byte transport stream packet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PacketFormat {
+    /// Plain 188-byte packets
+    Ts,
+    /// 192-byte packets with a 4-byte TP_extra_header prefix
+    M2ts,
+    /// 204-byte packets with 16 bytes of Reed-Solomon parity
+    Rs,
+}
+
+impl PacketFormat {
+    /// Get the size of one packet in this format
+    pub fn packet_size(self) -> usize {
+        match self {
+            PacketFormat::Ts => PACKET_SIZE,
+            PacketFormat::M2ts => M2TS_PACKET_SIZE,
+            PacketFormat::Rs => RS_PACKET_SIZE,
+        }
+    }
+
+    /// Get the offset of the transport stream packet within one packet in this format
+    pub fn packet_offset(self) -> usize {
+        match self {
+            PacketFormat::M2ts => TP_EXTRA_HEADER_SIZE,
+            _ => 0,
+        }
+    }
+
+    /// Get the format with the given packet size, if any
+    pub fn from_packet_size(size: usize) -> Option<Self> {
+        match size {
+            PACKET_SIZE => Some(PacketFormat::Ts),
+            M2TS_PACKET_SIZE => Some(PacketFormat::M2ts),
+            RS_PACKET_SIZE => Some(PacketFormat::Rs),
+            _ => None,
+        }
+    }
+
+    /// Get the transport stream packet out of one packet in this format,
+    /// checking the length and sync byte
+    pub fn read_packet(self, bytes: &[u8]) -> Result<&Packet, TsError> {
+        if bytes.len() != self.packet_size() {
+            return Err(TsError::BadLength(bytes.len()));
+        }
+        let offset = self.packet_offset();
+        let packet = <&Packet>::try_from(&bytes[offset..offset + PACKET_SIZE])
+            .map_err(|_| TsError::BadLength(bytes.len()))?;
+        if packet[0] != SYNC_BYTE {
+            return Err(TsError::BadSync(packet[0]));
+        }
+        Ok(packet)
+    }
+
+    /// Write a transport stream packet into one packet in this format.
+    /// The TP_extra_header is zeroed and the parity bytes are computed.
+    pub fn write_packet(self, packet: &Packet, bytes: &mut [u8]) -> Result<(), TsError> {
+        if bytes.len() != self.packet_size() {
+            return Err(TsError::BadLength(bytes.len()));
+        }
+        let offset = self.packet_offset();
+        for byte in bytes.iter_mut() {
+            *byte = 0;
+        }
+        bytes[offset..offset + PACKET_SIZE].copy_from_slice(packet);
+        if self == PacketFormat::Rs {
+            bytes[PACKET_SIZE..].copy_from_slice(&rs_encode(packet));
+        }
+        Ok(())
+    }
+}
+
+/// Convert a buffer of packets from one format to another, returning the
+/// number of packets converted. Stops when either buffer is exhausted.
+/// The TP_extra_header and parity bytes are only kept between packets of
+/// the same format; parity is computed afresh for other inputs.
+pub fn convert(
+    input: &[u8],
+    from: PacketFormat,
+    output: &mut [u8],
+    to: PacketFormat,
+) -> Result<usize, TsError> {
+    let mut count = 0;
+    let inputs = input.chunks_exact(from.packet_size());
+    let outputs = output.chunks_exact_mut(to.packet_size());
+    for (src, dst) in inputs.zip(outputs) {
+        let packet = from.read_packet(src)?;
+        if from == to {
+            dst.copy_from_slice(src);
+        } else {
+            to.write_packet(packet, dst)?;
+        }
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Make an M2TS packet from a transport stream packet and its TP_extra_header fields
+pub fn m2ts_packet(packet: &Packet, copy_permission: u8, arrival_time_stamp: u32) -> M2tsPacket {
+    let mut m2ts = [0; M2TS_PACKET_SIZE];
+    m2ts[TP_EXTRA_HEADER_SIZE..].copy_from_slice(packet);
+    set_copy_permission(&mut m2ts, copy_permission);
+    set_arrival_time_stamp(&mut m2ts, arrival_time_stamp);
+    m2ts
+}
+
+/// Get the transport stream packet inside an M2TS packet
+pub fn m2ts_inner(m2ts: &M2tsPacket) -> &Packet {
+    <&Packet>::try_from(&m2ts[TP_EXTRA_HEADER_SIZE..]).unwrap()
+}
+
+/// Get the transport stream packet inside an M2TS packet for modification
+pub fn m2ts_inner_mut(m2ts: &mut M2tsPacket) -> &mut Packet {
+    <&mut Packet>::try_from(&mut m2ts[TP_EXTRA_HEADER_SIZE..]).unwrap()
+}
+
+/// Set the copy permission indicator. Max: 3
+pub fn set_copy_permission(m2ts: &mut M2tsPacket, copy_permission: u8) {
+    m2ts[0] = (m2ts[0] & 0x3f) | ((copy_permission & 0x03) << 6);
+}
+
+/// Get the copy permission indicator
+pub fn copy_permission(m2ts: &M2tsPacket) -> u8 {
+    m2ts[0] >> 6
+}
+
+/// Set the arrival time stamp. Max: 2^30 - 1
+pub fn set_arrival_time_stamp(m2ts: &mut M2tsPacket, ats: u32) {
+    m2ts[0] = (m2ts[0] & 0xc0) | ((ats >> 24) as u8 & 0x3f);
+    m2ts[1] = (ats >> 16) as u8;
+    m2ts[2] = (ats >> 8) as u8;
+    m2ts[3] = ats as u8;
+}
+
+/// Get the arrival time stamp
+pub fn arrival_time_stamp(m2ts: &M2tsPacket) -> u32 {
+    ((m2ts[0] as u32 & 0x3f) << 24) | (m2ts[1] as u32) << 16 | (m2ts[2] as u32) << 8 | m2ts[3] as u32
+}
+
+/// Make an RS packet from a transport stream packet and its parity bytes
+pub fn rs_packet(packet: &Packet, parity: &[u8; RS_PARITY_SIZE]) -> RsPacket {
+    let mut rs = [0; RS_PACKET_SIZE];
+    rs[..PACKET_SIZE].copy_from_slice(packet);
+    rs[PACKET_SIZE..].copy_from_slice(parity);
+    rs
+}
+
+/// Get the transport stream packet inside an RS packet
+pub fn rs_inner(rs: &RsPacket) -> &Packet {
+    <&Packet>::try_from(&rs[..PACKET_SIZE]).unwrap()
+}
+
+/// Get the transport stream packet inside an RS packet for modification
+pub fn rs_inner_mut(rs: &mut RsPacket) -> &mut Packet {
+    <&mut Packet>::try_from(&mut rs[..PACKET_SIZE]).unwrap()
+}
+
+/// Get the Reed-Solomon parity bytes
+pub fn rs_parity(rs: &RsPacket) -> &[u8; RS_PARITY_SIZE] {
+    <&[u8; RS_PARITY_SIZE]>::try_from(&rs[PACKET_SIZE..]).unwrap()
+}
+
+/// Get the Reed-Solomon parity bytes for modification
+pub fn rs_parity_mut(rs: &mut RsPacket) -> &mut [u8; RS_PARITY_SIZE] {
+    <&mut [u8; RS_PARITY_SIZE]>::try_from(&mut rs[PACKET_SIZE..]).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpeg::packet;
+
+    #[test]
+    fn test_packet_format_sizes() {
+        assert_eq!(PacketFormat::Ts.packet_size(), 188);
+        assert_eq!(PacketFormat::M2ts.packet_size(), 192);
+        assert_eq!(PacketFormat::Rs.packet_size(), 204);
+        assert_eq!(PacketFormat::from_packet_size(192), Some(PacketFormat::M2ts));
+        assert_eq!(PacketFormat::from_packet_size(200), None);
+    }
+
+    #[test]
+    fn test_m2ts_header() {
+        let mut m2ts = m2ts_packet(&packet::null_packet(), 2, ARRIVAL_TIME_STAMP_MAX);
+        assert_eq!(copy_permission(&m2ts), 2);
+        assert_eq!(arrival_time_stamp(&m2ts), ARRIVAL_TIME_STAMP_MAX);
+        set_arrival_time_stamp(&mut m2ts, 0x1234_5678);
+        assert_eq!(copy_permission(&m2ts), 2);
+        assert_eq!(arrival_time_stamp(&m2ts), 0x1234_5678);
+        set_copy_permission(&mut m2ts, 1);
+        assert_eq!(copy_permission(&m2ts), 1);
+        assert_eq!(arrival_time_stamp(&m2ts), 0x1234_5678);
+        assert_eq!(packet::pid(m2ts_inner(&m2ts)), packet::NULL_PACKET_PID);
+        packet::set_pid(m2ts_inner_mut(&mut m2ts), 0x100);
+        assert_eq!(packet::pid(m2ts_inner(&m2ts)), 0x100);
+    }
+
+    #[test]
+    fn test_rs_parts() {
+        let parity = [7; RS_PARITY_SIZE];
+        let mut rs = rs_packet(&packet::null_packet(), &parity);
+        assert_eq!(rs_parity(&rs), &parity);
+        assert_eq!(rs_inner(&rs), &packet::null_packet());
+        rs_parity_mut(&mut rs)[0] = 0;
+        packet::set_unit_start(rs_inner_mut(&mut rs));
+        assert_eq!(rs[RS_PACKET_SIZE - RS_PARITY_SIZE], 0);
+        assert!(packet::has_unit_start(rs_inner(&rs)));
+    }
+
+    #[test]
+    fn test_read_packet() {
+        let m2ts = m2ts_packet(&packet::null_packet(), 0, 1);
+        assert!(PacketFormat::M2ts.read_packet(&m2ts).is_ok());
+        assert_eq!(PacketFormat::Rs.read_packet(&m2ts), Err(TsError::BadLength(192)));
+        assert_eq!(PacketFormat::Ts.read_packet(&m2ts[..188]), Err(TsError::BadSync(0)));
+    }
+
+    #[test]
+    fn test_convert() {
+        let mut ts = [0; PACKET_SIZE * 3];
+        for (i, chunk) in ts.chunks_exact_mut(PACKET_SIZE).enumerate() {
+            let mut pkt = packet::null_packet();
+            packet::set_pid(&mut pkt, i as u16);
+            chunk.copy_from_slice(&pkt);
+        }
+        let mut rs = [0xaa; RS_PACKET_SIZE * 3];
+        assert_eq!(convert(&ts, PacketFormat::Ts, &mut rs, PacketFormat::Rs), Ok(3));
+        for chunk in rs.chunks_exact(RS_PACKET_SIZE) {
+            let rs_pkt = <&RsPacket>::try_from(chunk).unwrap();
+            assert_eq!(rs_parity(rs_pkt), &rs_encode(rs_inner(rs_pkt)));
+        }
+        let mut m2ts = [0; M2TS_PACKET_SIZE * 2];
+        assert_eq!(convert(&rs, PacketFormat::Rs, &mut m2ts, PacketFormat::M2ts), Ok(2));
+        let mut back = [0; PACKET_SIZE * 2];
+        assert_eq!(convert(&m2ts, PacketFormat::M2ts, &mut back, PacketFormat::Ts), Ok(2));
+        assert_eq!(&back[..], &ts[..PACKET_SIZE * 2]);
+        assert_eq!(&m2ts[..TP_EXTRA_HEADER_SIZE], &[0; TP_EXTRA_HEADER_SIZE]);
+    }
+
+    #[test]
+    fn test_convert_keeps_m2ts_header() {
+        let src = m2ts_packet(&packet::null_packet(), 3, 99);
+        let mut dst = [0; M2TS_PACKET_SIZE];
+        assert_eq!(convert(&src, PacketFormat::M2ts, &mut dst, PacketFormat::M2ts), Ok(1));
+        assert_eq!(dst, src);
+    }
+
+    #[test]
+    fn test_convert_bad_sync() {
+        let ts = [0; PACKET_SIZE];
+        let mut rs = [0; RS_PACKET_SIZE];
+        assert_eq!(
+            convert(&ts, PacketFormat::Ts, &mut rs, PacketFormat::Rs),
+            Err(TsError::BadSync(0))
+        );
+    }
+}
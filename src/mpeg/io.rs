@@ -0,0 +1,341 @@
+use std::io::{self, ErrorKind, Read, Write};
+use std::vec::Vec;
+
+use super::packet::{Packet, PACKET_SIZE};
+use super::sync::{SyncEvent, Synchroniser};
+
+pub const DEFAULT_READ_SIZE: usize = 64 * 1024;
+/// Seven packets, the usual payload of a UDP datagram
+pub const DEFAULT_BATCH_PACKETS: usize = 7;
+
+/// Reads packets from a byte stream, resynchronising after corruption.
+///
+/// Any packet format the synchroniser locks onto is accepted; the inner
+/// transport stream packet is yielded along with the byte offset in the
+/// stream where its packet slot started. Bytes that do not end up in a
+/// packet are counted by `discarded_bytes`.
+pub struct PacketReader<R: Read> {
+    inner: R,
+    sync: Synchroniser,
+    buf: Vec<u8>,
+    start: usize,
+    offset: u64,
+    discarded: u64,
+    eof: bool,
+}
+
+impl<R: Read> PacketReader<R> {
+    /// Read packets from `inner` with the default synchroniser
+    pub fn new(inner: R) -> Self {
+        PacketReader::with_synchroniser(inner, Synchroniser::default())
+    }
+
+    /// Read packets from `inner` with a configured synchroniser
+    pub fn with_synchroniser(inner: R, sync: Synchroniser) -> Self {
+        PacketReader {
+            inner,
+            sync,
+            buf: Vec::new(),
+            start: 0,
+            offset: 0,
+            discarded: 0,
+            eof: false,
+        }
+    }
+
+    /// Get the synchroniser, for its lock state and statistics
+    pub fn synchroniser(&self) -> &Synchroniser {
+        &self.sync
+    }
+
+    /// Get the number of bytes read but not returned in a packet: skipped
+    /// while searching for sync, in slots whose sync byte was missing, or
+    /// left over at the end of the stream
+    pub fn discarded_bytes(&self) -> u64 {
+        self.discarded
+    }
+
+    /// Get a reference to the underlying reader
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Unwrap the underlying reader, discarding any buffered data
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Read the next packet and its byte offset, or `None` at the end of
+    /// the stream. A trailing partial packet is discarded.
+    pub fn read_packet(&mut self) -> io::Result<Option<(u64, Packet)>> {
+        loop {
+            let data = &self.buf[self.start..];
+            let (used, event) = self.sync.next(data);
+            let found = match event {
+                SyncEvent::NeedMore => {
+                    if self.eof {
+                        let rest = self.buf.len() - self.start;
+                        self.start += rest;
+                        self.offset += rest as u64;
+                        self.discarded += rest as u64;
+                        return Ok(None);
+                    }
+                    self.fill()?;
+                    continue;
+                }
+                SyncEvent::Packet(bytes) => match self.sync.locked_format() {
+                    Some(format) => Some(
+                        *format
+                            .read_packet(bytes)
+                            .map_err(|e| io::Error::new(ErrorKind::InvalidData, e))?,
+                    ),
+                    None => None,
+                },
+                _ => None,
+            };
+            let offset = self.offset;
+            self.start += used;
+            self.offset += used as u64;
+            match found {
+                Some(packet) => return Ok(Some((offset, packet))),
+                None => self.discarded += used as u64,
+            }
+        }
+    }
+
+    /// Move the unconsumed bytes to the front and read more after them
+    fn fill(&mut self) -> io::Result<()> {
+        self.buf.drain(..self.start);
+        self.start = 0;
+        let len = self.buf.len();
+        self.buf.resize(len + DEFAULT_READ_SIZE, 0);
+        loop {
+            match self.inner.read(&mut self.buf[len..]) {
+                Ok(count) => {
+                    self.buf.truncate(len + count);
+                    self.eof = count == 0;
+                    return Ok(());
+                }
+                Err(ref e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => {
+                    self.buf.truncate(len);
+                    return Err(e);
+                }
+            }
+        }
+    }
+}
+
+impl<R: Read> Iterator for PacketReader<R> {
+    type Item = io::Result<(u64, Packet)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_packet().transpose()
+    }
+}
+
+/// Writes packets to a byte stream in batches.
+///
+/// Buffered packets are flushed when the batch is full, on `flush`, and when
+/// the writer is dropped.
+pub struct PacketWriter<W: Write> {
+    inner: Option<W>,
+    buf: Vec<u8>,
+    batch_size: usize,
+}
+
+impl<W: Write> PacketWriter<W> {
+    /// Write packets to `inner` in batches of `DEFAULT_BATCH_PACKETS`
+    pub fn new(inner: W) -> Self {
+        PacketWriter::with_batch_size(inner, DEFAULT_BATCH_PACKETS)
+    }
+
+    /// Write packets to `inner` in batches of `packets` packets
+    pub fn with_batch_size(inner: W, packets: usize) -> Self {
+        let batch_size = packets.max(1) * PACKET_SIZE;
+        PacketWriter {
+            inner: Some(inner),
+            buf: Vec::with_capacity(batch_size),
+            batch_size,
+        }
+    }
+
+    /// Get a reference to the underlying writer
+    pub fn get_ref(&self) -> &W {
+        self.inner.as_ref().unwrap()
+    }
+
+    /// Get the number of bytes waiting to be written
+    pub fn buffered(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Queue a packet, writing the batch if it is full
+    pub fn write_packet(&mut self, packet: &Packet) -> io::Result<()> {
+        self.buf.extend_from_slice(packet);
+        if self.buf.len() >= self.batch_size {
+            self.flush_buf()?;
+        }
+        Ok(())
+    }
+
+    /// Queue every packet in a slice
+    pub fn write_packets(&mut self, packets: &[Packet]) -> io::Result<()> {
+        for packet in packets {
+            self.write_packet(packet)?;
+        }
+        Ok(())
+    }
+
+    /// Write any buffered packets and flush the underlying writer
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.flush_buf()?;
+        self.inner.as_mut().unwrap().flush()
+    }
+
+    /// Flush and unwrap the underlying writer
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.flush()?;
+        Ok(self.inner.take().unwrap())
+    }
+
+    fn flush_buf(&mut self) -> io::Result<()> {
+        if !self.buf.is_empty() {
+            self.inner.as_mut().unwrap().write_all(&self.buf)?;
+            self.buf.clear();
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> Drop for PacketWriter<W> {
+    fn drop(&mut self) {
+        if self.inner.is_some() {
+            let _ = self.flush_buf();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpeg::format::{m2ts_packet, M2TS_PACKET_SIZE};
+    use crate::mpeg::packet;
+
+    fn numbered_packet(i: usize) -> Packet {
+        let mut pkt = packet::null_packet();
+        packet::set_pid(&mut pkt, i as u16);
+        pkt
+    }
+
+    /// A reader that returns at most `step` bytes per call
+    struct Trickle<'a> {
+        data: &'a [u8],
+        step: usize,
+    }
+
+    impl<'a> Read for Trickle<'a> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let count = self.step.min(buf.len()).min(self.data.len());
+            buf[..count].copy_from_slice(&self.data[..count]);
+            self.data = &self.data[count..];
+            Ok(count)
+        }
+    }
+
+    #[test]
+    fn test_reader_offsets() {
+        let mut data = std::vec![0u8; 13];
+        for i in 0..5 {
+            data.extend_from_slice(&numbered_packet(i));
+        }
+        data.extend_from_slice(&[packet::SYNC_BYTE; 20]);
+        let reader = PacketReader::new(Trickle {
+            data: &data,
+            step: 50,
+        });
+        let packets: Vec<_> = reader.map(|r| r.unwrap()).collect();
+        assert_eq!(packets.len(), 5);
+        for (i, (offset, pkt)) in packets.iter().enumerate() {
+            assert_eq!(*offset, (13 + i * PACKET_SIZE) as u64);
+            assert_eq!(packet::pid(pkt), i as u16);
+        }
+    }
+
+    #[test]
+    fn test_reader_resync() {
+        let mut data = Vec::new();
+        for i in 0..4 {
+            data.extend_from_slice(&numbered_packet(i));
+        }
+        data.extend_from_slice(&[0u8; 1000]);
+        for i in 4..8 {
+            data.extend_from_slice(&numbered_packet(i));
+        }
+        let mut reader = PacketReader::new(&data[..]);
+        let mut pids = Vec::new();
+        while let Some((_, pkt)) = reader.read_packet().unwrap() {
+            pids.push(packet::pid(&pkt));
+        }
+        assert_eq!(pids, [0, 1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(reader.synchroniser().sync_losses(), 1);
+        assert_eq!(reader.discarded_bytes(), 1000);
+    }
+
+    #[test]
+    fn test_reader_discarded() {
+        let mut data = Vec::new();
+        for i in 0..6 {
+            data.extend_from_slice(&numbered_packet(i));
+        }
+        data[PACKET_SIZE * 4] = 0;
+        data.extend_from_slice(&[0x47, 0, 0]);
+        let mut reader = PacketReader::new(&data[..]);
+        let pids: Vec<_> = reader.by_ref().map(|r| packet::pid(&r.unwrap().1)).collect();
+        assert_eq!(pids, [0, 1, 2, 3, 5]);
+        assert_eq!(reader.discarded_bytes(), (PACKET_SIZE + 3) as u64);
+
+        // too short to lock onto
+        let mut reader = PacketReader::new(&data[..PACKET_SIZE * 2]);
+        assert!(reader.read_packet().unwrap().is_none());
+        assert_eq!(reader.discarded_bytes(), (PACKET_SIZE * 2) as u64);
+        assert!(reader.read_packet().unwrap().is_none());
+        assert_eq!(reader.discarded_bytes(), (PACKET_SIZE * 2) as u64);
+    }
+
+    #[test]
+    fn test_reader_m2ts() {
+        let mut data = Vec::new();
+        for i in 0..4 {
+            data.extend_from_slice(&m2ts_packet(&numbered_packet(i), 0, 0));
+        }
+        let packets: Vec<_> = PacketReader::new(&data[..]).map(|r| r.unwrap()).collect();
+        assert_eq!(packets.len(), 4);
+        assert_eq!(packets[1].0, M2TS_PACKET_SIZE as u64);
+        assert_eq!(packet::pid(&packets[3].1), 3);
+    }
+
+    #[test]
+    fn test_writer_batches() {
+        let mut writer = PacketWriter::with_batch_size(Vec::new(), 2);
+        writer.write_packet(&numbered_packet(0)).unwrap();
+        assert!(writer.get_ref().is_empty());
+        assert_eq!(writer.buffered(), PACKET_SIZE);
+        writer.write_packet(&numbered_packet(1)).unwrap();
+        assert_eq!(writer.get_ref().len(), PACKET_SIZE * 2);
+        writer.write_packets(&[numbered_packet(2)]).unwrap();
+        let out = writer.into_inner().unwrap();
+        assert_eq!(out.len(), PACKET_SIZE * 3);
+        assert_eq!(&out[PACKET_SIZE * 2..], &numbered_packet(2)[..]);
+    }
+
+    #[test]
+    fn test_writer_flushes_on_drop() {
+        let mut out = Vec::new();
+        {
+            let mut writer = PacketWriter::new(&mut out);
+            writer.write_packet(&numbered_packet(0)).unwrap();
+        }
+        assert_eq!(out.len(), PACKET_SIZE);
+    }
+}
@@ -0,0 +1,183 @@
+use super::descriptor::Descriptors;
+use super::section_builder::SectionBuffer;
+use super::table::{DescriptorTable, TABLE_MAX_DESCRIPTORS};
+use crate::error::TsError;
+
+/// table_id of an IPMP_Control_Information_section
+pub const IPMP_TABLE_ID: u8 = 0x07;
+/// Most descriptor bytes an `IpmpControlInfo` can hold
+pub const IPMP_MAX_DESCRIPTORS: usize = TABLE_MAX_DESCRIPTORS;
+
+/// IPMP control information from ISO 13818-11, with the section body kept
+/// as one descriptor loop, possibly spread over several sections.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IpmpControlInfo {
+    table_id_extension: u16,
+    table: DescriptorTable,
+}
+
+impl IpmpControlInfo {
+    /// Start empty, current control information at version 0
+    pub fn new(table_id_extension: u16) -> Self {
+        IpmpControlInfo {
+            table_id_extension,
+            table: DescriptorTable::new(),
+        }
+    }
+
+    /// Parse one IPMP_Control_Information_section, checking its CRC_32 and
+    /// descriptor loop. If it is one of several, add the others with
+    /// `add_section`.
+    pub fn parse(bytes: &[u8]) -> Result<Self, TsError> {
+        let table = DescriptorTable::parse(bytes, IPMP_TABLE_ID)?;
+        Ok(IpmpControlInfo {
+            table_id_extension: u16::from_be_bytes([bytes[3], bytes[4]]),
+            table,
+        })
+    }
+
+    /// Add another section of the same table, ignoring one already seen
+    pub fn add_section(&mut self, bytes: &[u8]) -> Result<(), TsError> {
+        self.table.add_section(bytes, IPMP_TABLE_ID, Some(self.table_id_extension))
+    }
+
+    /// Have all the sections of the table been seen?
+    pub fn is_complete(&self) -> bool {
+        self.table.is_complete()
+    }
+
+    /// Get the table_id_extension
+    pub fn table_id_extension(&self) -> u16 {
+        self.table_id_extension
+    }
+
+    /// Set the table_id_extension
+    pub fn set_table_id_extension(&mut self, extension: u16) {
+        self.table_id_extension = extension;
+    }
+
+    /// Get the version_number
+    pub fn version_number(&self) -> u8 {
+        self.table.version_number()
+    }
+
+    /// Set the version_number. Max: 31
+    pub fn set_version_number(&mut self, version: u8) {
+        self.table.set_version_number(version);
+    }
+
+    /// Get the current_next_indicator
+    pub fn current_next_indicator(&self) -> bool {
+        self.table.current_next_indicator()
+    }
+
+    /// Set the current_next_indicator
+    pub fn set_current_next_indicator(&mut self, current: bool) {
+        self.table.set_current_next_indicator(current);
+    }
+
+    /// Get the raw descriptor loop
+    pub fn descriptor_loop(&self) -> &[u8] {
+        self.table.descriptor_loop()
+    }
+
+    /// Iterate over the descriptors
+    pub fn descriptors(&self) -> Descriptors<'_> {
+        self.table.descriptors()
+    }
+
+    /// Append a descriptor
+    pub fn add_descriptor(&mut self, tag: u8, data: &[u8]) -> Result<(), TsError> {
+        self.table.add_descriptor(tag, data)
+    }
+
+    /// Remove every descriptor
+    pub fn clear_descriptors(&mut self) {
+        self.table.clear_descriptors();
+    }
+
+    /// Get the number of sections needed to carry the table
+    pub fn section_count(&self) -> usize {
+        self.table.section_count()
+    }
+
+    /// Write section `number` of the table to the start of `out`, returning
+    /// its length
+    pub fn write_section(&self, number: usize, out: &mut [u8]) -> Result<usize, TsError> {
+        self.table.write_section(IPMP_TABLE_ID, self.table_id_extension, number, out)
+    }
+
+    /// Build section `number` of the table, returning it with its length
+    pub fn build_section(&self, number: usize) -> Result<(SectionBuffer, usize), TsError> {
+        self.table.build_section(IPMP_TABLE_ID, self.table_id_extension, number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_and_parse() {
+        let mut ipmp = IpmpControlInfo::new(0x0042);
+        ipmp.set_current_next_indicator(false);
+        ipmp.add_descriptor(0x29, &[0x01, 0x00, 0x10]).unwrap();
+        assert_eq!(ipmp.section_count(), 1);
+
+        let (section, len) = ipmp.build_section(0).unwrap();
+        assert_eq!(&section[..8], &[0x07, 0xb0, 0x0e, 0x00, 0x42, 0xc0, 0x00, 0x00]);
+        let mut parsed = IpmpControlInfo::parse(&section[..len]).unwrap();
+        assert_eq!(parsed, ipmp);
+        assert_eq!(parsed.table_id_extension(), 0x0042);
+        assert!(!parsed.current_next_indicator());
+        assert_eq!(parsed.descriptors().next().unwrap().tag(), 0x29);
+
+        parsed.set_table_id_extension(7);
+        parsed.clear_descriptors();
+        assert!(parsed.descriptor_loop().is_empty());
+        let (section, len) = parsed.build_section(0).unwrap();
+        assert_eq!(IpmpControlInfo::parse(&section[..len]).unwrap().table_id_extension(), 7);
+    }
+
+    #[test]
+    fn test_bad_sections() {
+        let (section, len) = IpmpControlInfo::new(1).build_section(0).unwrap();
+        let mut other = [0u8; 12];
+        other.copy_from_slice(&section[..len]);
+        other[0] = 0x03;
+        assert_eq!(IpmpControlInfo::parse(&other), Err(TsError::BadTableId(0x03)));
+        assert_eq!(IpmpControlInfo::new(1).build_section(1).err(), Some(TsError::TooManyEntries(1)));
+    }
+
+    #[test]
+    fn test_other_extension() {
+        let mut tables = [IpmpControlInfo::new(1), IpmpControlInfo::new(2)];
+        for (n, ipmp) in tables.iter_mut().enumerate() {
+            let data = [0x20; 250];
+            for tag in 0..8 {
+                ipmp.add_descriptor(0x80 + n as u8 * 0x10 + tag, &data).unwrap();
+            }
+            assert_eq!(ipmp.section_count(), 2);
+        }
+        let (first, first_len) = tables[0].build_section(0).unwrap();
+        let (other, other_len) = tables[1].build_section(1).unwrap();
+        let mut parsed = IpmpControlInfo::parse(&first[..first_len]).unwrap();
+        assert_eq!(parsed.add_section(&other[..other_len]), Err(TsError::InconsistentSection));
+        assert!(!parsed.is_complete());
+        assert_eq!(parsed.descriptors().count(), 4);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let mut ipmp = IpmpControlInfo::new(0x0042);
+        ipmp.add_descriptor(0x29, &[0x01]).unwrap();
+        let json = serde_json::to_string(&ipmp).unwrap();
+        assert_eq!(
+            json,
+            r#"{"table_id_extension":66,"table":{"version_number":0,"current_next_indicator":true,"descriptors":[41,1,1]}}"#
+        );
+        assert_eq!(serde_json::from_str::<IpmpControlInfo>(&json).unwrap(), ipmp);
+    }
+}
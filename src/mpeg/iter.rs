@@ -0,0 +1,138 @@
+use core::slice::{ChunksExact, ChunksExactMut};
+
+use super::packet::PACKET_SIZE;
+use super::packet_ref::{PacketMut, PacketRef};
+use crate::error::TsError;
+
+/// Iterator over the whole packets in a buffer, created by `packets`.
+#[derive(Clone, Debug)]
+pub struct Packets<'a> {
+    chunks: ChunksExact<'a, u8>,
+}
+
+/// Iterate over the whole packets in a buffer. Packets with a bad sync byte
+/// are yielded as errors; a trailing partial packet is left in `remainder`.
+pub fn packets(buffer: &[u8]) -> Packets<'_> {
+    Packets {
+        chunks: buffer.chunks_exact(PACKET_SIZE),
+    }
+}
+
+impl<'a> Packets<'a> {
+    /// Get the trailing bytes that do not make up a whole packet
+    pub fn remainder(&self) -> &'a [u8] {
+        self.chunks.remainder()
+    }
+}
+
+impl<'a> Iterator for Packets<'a> {
+    type Item = Result<PacketRef<'a>, TsError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.chunks.next().map(PacketRef::new)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+}
+
+impl<'a> ExactSizeIterator for Packets<'a> {}
+
+/// Iterator over the whole packets in a mutable buffer, created by `packets_mut`.
+#[derive(Debug)]
+pub struct PacketsMut<'a> {
+    chunks: ChunksExactMut<'a, u8>,
+}
+
+/// Iterate mutably over the whole packets in a buffer. Packets with a bad
+/// sync byte are yielded as errors; a trailing partial packet is left in
+/// `into_remainder`.
+pub fn packets_mut(buffer: &mut [u8]) -> PacketsMut<'_> {
+    PacketsMut {
+        chunks: buffer.chunks_exact_mut(PACKET_SIZE),
+    }
+}
+
+impl<'a> PacketsMut<'a> {
+    /// Get the trailing bytes that do not make up a whole packet
+    pub fn into_remainder(self) -> &'a mut [u8] {
+        self.chunks.into_remainder()
+    }
+}
+
+impl<'a> Iterator for PacketsMut<'a> {
+    type Item = Result<PacketMut<'a>, TsError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.chunks.next().map(PacketMut::new)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+}
+
+impl<'a> ExactSizeIterator for PacketsMut<'a> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpeg::packet;
+
+    fn buffer() -> [u8; PACKET_SIZE * 3 + 10] {
+        let mut buf = [0xaa; PACKET_SIZE * 3 + 10];
+        for (i, chunk) in buf.chunks_exact_mut(PACKET_SIZE).enumerate() {
+            let mut pkt = packet::null_packet();
+            packet::set_pid(&mut pkt, i as u16);
+            chunk.copy_from_slice(&pkt);
+        }
+        buf
+    }
+
+    #[test]
+    fn test_packets() {
+        let buf = buffer();
+        let mut iter = packets(&buf);
+        assert_eq!(iter.len(), 3);
+        for i in 0..3 {
+            assert_eq!(iter.next().unwrap().unwrap().pid(), i);
+        }
+        assert!(iter.next().is_none());
+        assert_eq!(iter.remainder(), &[0xaa; 10]);
+    }
+
+    #[test]
+    fn test_packets_bad_sync() {
+        let mut buf = buffer();
+        buf[PACKET_SIZE] = 0;
+        let mut iter = packets(&buf);
+        assert!(iter.next().unwrap().is_ok());
+        assert_eq!(iter.next().unwrap().err(), Some(TsError::BadSync(0)));
+        assert!(iter.next().unwrap().is_ok());
+    }
+
+    #[test]
+    fn test_packets_mut() {
+        let mut buf = buffer();
+        let mut iter = packets_mut(&mut buf);
+        for view in iter.by_ref() {
+            let mut view = view.unwrap();
+            view.set_continuity_counter(9);
+        }
+        let remainder = iter.into_remainder();
+        assert_eq!(remainder.len(), 10);
+        remainder[0] = packet::SYNC_BYTE;
+        for view in packets(&buf) {
+            assert_eq!(view.unwrap().continuity_counter(), 9);
+        }
+        assert_eq!(buf[PACKET_SIZE * 3], packet::SYNC_BYTE);
+    }
+
+    #[test]
+    fn test_packets_empty() {
+        let mut iter = packets(&[]);
+        assert!(iter.next().is_none());
+        assert!(iter.remainder().is_empty());
+    }
+}
@@ -1,5 +1,59 @@
+pub mod ca;
+pub mod cat;
+pub mod continuity;
+pub mod crc;
+pub mod descriptor;
+pub mod dump;
+pub mod filter;
+pub mod format;
+#[cfg(feature = "std")]
+pub mod io;
+pub mod ipmp;
+pub mod iter;
 pub mod packet;
+pub mod packet_builder;
 pub mod packet_info;
+#[cfg(feature = "serde")]
+mod packet_record;
+pub mod packet_ref;
+pub mod packetizer;
+pub mod pat;
+pub mod pcr;
+pub mod pid;
+pub mod pmt;
+pub mod reed_solomon;
+pub mod section;
+pub mod section_builder;
+pub mod stream_type;
+pub mod sync;
+pub mod table;
+pub mod tsdt;
 
+pub use ca::*;
+pub use cat::*;
+pub use continuity::*;
+pub use crc::*;
+pub use descriptor::*;
+pub use dump::*;
+pub use filter::*;
+pub use format::*;
+#[cfg(feature = "std")]
+pub use io::*;
+pub use ipmp::*;
+pub use iter::*;
 pub use packet::*;
+pub use packet_builder::*;
 pub use packet_info::*;
+pub use packet_ref::*;
+pub use packetizer::*;
+pub use pat::*;
+pub use pcr::*;
+pub use pid::*;
+pub use pmt::*;
+pub use reed_solomon::*;
+pub use section::*;
+pub use section_builder::*;
+pub use stream_type::*;
+pub use sync::*;
+pub use table::*;
+pub use tsdt::*;
@@ -1,14 +1,19 @@
+use super::pcr::Pcr;
+use super::pid::Pid;
+use crate::error::TsError;
+
 pub const PACKET_SIZE: usize = 188;
 pub const HEADER_SIZE: usize = 4;
 pub const HEADER_SIZE_AF: usize = 6;
 pub const HEADER_SIZE_PCR: usize = 12;
 pub const PAYLOAD_SIZE: usize = PACKET_SIZE - HEADER_SIZE;
+pub const MAX_ADAPTATION_FIELD_LENGTH: u8 = (PAYLOAD_SIZE - 1) as u8;
 pub const SYNC_BYTE: u8 = 0x47;
 pub const MAX_PID: u16 = 0x1FFF;
 pub const NULL_PACKET_PID: u16 = MAX_PID;
 
-pub const PCR_MAX: usize = 2576980377600;	// 2^33 * 300
-pub const PCR_RATE: usize = 27000000;	// Hz
+pub const PCR_MAX: u64 = 2576980377600;	// 2^33 * 300
+pub const PCR_RATE: u64 = 27000000;	// Hz
 pub const SECTION_MAX_SIZE: usize = 0x1000;
 
 // not technically defined in ISO-13818, but this seems to be the industry consensus
@@ -16,16 +21,65 @@ pub const SCRAMBLING_CLEAR: u8 = 0;
 pub const SCRAMBLING_EVEN: u8 = 2;
 pub const SCRAMBLING_ODD: u8 = 3;
 
+/// The transport scrambling control field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ScramblingControl {
+    Clear,
+    Reserved,
+    Even,
+    Odd,
+}
+
+impl From<u8> for ScramblingControl {
+    /// Convert from the two-bit field value, ignoring any higher bits
+    fn from(value: u8) -> Self {
+        match value & 0x03 {
+            SCRAMBLING_CLEAR => ScramblingControl::Clear,
+            SCRAMBLING_EVEN => ScramblingControl::Even,
+            SCRAMBLING_ODD => ScramblingControl::Odd,
+            _ => ScramblingControl::Reserved,
+        }
+    }
+}
+
+impl From<ScramblingControl> for u8 {
+    fn from(value: ScramblingControl) -> Self {
+        match value {
+            ScramblingControl::Clear => SCRAMBLING_CLEAR,
+            ScramblingControl::Reserved => 1,
+            ScramblingControl::Even => SCRAMBLING_EVEN,
+            ScramblingControl::Odd => SCRAMBLING_ODD,
+        }
+    }
+}
+
 pub type Packet = [u8; PACKET_SIZE];
 
 /// Get a new null packet
 pub fn null_packet() -> Packet {
     let mut packet: Packet = [0xff; PACKET_SIZE];
     packet[0] = SYNC_BYTE;
+    packet[1] = 0x00;
+    packet[3] = 0x00;
     set_pid(&mut packet, 0x1fff);
     set_continuity_counter(&mut packet, 0);
     set_payload(&mut packet);
-    return packet;
+    packet
+}
+
+/// Check the sync byte and the adaptation field layout
+pub fn validate(packet: &Packet) -> Result<(), TsError> {
+    if packet[0] != SYNC_BYTE {
+        return Err(TsError::BadSync(packet[0]));
+    }
+    if packet[3] & 0x30 == 0 {
+        return Err(TsError::Reserved("adaptation_field_control"));
+    }
+    if has_adaptation_field(packet) {
+        try_adaptation_field(packet)?;
+    }
+    Ok(())
 }
 
 /// Set the transport error indicator
@@ -48,15 +102,48 @@ pub fn has_unit_start(packet: &Packet) -> bool {
     packet[1] & 0x40 != 0
 }
 
-/// Get the MPEG section, if present
+/// Get the MPEG section data, if present. When the unit start indicator is
+/// set this skips the pointer_field to the first new section; otherwise the
+/// whole payload continues the current section.
 pub fn section(packet: &Packet) -> Option<&[u8]> {
-    let p = payload(&packet);
-    if has_unit_start(&packet) {
-        return p;
+    try_section(packet).unwrap_or(None)
+}
+
+/// Get the MPEG section data, if present, checking the adaptation field
+/// length and the pointer_field
+pub fn try_section(packet: &Packet) -> Result<Option<&[u8]>, TsError> {
+    let p = match try_payload(packet)? {
+        Some(p) => p,
+        None => return Ok(None),
+    };
+    if !has_unit_start(packet) {
+        return Ok(Some(p));
     }
-    return match p {
-        Some(value) => Some(&value[1..value.len()]),
-        None => p,
+    match try_pointer_field(packet)? {
+        Some(pointer) => Ok(Some(&p[1 + pointer as usize..])),
+        None => Ok(None),
+    }
+}
+
+/// Get the pointer_field of a packet starting a section, if present
+pub fn pointer_field(packet: &Packet) -> Option<u8> {
+    try_pointer_field(packet).unwrap_or(None)
+}
+
+/// Get the pointer_field of a packet starting a section, if present,
+/// checking it points inside the payload
+pub fn try_pointer_field(packet: &Packet) -> Result<Option<u8>, TsError> {
+    if !has_unit_start(packet) {
+        return Ok(None);
+    }
+    match try_payload(packet)? {
+        Some(p) if !p.is_empty() => {
+            if p[0] as usize >= p.len() {
+                return Err(TsError::PointerFieldOverflow(p[0]));
+            }
+            Ok(Some(p[0]))
+        }
+        _ => Ok(None),
     }
 }
 
@@ -83,25 +170,37 @@ pub fn has_payload(packet: &Packet) -> bool {
     packet[3] & 0x10 != 0
 }
 
-/// Get the payload as a slice of bytes
+/// Get the payload as a slice of bytes.
+/// Returns `None` if the adaptation field length is invalid.
 pub fn payload(packet: &Packet) -> Option<&[u8]> {
-    if !has_payload(&packet) {
-        return None;
+    try_payload(packet).unwrap_or(None)
+}
+
+/// Get the payload as a slice of bytes, checking the adaptation field length
+pub fn try_payload(packet: &Packet) -> Result<Option<&[u8]>, TsError> {
+    if !has_payload(packet) {
+        return Ok(None);
     }
-    if !has_adaptation_field(&packet) {
-        return Some(&packet[HEADER_SIZE..PACKET_SIZE]);
+    if !has_adaptation_field(packet) {
+        return Ok(Some(&packet[HEADER_SIZE..PACKET_SIZE]));
     }
-    let adaptation = adaptation_field(&packet) as usize;
+    let adaptation = try_adaptation_field(packet)? as usize;
     let start_index: usize = HEADER_SIZE + 1 + adaptation;
-    return Some(&packet[start_index..PACKET_SIZE]);
+    Ok(Some(&packet[start_index..PACKET_SIZE]))
 }
 
 /// Set the PID. Max: 8191 (0x1fff)
 pub fn set_pid(packet: &mut Packet, pid: u16) {
-    packet[1] = (pid >> 8) as u8 & 0x1f;
+    packet[1] = (packet[1] & 0xe0) | ((pid >> 8) as u8 & 0x1f);
     packet[2] = (pid & 0x00ff) as u8;
 }
 
+/// Set the PID, failing if it is above 8191 (0x1fff)
+pub fn try_set_pid(packet: &mut Packet, pid: u16) -> Result<(), TsError> {
+    set_pid(packet, u16::from(Pid::new(pid)?));
+    Ok(())
+}
+
 /// Get the value of the pid
 pub fn pid(packet: &Packet) -> u16 {
     (((packet[1] & 0x1f) as u16) << 8) | packet[2] as u16
@@ -109,12 +208,12 @@ pub fn pid(packet: &Packet) -> u16 {
 
 /// Set the continuity counter. Max: 15
 pub fn set_continuity_counter(packet: &mut Packet, cc: u8) {
-    packet[3] = cc & 0x0f;
+    packet[3] = (packet[3] & 0xf0) | (cc & 0x0f);
 }
 
 /// Reset the continuity counter to zero
 pub fn zero_continuity_counter(packet: &mut Packet) {
-    packet[3] = packet[3] & 0xf0;
+    packet[3] &= 0xf0;
 }
 
 /// Get the value of the continuity counter
@@ -129,13 +228,24 @@ pub fn set_adaptation_field(packet: &mut Packet, length: u8) {
     if length > 0 {
         packet[5] = 0x00;
     }
+    let end = (HEADER_SIZE + 1 + length as usize).min(PACKET_SIZE);
     if length > 1 {
-        for i in 6..PACKET_SIZE {
-            packet[i] = 0xff; // stuffing
+        for byte in packet[HEADER_SIZE_AF..end].iter_mut() {
+            *byte = 0xff; // stuffing
         }
     }
 }
 
+/// Set the adaptation field length (&indicator).
+/// Fails if the length does not fit in the packet.
+pub fn try_set_adaptation_field(packet: &mut Packet, length: u8) -> Result<(), TsError> {
+    if length > MAX_ADAPTATION_FIELD_LENGTH {
+        return Err(TsError::AdaptationFieldOverflow(length));
+    }
+    set_adaptation_field(packet, length);
+    Ok(())
+}
+
 /// Is the adaptation field indicator set?
 pub fn has_adaptation_field(packet: &Packet) -> bool {
     packet[3] & 0x20 != 0
@@ -146,9 +256,26 @@ pub fn adaptation_field(packet: &Packet) -> u8 {
     packet[4]
 }
 
-/// Set the scrambling mode
+/// Get the adaptation field length, checking it is present and fits in the packet
+pub fn try_adaptation_field(packet: &Packet) -> Result<u8, TsError> {
+    if !has_adaptation_field(packet) {
+        return Err(TsError::MissingAdaptationField);
+    }
+    let length = adaptation_field(packet);
+    if length > MAX_ADAPTATION_FIELD_LENGTH {
+        return Err(TsError::AdaptationFieldOverflow(length));
+    }
+    Ok(length)
+}
+
+/// Set the scrambling mode. Max: 3
 pub fn set_scrambling(packet: &mut Packet, scrambling: u8) {
-    packet[3] = (scrambling | 0xc0) << 6;
+    packet[3] = (packet[3] & 0x3f) | ((scrambling & 0x03) << 6);
+}
+
+/// Set the scrambling control field
+pub fn set_scrambling_control(packet: &mut Packet, scrambling: ScramblingControl) {
+    set_scrambling(packet, scrambling.into());
 }
 
 /// Get the scrambling mode
@@ -156,6 +283,27 @@ pub fn scrambling(packet: &Packet) -> u8 {
     (packet[3] & 0xc0) >> 6
 }
 
+/// Get the scrambling control field
+pub fn scrambling_control(packet: &Packet) -> ScramblingControl {
+    scrambling(packet).into()
+}
+
+/// Get the scrambling mode, rejecting the reserved value
+pub fn try_scrambling(packet: &Packet) -> Result<u8, TsError> {
+    match scrambling(packet) {
+        1 => Err(TsError::Reserved("transport_scrambling_control")),
+        value => Ok(value),
+    }
+}
+
+/// Get the adaptation field flags byte, or zero for an empty adaptation field
+fn try_adaptation_flags(packet: &Packet) -> Result<u8, TsError> {
+    match try_adaptation_field(packet)? {
+        0 => Ok(0),
+        _ => Ok(packet[5]),
+    }
+}
+
 /// Set the discontinuity indicator
 pub fn set_discontinuity(packet: &mut Packet) {
     packet[5] |= 0x80;
@@ -171,6 +319,11 @@ pub fn has_discontinuity(packet: &Packet) -> bool {
     packet[5] & 0x80 != 0
 }
 
+/// Is the discontinuity indicator set? Fails without an adaptation field.
+pub fn try_has_discontinuity(packet: &Packet) -> Result<bool, TsError> {
+    Ok(try_adaptation_flags(packet)? & 0x80 != 0)
+}
+
 /// Set the random access indicator
 pub fn set_random_access(packet: &mut Packet) {
 	packet[5] |= 0x40;
@@ -181,6 +334,11 @@ pub fn has_random_access(packet: &Packet) -> bool {
 	packet[5] & 0x40 != 0
 }
 
+/// Is the random access indicator set? Fails without an adaptation field.
+pub fn try_has_random_access(packet: &Packet) -> Result<bool, TsError> {
+    Ok(try_adaptation_flags(packet)? & 0x40 != 0)
+}
+
 /// Set the stream priority indicator
 pub fn set_stream_priority(packet: &mut Packet) {
 	packet[5] |= 0x20;
@@ -191,13 +349,18 @@ pub fn has_stream_priority(packet: &Packet) -> bool {
 	packet[5] & 0x20 != 0
 }
 
+/// Is the stream priority indicator set? Fails without an adaptation field.
+pub fn try_has_stream_priority(packet: &Packet) -> Result<bool, TsError> {
+    Ok(try_adaptation_flags(packet)? & 0x20 != 0)
+}
+
 /// Set the programme clock reference
 pub fn set_pcr(packet: &mut Packet, pcr: u64) {
     packet[5] |= 0x10;
-    packet[6] = (pcr >> 25) as u8 & 0xff;
-    packet[7] = (pcr >> 17) as u8 & 0xff;
-    packet[8] = (pcr >> 9) as u8 & 0xff;
-    packet[9] = (pcr >> 1) as u8 & 0xff;
+    packet[6] = (pcr >> 25) as u8;
+    packet[7] = (pcr >> 17) as u8;
+    packet[8] = (pcr >> 9) as u8;
+    packet[9] = (pcr >> 1) as u8;
     packet[10] = 0x7e | ((pcr << 7) as u8 & 0x80);
 }
 
@@ -207,20 +370,449 @@ pub fn has_pcr(packet: &Packet) -> bool {
     packet[5] & 0x10 != 0
 }
 
+/// Does the packet have a programme clock reference field?
+/// Fails without an adaptation field.
+pub fn try_has_pcr(packet: &Packet) -> Result<bool, TsError> {
+    Ok(try_adaptation_flags(packet)? & 0x10 != 0)
+}
+
 /// Get the programme clock reference
 pub fn pcr(packet: &Packet) -> u64 {
     (packet[6] as u64) << 25 | (packet[7] as u64) << 17 | (packet[8] as u64) << 9 | (packet[9] as u64) << 1 | (packet[10] as u64) >> 7
 }
 
+/// Get the programme clock reference, if present and fully contained
+/// in the adaptation field
+pub fn try_pcr(packet: &Packet) -> Result<Option<u64>, TsError> {
+    if !try_has_pcr(packet)? {
+        return Ok(None);
+    }
+    let length = adaptation_field(packet);
+    if (length as usize) < HEADER_SIZE_PCR - HEADER_SIZE - 1 {
+        return Err(TsError::TruncatedAdaptationField(length));
+    }
+    Ok(Some(pcr(packet)))
+}
+
 /// Set the programme clock reference extension
 pub fn set_pcr_ext(packet: &mut Packet, ext: u16) {
-    packet[10] |= (ext >> 8) as u8 & 0x1;
-    packet[11] = ext as u8 & 0xff;
+    packet[10] = (packet[10] & 0xfe) | ((ext >> 8) as u8 & 0x01);
+    packet[11] = ext as u8;
 }
 
 /// Get the programme clock reference extension
 pub fn pcr_ext(packet: &Packet) -> u16 {
-    (((packet[10] as u16) << 8) & 1) | packet[11] as u16
+    ((packet[10] as u16 & 0x01) << 8) | packet[11] as u16
+}
+
+/// Get the programme clock reference extension, if present and fully
+/// contained in the adaptation field
+pub fn try_pcr_ext(packet: &Packet) -> Result<Option<u16>, TsError> {
+    Ok(try_pcr(packet)?.map(|_| pcr_ext(packet)))
+}
+
+/// Set the programme clock reference base and extension together
+pub fn set_pcr_clock(packet: &mut Packet, pcr: Pcr) {
+    set_pcr(packet, pcr.base());
+    set_pcr_ext(packet, pcr.ext());
+}
+
+/// Get the programme clock reference base and extension together, if present
+pub fn pcr_clock(packet: &Packet) -> Option<Pcr> {
+    try_pcr_clock(packet).unwrap_or(None)
+}
+
+/// Get the programme clock reference base and extension together, if
+/// present and fully contained in the adaptation field
+pub fn try_pcr_clock(packet: &Packet) -> Result<Option<Pcr>, TsError> {
+    Ok(try_pcr(packet)?.map(|base| Pcr::new(base, pcr_ext(packet))))
+}
+
+/// Set the original programme clock reference, inserting it into the
+/// adaptation field if it is not already present
+pub fn set_opcr(packet: &mut Packet, opcr: u64, ext: u16) -> Result<(), TsError> {
+    let layout = AdaptationLayout::parse(packet)?;
+    if layout.flags & 0x08 == 0 {
+        resize_adaptation_bytes(packet, &layout, layout.opcr, 0, 6)?;
+        packet[5] |= 0x08;
+    }
+    write_clock(&mut packet[layout.opcr..layout.opcr + 6], opcr, ext);
+    Ok(())
+}
+
+/// Does the packet have an original programme clock reference field?
+/// Only valid if it has an adaptation field.
+pub fn has_opcr(packet: &Packet) -> bool {
+    packet[5] & 0x08 != 0
+}
+
+/// Get the original programme clock reference, if present
+pub fn opcr(packet: &Packet) -> Option<u64> {
+    try_opcr(packet).unwrap_or(None)
+}
+
+/// Get the original programme clock reference, if present, checking the
+/// adaptation field layout
+pub fn try_opcr(packet: &Packet) -> Result<Option<u64>, TsError> {
+    let layout = AdaptationLayout::parse(packet)?;
+    if layout.flags & 0x08 == 0 {
+        return Ok(None);
+    }
+    Ok(Some(read_clock(&packet[layout.opcr..layout.opcr + 6]).0))
+}
+
+/// Get the original programme clock reference extension, if present
+pub fn opcr_ext(packet: &Packet) -> Option<u16> {
+    try_opcr_ext(packet).unwrap_or(None)
+}
+
+/// Get the original programme clock reference extension, if present,
+/// checking the adaptation field layout
+pub fn try_opcr_ext(packet: &Packet) -> Result<Option<u16>, TsError> {
+    let layout = AdaptationLayout::parse(packet)?;
+    if layout.flags & 0x08 == 0 {
+        return Ok(None);
+    }
+    Ok(Some(read_clock(&packet[layout.opcr..layout.opcr + 6]).1))
+}
+
+/// Get the original programme clock reference base and extension together,
+/// if present
+pub fn opcr_clock(packet: &Packet) -> Option<Pcr> {
+    try_opcr_clock(packet).unwrap_or(None)
+}
+
+/// Get the original programme clock reference base and extension together,
+/// if present, checking the adaptation field layout
+pub fn try_opcr_clock(packet: &Packet) -> Result<Option<Pcr>, TsError> {
+    let layout = AdaptationLayout::parse(packet)?;
+    if layout.flags & 0x08 == 0 {
+        return Ok(None);
+    }
+    let (base, ext) = read_clock(&packet[layout.opcr..layout.opcr + 6]);
+    Ok(Some(Pcr::new(base, ext)))
+}
+
+/// Set the splicing point flag and the splice countdown
+pub fn set_splice_countdown(packet: &mut Packet, countdown: i8) -> Result<(), TsError> {
+    let layout = AdaptationLayout::parse(packet)?;
+    if layout.flags & 0x04 == 0 {
+        resize_adaptation_bytes(packet, &layout, layout.splice, 0, 1)?;
+        packet[5] |= 0x04;
+    }
+    packet[layout.splice] = countdown as u8;
+    Ok(())
+}
+
+/// Is the splicing point flag set?
+/// Only valid if it has an adaptation field.
+pub fn has_splicing_point(packet: &Packet) -> bool {
+    packet[5] & 0x04 != 0
+}
+
+/// Get the splice countdown, if present
+pub fn splice_countdown(packet: &Packet) -> Option<i8> {
+    try_splice_countdown(packet).unwrap_or(None)
+}
+
+/// Get the splice countdown, if present, checking the adaptation field layout
+pub fn try_splice_countdown(packet: &Packet) -> Result<Option<i8>, TsError> {
+    let layout = AdaptationLayout::parse(packet)?;
+    if layout.flags & 0x04 == 0 {
+        return Ok(None);
+    }
+    Ok(Some(packet[layout.splice] as i8))
+}
+
+/// Set the transport private data, replacing any already present
+pub fn set_transport_private_data(packet: &mut Packet, data: &[u8]) -> Result<(), TsError> {
+    let layout = AdaptationLayout::parse(packet)?;
+    let old_len = if layout.flags & 0x02 != 0 {
+        1 + packet[layout.private] as usize
+    } else {
+        0
+    };
+    resize_adaptation_bytes(packet, &layout, layout.private, old_len, 1 + data.len())?;
+    packet[5] |= 0x02;
+    packet[layout.private] = data.len() as u8;
+    packet[layout.private + 1..layout.private + 1 + data.len()].copy_from_slice(data);
+    Ok(())
+}
+
+/// Is the transport private data flag set?
+/// Only valid if it has an adaptation field.
+pub fn has_transport_private_data(packet: &Packet) -> bool {
+    packet[5] & 0x02 != 0
+}
+
+/// Get the transport private data, if present
+pub fn transport_private_data(packet: &Packet) -> Option<&[u8]> {
+    try_transport_private_data(packet).unwrap_or(None)
+}
+
+/// Get the transport private data, if present, checking the adaptation
+/// field layout
+pub fn try_transport_private_data(packet: &Packet) -> Result<Option<&[u8]>, TsError> {
+    let layout = AdaptationLayout::parse(packet)?;
+    if layout.flags & 0x02 == 0 {
+        return Ok(None);
+    }
+    let length = packet[layout.private] as usize;
+    Ok(Some(&packet[layout.private + 1..layout.private + 1 + length]))
+}
+
+/// Add an empty adaptation field extension, if not already present
+pub fn set_adaptation_field_extension(packet: &mut Packet) -> Result<(), TsError> {
+    let layout = AdaptationLayout::parse(packet)?;
+    if layout.flags & 0x01 == 0 {
+        resize_adaptation_bytes(packet, &layout, layout.extension, 0, 2)?;
+        packet[5] |= 0x01;
+        packet[layout.extension] = 1;
+        packet[layout.extension + 1] = 0x1f; // no flags, reserved bits set
+    }
+    Ok(())
+}
+
+/// Is the adaptation field extension flag set?
+/// Only valid if it has an adaptation field.
+pub fn has_adaptation_field_extension(packet: &Packet) -> bool {
+    packet[5] & 0x01 != 0
+}
+
+/// Set the legal time window, adding the extension if needed
+pub fn set_ltw(packet: &mut Packet, valid: bool, offset: u16) -> Result<(), TsError> {
+    let at = insert_extension_field(packet, 0x80, 2)?;
+    packet[at] = ((valid as u8) << 7) | ((offset >> 8) as u8 & 0x7f);
+    packet[at + 1] = offset as u8;
+    Ok(())
+}
+
+/// Get the legal time window valid flag, if present
+pub fn ltw_valid(packet: &Packet) -> Option<bool> {
+    try_ltw_valid(packet).unwrap_or(None)
+}
+
+/// Get the legal time window valid flag, if present, checking the
+/// adaptation field layout
+pub fn try_ltw_valid(packet: &Packet) -> Result<Option<bool>, TsError> {
+    Ok(extension_field(packet, 0x80)?.map(|at| packet[at] & 0x80 != 0))
+}
+
+/// Get the legal time window offset, if present
+pub fn ltw_offset(packet: &Packet) -> Option<u16> {
+    try_ltw_offset(packet).unwrap_or(None)
+}
+
+/// Get the legal time window offset, if present, checking the adaptation
+/// field layout
+pub fn try_ltw_offset(packet: &Packet) -> Result<Option<u16>, TsError> {
+    Ok(extension_field(packet, 0x80)?
+        .map(|at| ((packet[at] as u16 & 0x7f) << 8) | packet[at + 1] as u16))
+}
+
+/// Set the piecewise rate (22 bits), adding the extension if needed
+pub fn set_piecewise_rate(packet: &mut Packet, rate: u32) -> Result<(), TsError> {
+    let at = insert_extension_field(packet, 0x40, 3)?;
+    packet[at] = 0xc0 | ((rate >> 16) as u8 & 0x3f);
+    packet[at + 1] = (rate >> 8) as u8;
+    packet[at + 2] = rate as u8;
+    Ok(())
+}
+
+/// Get the piecewise rate, if present
+pub fn piecewise_rate(packet: &Packet) -> Option<u32> {
+    try_piecewise_rate(packet).unwrap_or(None)
+}
+
+/// Get the piecewise rate, if present, checking the adaptation field layout
+pub fn try_piecewise_rate(packet: &Packet) -> Result<Option<u32>, TsError> {
+    Ok(extension_field(packet, 0x40)?.map(|at| {
+        ((packet[at] as u32 & 0x3f) << 16) | (packet[at + 1] as u32) << 8 | packet[at + 2] as u32
+    }))
+}
+
+/// Set the seamless splice type and DTS of the next access unit,
+/// adding the extension if needed
+pub fn set_seamless_splice(
+    packet: &mut Packet,
+    splice_type: u8,
+    dts_next_au: u64,
+) -> Result<(), TsError> {
+    let at = insert_extension_field(packet, 0x20, 5)?;
+    packet[at] = (splice_type << 4) | ((dts_next_au >> 29) as u8 & 0x0e) | 0x01;
+    packet[at + 1] = (dts_next_au >> 22) as u8;
+    packet[at + 2] = ((dts_next_au >> 14) as u8 & 0xfe) | 0x01;
+    packet[at + 3] = (dts_next_au >> 7) as u8;
+    packet[at + 4] = ((dts_next_au << 1) as u8 & 0xfe) | 0x01;
+    Ok(())
+}
+
+/// Get the seamless splice type, if present
+pub fn splice_type(packet: &Packet) -> Option<u8> {
+    try_splice_type(packet).unwrap_or(None)
+}
+
+/// Get the seamless splice type, if present, checking the adaptation field
+/// layout
+pub fn try_splice_type(packet: &Packet) -> Result<Option<u8>, TsError> {
+    Ok(extension_field(packet, 0x20)?.map(|at| packet[at] >> 4))
+}
+
+/// Get the DTS of the next access unit, if present
+pub fn dts_next_au(packet: &Packet) -> Option<u64> {
+    try_dts_next_au(packet).unwrap_or(None)
+}
+
+/// Get the DTS of the next access unit, if present, checking the
+/// adaptation field layout
+pub fn try_dts_next_au(packet: &Packet) -> Result<Option<u64>, TsError> {
+    Ok(extension_field(packet, 0x20)?.map(|at| {
+        (packet[at] as u64 & 0x0e) << 29
+            | (packet[at + 1] as u64) << 22
+            | (packet[at + 2] as u64 & 0xfe) << 14
+            | (packet[at + 3] as u64) << 7
+            | (packet[at + 4] as u64) >> 1
+    }))
+}
+
+/// Offsets of the optional adaptation field members, derived from the flags
+struct AdaptationLayout {
+    /// Flags byte, or zero for an empty adaptation field
+    flags: u8,
+    opcr: usize,
+    splice: usize,
+    private: usize,
+    extension: usize,
+    /// End of the last member; stuffing runs from here to `end`
+    used: usize,
+    /// End of the adaptation field
+    end: usize,
+}
+
+impl AdaptationLayout {
+    fn parse(packet: &Packet) -> Result<Self, TsError> {
+        let flags = try_adaptation_flags(packet)?;
+        let length = adaptation_field(packet);
+        let truncated = TsError::TruncatedAdaptationField(length);
+        let end = HEADER_SIZE + 1 + length as usize;
+        let mut offset = HEADER_SIZE_AF;
+        if flags & 0x10 != 0 {
+            offset += 6;
+        }
+        let opcr = offset;
+        if flags & 0x08 != 0 {
+            offset += 6;
+        }
+        let splice = offset;
+        if flags & 0x04 != 0 {
+            offset += 1;
+        }
+        let private = offset;
+        if flags & 0x02 != 0 {
+            offset += 1 + *packet.get(offset).ok_or(truncated)? as usize;
+        }
+        let extension = offset;
+        if flags & 0x01 != 0 {
+            offset += 1 + *packet.get(offset).ok_or(truncated)? as usize;
+        }
+        if offset > end.max(HEADER_SIZE_AF) {
+            return Err(truncated);
+        }
+        Ok(AdaptationLayout { flags, opcr, splice, private, extension, used: offset, end })
+    }
+}
+
+/// Grow or shrink the member at `at` from `old_len` to `new_len` bytes,
+/// moving the following members and taking from or returning to the stuffing
+fn resize_adaptation_bytes(
+    packet: &mut Packet,
+    layout: &AdaptationLayout,
+    at: usize,
+    old_len: usize,
+    new_len: usize,
+) -> Result<(), TsError> {
+    if layout.used - old_len + new_len > layout.end {
+        return Err(TsError::TruncatedAdaptationField(adaptation_field(packet)));
+    }
+    packet.copy_within(at + old_len..layout.used, at + new_len);
+    if new_len < old_len {
+        let new_used = layout.used - old_len + new_len;
+        for byte in packet[new_used..layout.used].iter_mut() {
+            *byte = 0xff; // stuffing
+        }
+    }
+    Ok(())
+}
+
+/// Find the extension member selected by `flag`, returning its offset
+fn extension_field(packet: &Packet, flag: u8) -> Result<Option<usize>, TsError> {
+    let layout = AdaptationLayout::parse(packet)?;
+    if layout.flags & 0x01 == 0 {
+        return Ok(None);
+    }
+    let ext_end = layout.extension + 1 + packet[layout.extension] as usize;
+    let (offset, size) = extension_offset(packet, &layout, flag)?;
+    if packet[layout.extension + 1] & flag == 0 {
+        return Ok(None);
+    }
+    if offset + size > ext_end {
+        return Err(TsError::TruncatedAdaptationField(adaptation_field(packet)));
+    }
+    Ok(Some(offset))
+}
+
+/// Add the extension member selected by `flag` if missing, returning its offset
+fn insert_extension_field(packet: &mut Packet, flag: u8, size: usize) -> Result<usize, TsError> {
+    set_adaptation_field_extension(packet)?;
+    let layout = AdaptationLayout::parse(packet)?;
+    let (offset, _) = extension_offset(packet, &layout, flag)?;
+    if packet[layout.extension + 1] & flag == 0 {
+        resize_adaptation_bytes(packet, &layout, offset, 0, size)?;
+        packet[layout.extension] += size as u8;
+        packet[layout.extension + 1] |= flag;
+    }
+    Ok(offset)
+}
+
+/// Offset and size of the extension member selected by `flag`, checking
+/// the extension's flags byte is inside the adaptation field
+fn extension_offset(packet: &Packet, layout: &AdaptationLayout, flag: u8) -> Result<(usize, usize), TsError> {
+    let extension = layout.extension;
+    if packet[extension] == 0 || extension + 1 >= layout.end {
+        return Err(TsError::TruncatedAdaptationField(adaptation_field(packet)));
+    }
+    let flags = packet[extension + 1];
+    let mut offset = extension + 2;
+    for &(bit, size) in [(0x80u8, 2usize), (0x40, 3), (0x20, 5)].iter() {
+        if bit == flag {
+            return Ok((offset, size));
+        }
+        if flags & bit != 0 {
+            offset += size;
+        }
+    }
+    Ok((offset, 0))
+}
+
+/// Write a 33-bit base and 9-bit extension clock in the PCR format
+fn write_clock(bytes: &mut [u8], base: u64, ext: u16) {
+    bytes[0] = (base >> 25) as u8;
+    bytes[1] = (base >> 17) as u8;
+    bytes[2] = (base >> 9) as u8;
+    bytes[3] = (base >> 1) as u8;
+    bytes[4] = 0x7e | ((base << 7) as u8 & 0x80) | ((ext >> 8) as u8 & 0x01);
+    bytes[5] = ext as u8;
+}
+
+/// Read a 33-bit base and 9-bit extension clock in the PCR format
+fn read_clock(bytes: &[u8]) -> (u64, u16) {
+    let base = (bytes[0] as u64) << 25
+        | (bytes[1] as u64) << 17
+        | (bytes[2] as u64) << 9
+        | (bytes[3] as u64) << 1
+        | (bytes[4] as u64) >> 7;
+    let ext = ((bytes[4] as u16 & 0x01) << 8) | bytes[5] as u16;
+    (base, ext)
 }
 
 #[cfg(test)]
@@ -235,8 +827,8 @@ mod tests {
         assert_eq!(continuity_counter(&packet), 0);
         assert!(has_payload(&packet));
         let payload = payload(&packet).unwrap();
-        for i in 0..payload.len() {
-            assert_eq!(payload[i], 0xff);
+        for byte in payload.iter() {
+            assert_eq!(*byte, 0xff);
         }
         assert!(has_discontinuity(&packet));
         assert!(!has_adaptation_field(&packet));
@@ -280,6 +872,15 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_try_set_pid() {
+        let mut packet = null_packet();
+        assert_eq!(try_set_pid(&mut packet, 0x100), Ok(()));
+        assert_eq!(pid(&packet), 0x100);
+        assert_eq!(try_set_pid(&mut packet, 0x2000), Err(TsError::PidOutOfRange(0x2000)));
+        assert_eq!(pid(&packet), 0x100);
+    }
+
     #[test]
     fn test_continuity_counter() {
         let mut packet = null_packet();
@@ -326,8 +927,8 @@ mod tests {
         assert!(has_adaptation_field(&packet));
         assert_eq!(adaptation_field(&packet), length);
         assert_eq!(packet[5], 0x00);
-        for i in 6..PACKET_SIZE {
-            assert_eq!(packet[i], 0xff);
+        for byte in packet[6..PACKET_SIZE].iter() {
+            assert_eq!(*byte, 0xff);
         }
     }
 
@@ -391,4 +992,352 @@ mod tests {
         set_pcr_ext(&mut packet, ext);
         assert_eq!(pcr_ext(&packet), ext);
     }
+
+    #[test]
+    fn test_validate() {
+        let mut packet = null_packet();
+        assert_eq!(validate(&packet), Ok(()));
+        packet[3] &= 0xcf;
+        assert_eq!(validate(&packet), Err(TsError::Reserved("adaptation_field_control")));
+        packet[0] = 0x48;
+        assert_eq!(validate(&packet), Err(TsError::BadSync(0x48)));
+    }
+
+    #[test]
+    fn test_try_payload_overflow() {
+        let mut packet = null_packet();
+        set_adaptation_field(&mut packet, 200);
+        assert_eq!(try_payload(&packet), Err(TsError::AdaptationFieldOverflow(200)));
+        assert_eq!(try_section(&packet), Err(TsError::AdaptationFieldOverflow(200)));
+        assert_eq!(payload(&packet), None);
+        assert_eq!(validate(&packet), Err(TsError::AdaptationFieldOverflow(200)));
+    }
+
+    #[test]
+    fn test_section() {
+        let mut packet = null_packet();
+        packet[HEADER_SIZE] = 0x00;
+        assert_eq!(section(&packet).unwrap().len(), PAYLOAD_SIZE);
+        assert_eq!(pointer_field(&packet), None);
+        set_unit_start(&mut packet);
+        assert_eq!(pointer_field(&packet), Some(0));
+        assert_eq!(section(&packet).unwrap().len(), PAYLOAD_SIZE - 1);
+        packet[HEADER_SIZE] = 10;
+        packet[HEADER_SIZE + 11] = 0x42;
+        assert_eq!(section(&packet).unwrap()[0], 0x42);
+        assert_eq!(section(&packet).unwrap().len(), PAYLOAD_SIZE - 11);
+        packet[HEADER_SIZE] = PAYLOAD_SIZE as u8;
+        assert_eq!(try_section(&packet), Err(TsError::PointerFieldOverflow(PAYLOAD_SIZE as u8)));
+        assert_eq!(section(&packet), None);
+    }
+
+    #[test]
+    fn test_try_payload_full_adaptation_field() {
+        let mut packet = null_packet();
+        try_set_adaptation_field(&mut packet, MAX_ADAPTATION_FIELD_LENGTH).unwrap();
+        assert_eq!(try_payload(&packet).unwrap().unwrap().len(), 0);
+        assert_eq!(try_section(&packet).unwrap().unwrap().len(), 0);
+        assert_eq!(
+            try_set_adaptation_field(&mut packet, MAX_ADAPTATION_FIELD_LENGTH + 1),
+            Err(TsError::AdaptationFieldOverflow(MAX_ADAPTATION_FIELD_LENGTH + 1))
+        );
+    }
+
+    #[test]
+    fn test_try_adaptation_field_missing() {
+        let packet = null_packet();
+        assert_eq!(try_adaptation_field(&packet), Err(TsError::MissingAdaptationField));
+        assert_eq!(try_has_discontinuity(&packet), Err(TsError::MissingAdaptationField));
+        assert_eq!(try_has_random_access(&packet), Err(TsError::MissingAdaptationField));
+        assert_eq!(try_has_stream_priority(&packet), Err(TsError::MissingAdaptationField));
+        assert_eq!(try_has_pcr(&packet), Err(TsError::MissingAdaptationField));
+        assert_eq!(try_pcr(&packet), Err(TsError::MissingAdaptationField));
+    }
+
+    #[test]
+    fn test_try_flags_empty_adaptation_field() {
+        let mut packet = null_packet();
+        set_adaptation_field(&mut packet, 0);
+        assert_eq!(try_has_discontinuity(&packet), Ok(false));
+        assert_eq!(try_has_pcr(&packet), Ok(false));
+        assert_eq!(try_pcr(&packet), Ok(None));
+    }
+
+    #[test]
+    fn test_try_pcr() {
+        let mut packet = null_packet();
+        set_adaptation_field(&mut packet, 7);
+        assert_eq!(try_pcr(&packet), Ok(None));
+        set_pcr(&mut packet, 23647);
+        set_pcr_ext(&mut packet, 137);
+        assert_eq!(try_pcr(&packet), Ok(Some(23647)));
+        assert_eq!(try_pcr_ext(&packet), Ok(Some(137)));
+        packet[4] = 6;
+        assert_eq!(try_pcr(&packet), Err(TsError::TruncatedAdaptationField(6)));
+    }
+
+    #[test]
+    fn test_try_scrambling() {
+        let mut packet = null_packet();
+        assert_eq!(try_scrambling(&packet), Ok(SCRAMBLING_CLEAR));
+        packet[3] |= 0x40;
+        assert_eq!(try_scrambling(&packet), Err(TsError::Reserved("transport_scrambling_control")));
+    }
+
+    #[test]
+    fn test_pcr_ext_high_bit() {
+        let mut packet = null_packet();
+        set_adaptation_field(&mut packet, 7);
+        set_pcr(&mut packet, 0x1_2345_6789);
+        set_pcr_ext(&mut packet, 299);
+        assert_eq!(pcr(&packet), 0x1_2345_6789);
+        assert_eq!(pcr_ext(&packet), 299);
+    }
+
+    #[test]
+    fn test_adaptation_field_keeps_payload() {
+        let mut packet = null_packet();
+        packet[PACKET_SIZE - 1] = 0x42;
+        set_adaptation_field(&mut packet, 10);
+        assert_eq!(packet[PACKET_SIZE - 1], 0x42);
+        assert_eq!(packet[HEADER_SIZE + 10], 0xff);
+    }
+
+    #[test]
+    fn test_opcr() {
+        let mut packet = null_packet();
+        set_adaptation_field(&mut packet, 13);
+        assert_eq!(opcr(&packet), None);
+        set_pcr(&mut packet, 1000);
+        set_opcr(&mut packet, 0x1_ffff_ffff, 511).unwrap();
+        assert!(has_opcr(&packet));
+        assert_eq!(opcr(&packet), Some(0x1_ffff_ffff));
+        assert_eq!(opcr_ext(&packet), Some(511));
+        assert_eq!(pcr(&packet), 1000);
+        assert_eq!(&packet[12..18], &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff]);
+    }
+
+    #[test]
+    fn test_splice_countdown() {
+        let mut packet = null_packet();
+        set_adaptation_field(&mut packet, 2);
+        set_splice_countdown(&mut packet, -3).unwrap();
+        assert!(has_splicing_point(&packet));
+        assert_eq!(splice_countdown(&packet), Some(-3));
+        assert_eq!(packet[6], 0xfd);
+    }
+
+    #[test]
+    fn test_transport_private_data() {
+        let mut packet = null_packet();
+        set_adaptation_field(&mut packet, 10);
+        set_splice_countdown(&mut packet, 5).unwrap();
+        set_transport_private_data(&mut packet, &[1, 2, 3, 4]).unwrap();
+        assert_eq!(transport_private_data(&packet), Some(&[1u8, 2, 3, 4][..]));
+        set_transport_private_data(&mut packet, &[9]).unwrap();
+        assert_eq!(transport_private_data(&packet), Some(&[9u8][..]));
+        assert_eq!(splice_countdown(&packet), Some(5));
+        assert_eq!(&packet[9..15], &[0xff; 6]);
+        assert_eq!(
+            set_transport_private_data(&mut packet, &[0; 8]),
+            Err(TsError::TruncatedAdaptationField(10))
+        );
+    }
+
+    #[test]
+    fn test_insert_keeps_later_fields() {
+        let mut packet = null_packet();
+        set_adaptation_field(&mut packet, 30);
+        set_transport_private_data(&mut packet, &[0xaa, 0xbb]).unwrap();
+        set_seamless_splice(&mut packet, 3, 0x1_2345_6789).unwrap();
+        set_splice_countdown(&mut packet, 1).unwrap();
+        set_opcr(&mut packet, 42, 7).unwrap();
+        assert_eq!(opcr(&packet), Some(42));
+        assert_eq!(splice_countdown(&packet), Some(1));
+        assert_eq!(transport_private_data(&packet), Some(&[0xaau8, 0xbb][..]));
+        assert_eq!(splice_type(&packet), Some(3));
+        assert_eq!(dts_next_au(&packet), Some(0x1_2345_6789));
+    }
+
+    #[test]
+    fn test_adaptation_field_extension() {
+        let mut packet = null_packet();
+        set_adaptation_field(&mut packet, 13);
+        set_adaptation_field_extension(&mut packet).unwrap();
+        assert!(has_adaptation_field_extension(&packet));
+        assert_eq!(ltw_offset(&packet), None);
+        set_seamless_splice(&mut packet, 0xf, 0x1_ffff_ffff).unwrap();
+        set_ltw(&mut packet, true, 0x7fff).unwrap();
+        set_piecewise_rate(&mut packet, 0x3f_ffff).unwrap();
+        assert_eq!(packet[6], 11);
+        assert_eq!(ltw_valid(&packet), Some(true));
+        assert_eq!(ltw_offset(&packet), Some(0x7fff));
+        assert_eq!(piecewise_rate(&packet), Some(0x3f_ffff));
+        assert_eq!(splice_type(&packet), Some(0xf));
+        assert_eq!(dts_next_au(&packet), Some(0x1_ffff_ffff));
+        set_ltw(&mut packet, false, 0x1234).unwrap();
+        assert_eq!(ltw_valid(&packet), Some(false));
+        assert_eq!(ltw_offset(&packet), Some(0x1234));
+        assert_eq!(
+            set_splice_countdown(&mut packet, 0),
+            Err(TsError::TruncatedAdaptationField(13))
+        );
+    }
+
+    #[test]
+    fn test_adaptation_field_members_missing() {
+        let mut packet = null_packet();
+        assert_eq!(try_opcr(&packet), Err(TsError::MissingAdaptationField));
+        assert_eq!(set_splice_countdown(&mut packet, 0), Err(TsError::MissingAdaptationField));
+        set_adaptation_field(&mut packet, 0);
+        assert_eq!(try_opcr(&packet), Ok(None));
+        assert_eq!(try_ltw_offset(&packet), Ok(None));
+        assert_eq!(set_splice_countdown(&mut packet, 0), Err(TsError::TruncatedAdaptationField(0)));
+    }
+
+    #[test]
+    fn test_adaptation_field_members_truncated() {
+        let mut packet = null_packet();
+        set_adaptation_field(&mut packet, 3);
+        packet[5] |= 0x02;
+        packet[6] = 100;
+        assert_eq!(try_transport_private_data(&packet), Err(TsError::TruncatedAdaptationField(3)));
+        assert_eq!(transport_private_data(&packet), None);
+    }
+
+    #[test]
+    fn test_adaptation_field_extension_truncated() {
+        // private data fills the field, leaving only the extension length byte
+        let mut packet = null_packet();
+        set_adaptation_field(&mut packet, 183);
+        packet[3] = (packet[3] & 0xcf) | 0x20;
+        packet[5] = 0x03;
+        packet[6] = 180;
+        packet[PACKET_SIZE - 1] = 0;
+        let truncated = TsError::TruncatedAdaptationField(183);
+        assert_eq!(try_ltw_offset(&packet), Err(truncated));
+        assert_eq!(try_piecewise_rate(&packet), Err(truncated));
+        assert_eq!(try_splice_type(&packet), Err(truncated));
+        assert_eq!(try_dts_next_au(&packet), Err(truncated));
+        assert_eq!(ltw_offset(&packet), None);
+        assert_eq!(set_ltw(&mut packet, true, 1), Err(truncated));
+    }
+
+    #[test]
+    fn test_pcr_clock() {
+        let mut packet = null_packet();
+        set_adaptation_field(&mut packet, 13);
+        assert_eq!(pcr_clock(&packet), None);
+        let clock = Pcr::new(0x1_0000_0001, 257);
+        set_pcr_clock(&mut packet, clock);
+        set_opcr(&mut packet, clock.base(), clock.ext()).unwrap();
+        assert_eq!(pcr_clock(&packet), Some(clock));
+        assert_eq!(opcr_clock(&packet), Some(clock));
+
+        // a reserved extension is read and written back unchanged
+        set_pcr_ext(&mut packet, 0x1ff);
+        let clock = try_pcr_clock(&packet).unwrap().unwrap();
+        assert_eq!(clock.ext(), 0x1ff);
+        let mut copy = packet;
+        set_pcr_clock(&mut copy, Pcr::default());
+        set_pcr_clock(&mut copy, clock);
+        assert_eq!(copy[..], packet[..]);
+
+        packet[4] = 6;
+        assert_eq!(try_pcr_clock(&packet), Err(TsError::TruncatedAdaptationField(6)));
+    }
+
+    #[test]
+    fn test_scrambling_control() {
+        let mut packet = null_packet();
+        for &value in [
+            ScramblingControl::Odd,
+            ScramblingControl::Even,
+            ScramblingControl::Reserved,
+            ScramblingControl::Clear,
+        ]
+        .iter()
+        {
+            set_scrambling_control(&mut packet, value);
+            assert_eq!(scrambling_control(&packet), value);
+            assert_eq!(ScramblingControl::from(u8::from(value)), value);
+        }
+    }
+
+    /// Header fields as (transport_error, unit_start, priority, pid, scrambling, afc, cc)
+    type Header = (bool, bool, bool, u16, u8, u8, u8);
+
+    fn write_header(packet: &mut Packet, header: Header, order: usize) {
+        let (tei, pusi, priority, p, sc, afc, cc) = header;
+        let mut steps = [0usize, 1, 2, 3, 4, 5, 6];
+        steps.rotate_left(order % 7);
+        if order % 2 == 1 {
+            steps.reverse();
+        }
+        for step in steps.iter() {
+            match step {
+                0 if tei => set_transport_error(packet),
+                1 if pusi => set_unit_start(packet),
+                2 if priority => set_transport_priority(packet),
+                3 => set_pid(packet, p),
+                4 => set_scrambling(packet, sc),
+                5 => {
+                    if afc & 0x02 != 0 {
+                        set_adaptation_field(packet, 0);
+                    }
+                    if afc & 0x01 != 0 {
+                        set_payload(packet);
+                    }
+                }
+                6 => set_continuity_counter(packet, cc),
+                _ => {}
+            }
+        }
+    }
+
+    #[test]
+    fn test_header_fields_independent() {
+        let pids = [0x0000, 0x0001, 0x00ff, 0x0100, 0x1234, 0x1ffe, MAX_PID];
+        let mut order = 0;
+        for &p in pids.iter() {
+            for sc in 0..4 {
+                for cc in 0..16 {
+                    for flags in 0..8 {
+                        order += 1;
+                        let afc = 1 + (order % 3) as u8;
+                        let header = (flags & 1 != 0, flags & 2 != 0, flags & 4 != 0, p, sc, afc, cc);
+                        let mut packet = [0u8; PACKET_SIZE];
+                        packet[0] = SYNC_BYTE;
+                        write_header(&mut packet, header, order);
+                        assert_eq!(has_transport_error(&packet), header.0);
+                        assert_eq!(has_unit_start(&packet), header.1);
+                        assert_eq!(has_transport_priority(&packet), header.2);
+                        assert_eq!(pid(&packet), p);
+                        assert_eq!(scrambling(&packet), sc);
+                        assert_eq!(has_adaptation_field(&packet), afc & 0x02 != 0);
+                        assert_eq!(has_payload(&packet), afc & 0x01 != 0);
+                        assert_eq!(continuity_counter(&packet), cc);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_setters_overwrite_own_field() {
+        let mut packet = null_packet();
+        set_unit_start(&mut packet);
+        set_pid(&mut packet, MAX_PID);
+        set_pid(&mut packet, 0x0100);
+        set_scrambling(&mut packet, SCRAMBLING_ODD);
+        set_scrambling(&mut packet, SCRAMBLING_EVEN);
+        set_continuity_counter(&mut packet, 15);
+        set_continuity_counter(&mut packet, 4);
+        assert!(has_unit_start(&packet));
+        assert!(!has_transport_error(&packet));
+        assert_eq!(pid(&packet), 0x0100);
+        assert_eq!(scrambling(&packet), SCRAMBLING_EVEN);
+        assert_eq!(continuity_counter(&packet), 4);
+        assert!(has_payload(&packet));
+    }
 }
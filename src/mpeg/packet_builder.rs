@@ -0,0 +1,333 @@
+use super::packet::{self, ScramblingControl};
+use super::pcr::Pcr;
+use crate::error::TsError;
+
+/// Composes a transport stream packet from its header fields, adaptation
+/// field members and payload, sizing the adaptation field and stuffing to
+/// fill the packet.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PacketBuilder<'a> {
+    pid: u16,
+    continuity_counter: u8,
+    scrambling: u8,
+    transport_error: bool,
+    unit_start: bool,
+    transport_priority: bool,
+    discontinuity: bool,
+    random_access: bool,
+    stream_priority: bool,
+    pcr: Option<Pcr>,
+    opcr: Option<Pcr>,
+    splice_countdown: Option<i8>,
+    private_data: Option<&'a [u8]>,
+    payload: &'a [u8],
+}
+
+impl<'a> PacketBuilder<'a> {
+    /// Start a packet on the given PID
+    pub fn new(pid: u16) -> Self {
+        PacketBuilder {
+            pid,
+            ..Default::default()
+        }
+    }
+
+    /// Set the PID. Max: 8191 (0x1fff)
+    pub fn pid(mut self, pid: u16) -> Self {
+        self.pid = pid;
+        self
+    }
+
+    /// Set the continuity counter. Max: 15
+    pub fn continuity_counter(mut self, cc: u8) -> Self {
+        self.continuity_counter = cc;
+        self
+    }
+
+    /// Set the scrambling mode
+    pub fn scrambling(mut self, scrambling: ScramblingControl) -> Self {
+        self.scrambling = scrambling.into();
+        self
+    }
+
+    /// Set the transport error indicator
+    pub fn transport_error(mut self) -> Self {
+        self.transport_error = true;
+        self
+    }
+
+    /// Set the unit start indicator
+    pub fn unit_start(mut self) -> Self {
+        self.unit_start = true;
+        self
+    }
+
+    /// Set the transport priority indicator
+    pub fn transport_priority(mut self) -> Self {
+        self.transport_priority = true;
+        self
+    }
+
+    /// Set the discontinuity indicator
+    pub fn discontinuity(mut self) -> Self {
+        self.discontinuity = true;
+        self
+    }
+
+    /// Set the random access indicator
+    pub fn random_access(mut self) -> Self {
+        self.random_access = true;
+        self
+    }
+
+    /// Set the stream priority indicator
+    pub fn stream_priority(mut self) -> Self {
+        self.stream_priority = true;
+        self
+    }
+
+    /// Set the programme clock reference
+    pub fn pcr(mut self, pcr: Pcr) -> Self {
+        self.pcr = Some(pcr);
+        self
+    }
+
+    /// Set the original programme clock reference
+    pub fn opcr(mut self, opcr: Pcr) -> Self {
+        self.opcr = Some(opcr);
+        self
+    }
+
+    /// Set the splicing point flag and the splice countdown
+    pub fn splice_countdown(mut self, countdown: i8) -> Self {
+        self.splice_countdown = Some(countdown);
+        self
+    }
+
+    /// Set the transport private data
+    pub fn private_data(mut self, data: &'a [u8]) -> Self {
+        self.private_data = Some(data);
+        self
+    }
+
+    /// Set the payload. Only as much as fits will be written.
+    pub fn payload(mut self, payload: &'a [u8]) -> Self {
+        self.payload = payload;
+        self
+    }
+
+    /// Get the number of payload bytes that fit alongside the adaptation
+    /// field members
+    pub fn payload_capacity(&self) -> Result<usize, TsError> {
+        let members = self.adaptation_members();
+        if members == 0 {
+            return Ok(packet::PAYLOAD_SIZE);
+        }
+        // length byte, then flags and members
+        let af_size = 1 + members;
+        if af_size > packet::PAYLOAD_SIZE {
+            return Err(TsError::AdaptationFieldOverflow(members.min(0xff) as u8));
+        }
+        Ok(packet::PAYLOAD_SIZE - af_size)
+    }
+
+    /// Build a new packet, returning it with the number of payload bytes written
+    pub fn build(&self) -> Result<(packet::Packet, usize), TsError> {
+        let mut pkt = [0xff; packet::PACKET_SIZE];
+        let written = self.write(&mut pkt)?;
+        Ok((pkt, written))
+    }
+
+    /// Overwrite an existing packet, returning the number of payload bytes written
+    pub fn write(&self, pkt: &mut packet::Packet) -> Result<usize, TsError> {
+        let capacity = self.payload_capacity()?;
+        let written = self.payload.len().min(capacity);
+        let stuffing = capacity - written;
+        let members = self.adaptation_members();
+
+        for byte in pkt.iter_mut() {
+            *byte = 0xff;
+        }
+        pkt[0] = packet::SYNC_BYTE;
+        pkt[1] = 0x00;
+        pkt[3] = 0x00;
+        packet::set_pid(pkt, self.pid);
+        packet::set_scrambling(pkt, self.scrambling);
+        packet::set_continuity_counter(pkt, self.continuity_counter);
+        if self.transport_error {
+            packet::set_transport_error(pkt);
+        }
+        if self.unit_start {
+            packet::set_unit_start(pkt);
+        }
+        if self.transport_priority {
+            packet::set_transport_priority(pkt);
+        }
+        if written > 0 {
+            packet::set_payload(pkt);
+        }
+
+        if members > 0 || stuffing > 0 {
+            let length = if members > 0 {
+                members + stuffing
+            } else {
+                stuffing - 1
+            };
+            packet::set_adaptation_field(pkt, length as u8);
+        }
+        if members > 0 {
+            if self.discontinuity {
+                packet::set_discontinuity(pkt);
+            }
+            if self.random_access {
+                packet::set_random_access(pkt);
+            }
+            if self.stream_priority {
+                packet::set_stream_priority(pkt);
+            }
+            if let Some(pcr) = self.pcr {
+                packet::set_pcr_clock(pkt, pcr);
+            }
+            if let Some(opcr) = self.opcr {
+                packet::set_opcr(pkt, opcr.base(), opcr.ext())?;
+            }
+            if let Some(countdown) = self.splice_countdown {
+                packet::set_splice_countdown(pkt, countdown)?;
+            }
+            if let Some(data) = self.private_data {
+                packet::set_transport_private_data(pkt, data)?;
+            }
+        }
+
+        pkt[packet::PACKET_SIZE - written..].copy_from_slice(&self.payload[..written]);
+        Ok(written)
+    }
+
+    /// Size of the adaptation field flags and members, excluding stuffing,
+    /// or zero if no member is needed
+    fn adaptation_members(&self) -> usize {
+        let flagged = self.discontinuity || self.random_access || self.stream_priority;
+        let mut size = 0;
+        if self.pcr.is_some() {
+            size += 6;
+        }
+        if self.opcr.is_some() {
+            size += 6;
+        }
+        if self.splice_countdown.is_some() {
+            size += 1;
+        }
+        if let Some(data) = self.private_data {
+            size += 1 + data.len();
+        }
+        if size > 0 || flagged {
+            size + 1
+        } else {
+            0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_full_payload() {
+        let payload = [0x55; 200];
+        let (pkt, written) = PacketBuilder::new(0x100)
+            .continuity_counter(9)
+            .unit_start()
+            .payload(&payload)
+            .build()
+            .unwrap();
+        assert_eq!(written, packet::PAYLOAD_SIZE);
+        assert_eq!(packet::validate(&pkt), Ok(()));
+        assert_eq!(packet::pid(&pkt), 0x100);
+        assert_eq!(packet::continuity_counter(&pkt), 9);
+        assert!(packet::has_unit_start(&pkt));
+        assert!(!packet::has_transport_error(&pkt));
+        assert!(!packet::has_transport_priority(&pkt));
+        assert_eq!(packet::scrambling(&pkt), packet::SCRAMBLING_CLEAR);
+        assert!(!packet::has_adaptation_field(&pkt));
+        assert_eq!(packet::payload(&pkt), Some(&payload[..written]));
+    }
+
+    #[test]
+    fn test_single_stuffing_byte() {
+        let payload = [0x55; packet::PAYLOAD_SIZE - 1];
+        let (pkt, written) = PacketBuilder::new(0x100).payload(&payload).build().unwrap();
+        assert_eq!(written, payload.len());
+        assert!(packet::has_adaptation_field(&pkt));
+        assert_eq!(packet::adaptation_field(&pkt), 0);
+        assert_eq!(packet::payload(&pkt), Some(&payload[..]));
+    }
+
+    #[test]
+    fn test_stuffing() {
+        let payload = [1, 2, 3];
+        let (pkt, written) = PacketBuilder::new(0x100).payload(&payload).build().unwrap();
+        assert_eq!(written, 3);
+        assert_eq!(packet::adaptation_field(&pkt) as usize, packet::PAYLOAD_SIZE - 4);
+        assert_eq!(pkt[5], 0x00);
+        assert!(pkt[6..packet::PACKET_SIZE - 3].iter().all(|&b| b == 0xff));
+        assert_eq!(packet::payload(&pkt), Some(&payload[..]));
+    }
+
+    #[test]
+    fn test_adaptation_only() {
+        let clock = Pcr::new(12345, 67);
+        let (pkt, written) = PacketBuilder::new(0x101)
+            .continuity_counter(3)
+            .pcr(clock)
+            .build()
+            .unwrap();
+        assert_eq!(written, 0);
+        assert!(!packet::has_payload(&pkt));
+        assert_eq!(packet::adaptation_field(&pkt), packet::MAX_ADAPTATION_FIELD_LENGTH);
+        assert_eq!(packet::pcr_clock(&pkt), Some(clock));
+        assert_eq!(packet::continuity_counter(&pkt), 3);
+        assert_eq!(packet::validate(&pkt), Ok(()));
+    }
+
+    #[test]
+    fn test_all_members() {
+        let payload = [0xaa; packet::PAYLOAD_SIZE];
+        let builder = PacketBuilder::new(0x200)
+            .scrambling(ScramblingControl::Odd)
+            .continuity_counter(15)
+            .transport_priority()
+            .discontinuity()
+            .random_access()
+            .stream_priority()
+            .pcr(Pcr::new(1, 2))
+            .opcr(Pcr::new(3, 4))
+            .splice_countdown(-1)
+            .private_data(&[9, 8, 7])
+            .payload(&payload);
+        assert_eq!(builder.payload_capacity(), Ok(packet::PAYLOAD_SIZE - 19));
+        let (pkt, written) = builder.build().unwrap();
+        assert_eq!(written, packet::PAYLOAD_SIZE - 19);
+        assert_eq!(packet::adaptation_field(&pkt), 18);
+        assert_eq!(packet::scrambling_control(&pkt), ScramblingControl::Odd);
+        assert_eq!(packet::continuity_counter(&pkt), 15);
+        assert!(packet::has_payload(&pkt));
+        assert!(packet::has_transport_priority(&pkt));
+        assert!(packet::has_discontinuity(&pkt));
+        assert!(packet::has_random_access(&pkt));
+        assert!(packet::has_stream_priority(&pkt));
+        assert_eq!(packet::pcr_clock(&pkt), Some(Pcr::new(1, 2)));
+        assert_eq!(packet::opcr_clock(&pkt), Some(Pcr::new(3, 4)));
+        assert_eq!(packet::splice_countdown(&pkt), Some(-1));
+        assert_eq!(packet::transport_private_data(&pkt), Some(&[9u8, 8, 7][..]));
+        assert_eq!(packet::payload(&pkt), Some(&payload[..written]));
+    }
+
+    #[test]
+    fn test_private_data_too_long() {
+        let data = [0; packet::PAYLOAD_SIZE];
+        let builder = PacketBuilder::new(0x100).private_data(&data);
+        assert!(builder.payload_capacity().is_err());
+        assert!(builder.build().is_err());
+    }
+}
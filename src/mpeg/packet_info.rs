@@ -1,4 +1,10 @@
-use super::packet;
+use core::convert::TryFrom;
+use core::fmt;
+
+use super::dump::{self, Hexdump};
+use super::packet::{self, ScramblingControl};
+use super::pcr::Pcr;
+use crate::error::TsError;
 
 /// A higher-level representation of a transport stream packet.
 #[derive(Clone, Copy)]
@@ -14,6 +20,21 @@ impl PacketInfo {
         }
     }
 
+    /// Get the underlying packet bytes
+    pub fn as_packet(&self) -> &packet::Packet {
+        &self.packet
+    }
+
+    /// Get the underlying packet bytes for modification
+    pub fn as_packet_mut(&mut self) -> &mut packet::Packet {
+        &mut self.packet
+    }
+
+    /// Check the sync byte and the adaptation field layout
+    pub fn validate(&self) -> Result<(), TsError> {
+        packet::validate(&self.packet)
+    }
+
     /// Set the transport error indicator
     pub fn set_transport_error(&mut self) {
         packet::set_transport_error(&mut self.packet);
@@ -39,6 +60,22 @@ impl PacketInfo {
         packet::section(&self.packet)
     }
 
+    /// Get the MPEG section, if present, checking the adaptation field length
+    pub fn try_section(&self) -> Result<Option<&[u8]>, TsError> {
+        packet::try_section(&self.packet)
+    }
+
+    /// Get the pointer_field of a packet starting a section, if present
+    pub fn pointer_field(&self) -> Option<u8> {
+        packet::pointer_field(&self.packet)
+    }
+
+    /// Get the pointer_field of a packet starting a section, if present,
+    /// checking it points inside the payload
+    pub fn try_pointer_field(&self) -> Result<Option<u8>, TsError> {
+        packet::try_pointer_field(&self.packet)
+    }
+
     /// Set the transport priority indicator
     pub fn set_transport_priority(&mut self) {
         packet::set_transport_priority(&mut self.packet);
@@ -64,11 +101,21 @@ impl PacketInfo {
         packet::payload(&self.packet)
     }
 
+    /// Get the payload as a slice of bytes, checking the adaptation field length
+    pub fn try_payload(&self) -> Result<Option<&[u8]>, TsError> {
+        packet::try_payload(&self.packet)
+    }
+
     /// Sets the PID. Max: 8191 (0x1fff)
     pub fn set_pid(&mut self, pid: u16) {
         packet::set_pid(&mut self.packet, pid);
     }
 
+    /// Set the PID, failing if it is above 8191 (0x1fff)
+    pub fn try_set_pid(&mut self, pid: u16) -> Result<(), TsError> {
+        packet::try_set_pid(&mut self.packet, pid)
+    }
+
     /// Get the value of the pid
     pub fn pid(&self) -> u16 {
         packet::pid(&self.packet)
@@ -94,6 +141,12 @@ impl PacketInfo {
         packet::set_adaptation_field(&mut self.packet, length);
     }
 
+    /// Set the adaptation field length (&indicator).
+    /// Fails if the length does not fit in the packet.
+    pub fn try_set_adaptation_field(&mut self, length: u8) -> Result<(), TsError> {
+        packet::try_set_adaptation_field(&mut self.packet, length)
+    }
+
     /// Is the adaptation field indicator set?
     pub fn has_adaptation_field(&self) -> bool {
         packet::has_adaptation_field(&self.packet)
@@ -104,16 +157,36 @@ impl PacketInfo {
         packet::adaptation_field(&self.packet)
     }
 
+    /// Get the adaptation field length, checking it is present and fits in the packet
+    pub fn try_adaptation_field(&self) -> Result<u8, TsError> {
+        packet::try_adaptation_field(&self.packet)
+    }
+
     /// Set the scrambling mode
     pub fn set_scrambling(&mut self, scrambling: u8) {
         packet::set_scrambling(&mut self.packet, scrambling);
     }
 
+    /// Set the scrambling control field
+    pub fn set_scrambling_control(&mut self, scrambling: ScramblingControl) {
+        packet::set_scrambling_control(&mut self.packet, scrambling);
+    }
+
     /// Get the scrambling mode
     pub fn scrambling(&self) -> u8 {
         packet::scrambling(&self.packet)
     }
 
+    /// Get the scrambling control field
+    pub fn scrambling_control(&self) -> ScramblingControl {
+        packet::scrambling_control(&self.packet)
+    }
+
+    /// Get the scrambling mode, rejecting the reserved value
+    pub fn try_scrambling(&self) -> Result<u8, TsError> {
+        packet::try_scrambling(&self.packet)
+    }
+
     /// Set the discontinuity indicator
     pub fn set_discontinuity(&mut self) {
         packet::set_discontinuity(&mut self.packet);
@@ -129,6 +202,11 @@ impl PacketInfo {
         packet::has_discontinuity(&self.packet)
     }
 
+    /// Is the discontinuity indicator set? Fails without an adaptation field.
+    pub fn try_has_discontinuity(&self) -> Result<bool, TsError> {
+        packet::try_has_discontinuity(&self.packet)
+    }
+
     /// Set the random access indicator
     pub fn set_random_access(&mut self) {
         packet::set_random_access(&mut self.packet);
@@ -139,6 +217,11 @@ impl PacketInfo {
         packet::has_random_access(&self.packet)
     }
 
+    /// Is the random access indicator set? Fails without an adaptation field.
+    pub fn try_has_random_access(&self) -> Result<bool, TsError> {
+        packet::try_has_random_access(&self.packet)
+    }
+
     /// Set the stream priority indicator
     pub fn set_stream_priority(&mut self) {
         packet::set_stream_priority(&mut self.packet);
@@ -149,6 +232,11 @@ impl PacketInfo {
         packet::has_stream_priority(&self.packet)
     }
 
+    /// Is the stream priority indicator set? Fails without an adaptation field.
+    pub fn try_has_stream_priority(&self) -> Result<bool, TsError> {
+        packet::try_has_stream_priority(&self.packet)
+    }
+
     /// Set the programme clock reference
     pub fn set_pcr(&mut self, pcr: u64) {
         packet::set_pcr(&mut self.packet, pcr);
@@ -160,11 +248,23 @@ impl PacketInfo {
         packet::has_pcr(&self.packet)
     }
 
+    /// Does the packet have a programme clock reference field?
+    /// Fails without an adaptation field.
+    pub fn try_has_pcr(&self) -> Result<bool, TsError> {
+        packet::try_has_pcr(&self.packet)
+    }
+
     /// Get the programme clock reference
     pub fn pcr(&self) -> u64 {
         packet::pcr(&self.packet)
     }
 
+    /// Get the programme clock reference, if present and fully contained
+    /// in the adaptation field
+    pub fn try_pcr(&self) -> Result<Option<u64>, TsError> {
+        packet::try_pcr(&self.packet)
+    }
+
     /// Set the programme clock reference extension
     pub fn set_pcr_ext(&mut self, ext: u16) {
         packet::set_pcr_ext(&mut self.packet, ext);
@@ -174,6 +274,203 @@ impl PacketInfo {
     pub fn pcr_ext(&self) -> u16 {
         packet::pcr_ext(&self.packet)
     }
+
+    /// Get the programme clock reference extension, if present and fully
+    /// contained in the adaptation field
+    pub fn try_pcr_ext(&self) -> Result<Option<u16>, TsError> {
+        packet::try_pcr_ext(&self.packet)
+    }
+
+    /// Set the programme clock reference base and extension together
+    pub fn set_pcr_clock(&mut self, pcr: Pcr) {
+        packet::set_pcr_clock(&mut self.packet, pcr);
+    }
+
+    /// Get the programme clock reference base and extension together, if present
+    pub fn pcr_clock(&self) -> Option<Pcr> {
+        packet::pcr_clock(&self.packet)
+    }
+
+    /// Get the programme clock reference base and extension together, if
+    /// present and fully contained in the adaptation field
+    pub fn try_pcr_clock(&self) -> Result<Option<Pcr>, TsError> {
+        packet::try_pcr_clock(&self.packet)
+    }
+
+    /// Set the original programme clock reference, inserting it into the
+    /// adaptation field if it is not already present
+    pub fn set_opcr(&mut self, opcr: u64, ext: u16) -> Result<(), TsError> {
+        packet::set_opcr(&mut self.packet, opcr, ext)
+    }
+
+    /// Does the packet have an original programme clock reference field?
+    /// Only valid if it has an adaptation field.
+    pub fn has_opcr(&self) -> bool {
+        packet::has_opcr(&self.packet)
+    }
+
+    /// Get the original programme clock reference, if present
+    pub fn opcr(&self) -> Option<u64> {
+        packet::opcr(&self.packet)
+    }
+
+    /// Get the original programme clock reference, if present, checking the
+    /// adaptation field layout
+    pub fn try_opcr(&self) -> Result<Option<u64>, TsError> {
+        packet::try_opcr(&self.packet)
+    }
+
+    /// Get the original programme clock reference extension, if present
+    pub fn opcr_ext(&self) -> Option<u16> {
+        packet::opcr_ext(&self.packet)
+    }
+
+    /// Get the original programme clock reference extension, if present,
+    /// checking the adaptation field layout
+    pub fn try_opcr_ext(&self) -> Result<Option<u16>, TsError> {
+        packet::try_opcr_ext(&self.packet)
+    }
+
+    /// Get the original programme clock reference base and extension together,
+    /// if present
+    pub fn opcr_clock(&self) -> Option<Pcr> {
+        packet::opcr_clock(&self.packet)
+    }
+
+    /// Get the original programme clock reference base and extension together,
+    /// if present, checking the adaptation field layout
+    pub fn try_opcr_clock(&self) -> Result<Option<Pcr>, TsError> {
+        packet::try_opcr_clock(&self.packet)
+    }
+
+    /// Set the splicing point flag and the splice countdown
+    pub fn set_splice_countdown(&mut self, countdown: i8) -> Result<(), TsError> {
+        packet::set_splice_countdown(&mut self.packet, countdown)
+    }
+
+    /// Is the splicing point flag set?
+    /// Only valid if it has an adaptation field.
+    pub fn has_splicing_point(&self) -> bool {
+        packet::has_splicing_point(&self.packet)
+    }
+
+    /// Get the splice countdown, if present
+    pub fn splice_countdown(&self) -> Option<i8> {
+        packet::splice_countdown(&self.packet)
+    }
+
+    /// Get the splice countdown, if present, checking the adaptation field layout
+    pub fn try_splice_countdown(&self) -> Result<Option<i8>, TsError> {
+        packet::try_splice_countdown(&self.packet)
+    }
+
+    /// Set the transport private data, replacing any already present
+    pub fn set_transport_private_data(&mut self, data: &[u8]) -> Result<(), TsError> {
+        packet::set_transport_private_data(&mut self.packet, data)
+    }
+
+    /// Is the transport private data flag set?
+    /// Only valid if it has an adaptation field.
+    pub fn has_transport_private_data(&self) -> bool {
+        packet::has_transport_private_data(&self.packet)
+    }
+
+    /// Get the transport private data, if present
+    pub fn transport_private_data(&self) -> Option<&[u8]> {
+        packet::transport_private_data(&self.packet)
+    }
+
+    /// Get the transport private data, if present, checking the adaptation
+    /// field layout
+    pub fn try_transport_private_data(&self) -> Result<Option<&[u8]>, TsError> {
+        packet::try_transport_private_data(&self.packet)
+    }
+
+    /// Add an empty adaptation field extension, if not already present
+    pub fn set_adaptation_field_extension(&mut self) -> Result<(), TsError> {
+        packet::set_adaptation_field_extension(&mut self.packet)
+    }
+
+    /// Is the adaptation field extension flag set?
+    /// Only valid if it has an adaptation field.
+    pub fn has_adaptation_field_extension(&self) -> bool {
+        packet::has_adaptation_field_extension(&self.packet)
+    }
+
+    /// Set the legal time window, adding the extension if needed
+    pub fn set_ltw(&mut self, valid: bool, offset: u16) -> Result<(), TsError> {
+        packet::set_ltw(&mut self.packet, valid, offset)
+    }
+
+    /// Get the legal time window valid flag, if present
+    pub fn ltw_valid(&self) -> Option<bool> {
+        packet::ltw_valid(&self.packet)
+    }
+
+    /// Get the legal time window valid flag, if present, checking the
+    /// adaptation field layout
+    pub fn try_ltw_valid(&self) -> Result<Option<bool>, TsError> {
+        packet::try_ltw_valid(&self.packet)
+    }
+
+    /// Get the legal time window offset, if present
+    pub fn ltw_offset(&self) -> Option<u16> {
+        packet::ltw_offset(&self.packet)
+    }
+
+    /// Get the legal time window offset, if present, checking the adaptation
+    /// field layout
+    pub fn try_ltw_offset(&self) -> Result<Option<u16>, TsError> {
+        packet::try_ltw_offset(&self.packet)
+    }
+
+    /// Set the piecewise rate (22 bits), adding the extension if needed
+    pub fn set_piecewise_rate(&mut self, rate: u32) -> Result<(), TsError> {
+        packet::set_piecewise_rate(&mut self.packet, rate)
+    }
+
+    /// Get the piecewise rate, if present
+    pub fn piecewise_rate(&self) -> Option<u32> {
+        packet::piecewise_rate(&self.packet)
+    }
+
+    /// Get the piecewise rate, if present, checking the adaptation field layout
+    pub fn try_piecewise_rate(&self) -> Result<Option<u32>, TsError> {
+        packet::try_piecewise_rate(&self.packet)
+    }
+
+    /// Set the seamless splice type and DTS of the next access unit,
+    /// adding the extension if needed
+    pub fn set_seamless_splice(&mut self, splice_type: u8, dts_next_au: u64) -> Result<(), TsError> {
+        packet::set_seamless_splice(&mut self.packet, splice_type, dts_next_au)
+    }
+
+    /// Get the seamless splice type, if present
+    pub fn splice_type(&self) -> Option<u8> {
+        packet::splice_type(&self.packet)
+    }
+
+    /// Get the seamless splice type, if present, checking the adaptation field
+    /// layout
+    pub fn try_splice_type(&self) -> Result<Option<u8>, TsError> {
+        packet::try_splice_type(&self.packet)
+    }
+
+    /// Get the DTS of the next access unit, if present
+    pub fn dts_next_au(&self) -> Option<u64> {
+        packet::dts_next_au(&self.packet)
+    }
+
+    /// Get the DTS of the next access unit, if present, checking the
+    /// adaptation field layout
+    pub fn try_dts_next_au(&self) -> Result<Option<u64>, TsError> {
+        packet::try_dts_next_au(&self.packet)
+    }
+
+    /// Get a hexdump view of the payload, if present
+    pub fn payload_hexdump(&self) -> Option<Hexdump<'_>> {
+        self.payload().map(Hexdump::new)
+    }
 }
 
 impl From<packet::Packet> for PacketInfo {
@@ -184,9 +481,21 @@ impl From<packet::Packet> for PacketInfo {
     }
 }
 
-impl Into<packet::Packet> for PacketInfo {
-    fn into(self) -> packet::Packet {
-        self.packet
+impl From<PacketInfo> for packet::Packet {
+    fn from(info: PacketInfo) -> Self {
+        info.packet
+    }
+}
+
+impl TryFrom<&[u8]> for PacketInfo {
+    type Error = TsError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        let pkt = packet::Packet::try_from(bytes).map_err(|_| TsError::BadLength(bytes.len()))?;
+        if pkt[0] != packet::SYNC_BYTE {
+            return Err(TsError::BadSync(pkt[0]));
+        }
+        Ok(PacketInfo::from(pkt))
     }
 }
 
@@ -196,6 +505,18 @@ impl Default for PacketInfo {
     }
 }
 
+impl fmt::Debug for PacketInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        dump::fmt_debug("PacketInfo", &self.packet, f)
+    }
+}
+
+impl fmt::Display for PacketInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        dump::fmt_summary(&self.packet, f)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -207,8 +528,8 @@ mod tests {
         assert_eq!(p.continuity_counter(), 0);
         assert!(p.has_payload());
         let payload = p.payload().unwrap();
-        for i in 0..payload.len() {
-            assert_eq!(payload[i], 0xff);
+        for byte in payload.iter() {
+            assert_eq!(*byte, 0xff);
         }
         assert!(p.has_discontinuity());
         assert!(!p.has_adaptation_field());
@@ -357,4 +678,77 @@ mod tests {
         p.set_pcr_ext(ext);
         assert_eq!(p.pcr_ext(), ext);
     }
+
+    #[test]
+    fn test_try_from_slice() {
+        let pkt = packet::null_packet();
+        assert!(PacketInfo::try_from(&pkt[..]).is_ok());
+        assert_eq!(PacketInfo::try_from(&pkt[1..]).err(), Some(TsError::BadLength(187)));
+        let bad = [0u8; packet::PACKET_SIZE];
+        assert_eq!(PacketInfo::try_from(&bad[..]).err(), Some(TsError::BadSync(0)));
+    }
+
+    #[test]
+    fn test_try_accessors() {
+        let mut p = PacketInfo::null_packet();
+        assert_eq!(p.validate(), Ok(()));
+        assert_eq!(p.try_has_pcr(), Err(TsError::MissingAdaptationField));
+        assert_eq!(
+            p.try_set_adaptation_field(200),
+            Err(TsError::AdaptationFieldOverflow(200))
+        );
+        p.try_set_adaptation_field(7).unwrap();
+        p.set_pcr(23647);
+        assert_eq!(p.try_pcr(), Ok(Some(23647)));
+        assert_eq!(p.try_payload().unwrap().unwrap().len(), 176);
+    }
+
+    #[test]
+    fn test_adaptation_field_members() {
+        let mut p = PacketInfo::null_packet();
+        p.set_adaptation_field(24);
+        p.set_opcr(1234, 56).unwrap();
+        p.set_splice_countdown(2).unwrap();
+        p.set_transport_private_data(&[0xde, 0xad]).unwrap();
+        p.set_piecewise_rate(1000).unwrap();
+        assert_eq!(p.opcr(), Some(1234));
+        assert_eq!(p.opcr_ext(), Some(56));
+        assert_eq!(p.splice_countdown(), Some(2));
+        assert_eq!(p.transport_private_data(), Some(&[0xdeu8, 0xad][..]));
+        assert!(p.has_adaptation_field_extension());
+        assert_eq!(p.piecewise_rate(), Some(1000));
+        assert_eq!(p.ltw_offset(), None);
+        assert_eq!(p.try_dts_next_au(), Ok(None));
+    }
+
+    #[test]
+    fn test_pcr_clock() {
+        let mut p = PacketInfo::null_packet();
+        p.set_adaptation_field(7);
+        let clock = Pcr::new(90_000, 150);
+        p.set_pcr_clock(clock);
+        assert_eq!(p.pcr_clock(), Some(clock));
+        assert_eq!(p.opcr_clock(), None);
+    }
+
+    #[test]
+    fn test_scrambling_control() {
+        let mut p = PacketInfo::null_packet();
+        p.set_continuity_counter(6);
+        p.set_scrambling_control(ScramblingControl::Odd);
+        assert_eq!(p.scrambling_control(), ScramblingControl::Odd);
+        assert_eq!(p.continuity_counter(), 6);
+        assert!(p.has_payload());
+    }
+
+    #[test]
+    fn test_fmt() {
+        let mut p = PacketInfo::null_packet();
+        p.set_pid(0x20);
+        assert_eq!(std::format!("{}", p), "PID 0x0020 CC  0 payload 184");
+        assert!(std::format!("{:?}", p).starts_with("PacketInfo { pid: 32,"));
+        let dump = std::format!("{}", p.payload_hexdump().unwrap());
+        assert!(dump.starts_with("0000: ff ff"));
+        assert!(dump.ends_with("00b0: ff ff ff ff ff ff ff ff                         |........|"));
+    }
 }
@@ -0,0 +1,439 @@
+use core::fmt;
+
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, Serializer};
+
+use super::packet::{self, Packet, ScramblingControl, HEADER_SIZE, PACKET_SIZE, SYNC_BYTE};
+use super::packet_info::PacketInfo;
+use super::packet_ref::{PacketMut, PacketRef};
+use super::pcr::Pcr;
+use crate::error::TsError;
+
+/// Up to a packet's worth of bytes, serialized as a byte sequence
+struct Bytes {
+    len: usize,
+    data: [u8; PACKET_SIZE],
+}
+
+impl Bytes {
+    fn new(bytes: &[u8]) -> Self {
+        let mut data = [0; PACKET_SIZE];
+        data[..bytes.len()].copy_from_slice(bytes);
+        Bytes {
+            len: bytes.len(),
+            data,
+        }
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        &self.data[..self.len]
+    }
+}
+
+impl Serialize for Bytes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(self.as_slice())
+    }
+}
+
+impl<'de> Deserialize<'de> for Bytes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_bytes(BytesVisitor)
+    }
+}
+
+struct BytesVisitor;
+
+impl<'de> Visitor<'de> for BytesVisitor {
+    type Value = Bytes;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "at most {} bytes", PACKET_SIZE)
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Bytes, E> {
+        if v.len() > PACKET_SIZE {
+            return Err(E::invalid_length(v.len(), &self));
+        }
+        Ok(Bytes::new(v))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Bytes, A::Error> {
+        let mut bytes = Bytes::new(&[]);
+        while let Some(byte) = seq.next_element::<u8>()? {
+            if bytes.len == PACKET_SIZE {
+                return Err(de::Error::invalid_length(bytes.len + 1, &self));
+            }
+            bytes.data[bytes.len] = byte;
+            bytes.len += 1;
+        }
+        Ok(bytes)
+    }
+}
+
+/// The decoded fields of a packet, as they are serialized. A packet whose
+/// adaptation field cannot be decoded keeps only its header fields, with the
+/// whole packet in `raw` and the reason in `error`.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename = "Packet")]
+struct PacketRecord {
+    pid: u16,
+    transport_error: bool,
+    unit_start: bool,
+    transport_priority: bool,
+    scrambling: ScramblingControl,
+    continuity_counter: u8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    adaptation_field: Option<AdaptationFieldRecord>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    payload: Option<Bytes>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    raw: Option<Bytes>,
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    error: Option<TsError>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename = "AdaptationField")]
+struct AdaptationFieldRecord {
+    length: u8,
+    #[serde(default)]
+    discontinuity: bool,
+    #[serde(default)]
+    random_access: bool,
+    #[serde(default)]
+    stream_priority: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pcr: Option<Pcr>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    opcr: Option<Pcr>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    splice_countdown: Option<i8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    private_data: Option<Bytes>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    extension: Option<ExtensionRecord>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename = "AdaptationFieldExtension")]
+struct ExtensionRecord {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ltw_valid: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ltw_offset: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    piecewise_rate: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    splice_type: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dts_next_au: Option<u64>,
+}
+
+impl PacketRecord {
+    fn from_packet(pkt: &Packet) -> Self {
+        let mut record = PacketRecord {
+            pid: packet::pid(pkt),
+            transport_error: packet::has_transport_error(pkt),
+            unit_start: packet::has_unit_start(pkt),
+            transport_priority: packet::has_transport_priority(pkt),
+            scrambling: packet::scrambling_control(pkt),
+            continuity_counter: packet::continuity_counter(pkt),
+            adaptation_field: None,
+            payload: None,
+            raw: None,
+            error: None,
+        };
+        if let Err(err) = record.decode(pkt) {
+            record.adaptation_field = None;
+            record.payload = None;
+            record.raw = Some(Bytes::new(pkt));
+            record.error = Some(err);
+        }
+        record
+    }
+
+    fn decode(&mut self, pkt: &Packet) -> Result<(), TsError> {
+        if packet::has_adaptation_field(pkt) {
+            self.adaptation_field = Some(AdaptationFieldRecord::from_packet(pkt)?);
+        }
+        self.payload = packet::try_payload(pkt)?.map(Bytes::new);
+        Ok(())
+    }
+
+    fn to_packet(&self) -> Result<Packet, TsError> {
+        if let Some(ref raw) = self.raw {
+            return raw_packet(raw.as_slice());
+        }
+        let mut pkt = packet::null_packet();
+        pkt[1] = 0;
+        pkt[3] = 0;
+        packet::try_set_pid(&mut pkt, self.pid)?;
+        if self.transport_error {
+            packet::set_transport_error(&mut pkt);
+        }
+        if self.unit_start {
+            packet::set_unit_start(&mut pkt);
+        }
+        if self.transport_priority {
+            packet::set_transport_priority(&mut pkt);
+        }
+        packet::set_scrambling_control(&mut pkt, self.scrambling);
+        packet::set_continuity_counter(&mut pkt, self.continuity_counter);
+        let mut start = HEADER_SIZE;
+        if let Some(ref adaptation_field) = self.adaptation_field {
+            adaptation_field.write(&mut pkt)?;
+            start += 1 + adaptation_field.length as usize;
+        }
+        if let Some(ref payload) = self.payload {
+            if payload.len != PACKET_SIZE - start {
+                return Err(TsError::BadLength(payload.len));
+            }
+            packet::set_payload(&mut pkt);
+            pkt[start..].copy_from_slice(payload.as_slice());
+        }
+        Ok(pkt)
+    }
+}
+
+impl AdaptationFieldRecord {
+    fn from_packet(pkt: &Packet) -> Result<Self, TsError> {
+        let length = packet::try_adaptation_field(pkt)?;
+        let mut record = AdaptationFieldRecord {
+            length,
+            discontinuity: false,
+            random_access: false,
+            stream_priority: false,
+            pcr: None,
+            opcr: None,
+            splice_countdown: None,
+            private_data: None,
+            extension: None,
+        };
+        if length == 0 {
+            return Ok(record);
+        }
+        record.discontinuity = packet::try_has_discontinuity(pkt)?;
+        record.random_access = packet::try_has_random_access(pkt)?;
+        record.stream_priority = packet::try_has_stream_priority(pkt)?;
+        record.pcr = packet::try_pcr_clock(pkt)?;
+        record.opcr = packet::try_opcr_clock(pkt)?;
+        record.splice_countdown = packet::try_splice_countdown(pkt)?;
+        record.private_data = packet::try_transport_private_data(pkt)?.map(Bytes::new);
+        if packet::has_adaptation_field_extension(pkt) {
+            record.extension = Some(ExtensionRecord {
+                ltw_valid: packet::try_ltw_valid(pkt)?,
+                ltw_offset: packet::try_ltw_offset(pkt)?,
+                piecewise_rate: packet::try_piecewise_rate(pkt)?,
+                splice_type: packet::try_splice_type(pkt)?,
+                dts_next_au: packet::try_dts_next_au(pkt)?,
+            });
+        }
+        Ok(record)
+    }
+
+    fn write(&self, pkt: &mut Packet) -> Result<(), TsError> {
+        packet::try_set_adaptation_field(pkt, self.length)?;
+        if self.length == 0 {
+            return Ok(());
+        }
+        if self.discontinuity {
+            packet::set_discontinuity(pkt);
+        }
+        if self.random_access {
+            packet::set_random_access(pkt);
+        }
+        if self.stream_priority {
+            packet::set_stream_priority(pkt);
+        }
+        if let Some(pcr) = self.pcr {
+            if (self.length as usize) < packet::HEADER_SIZE_PCR - HEADER_SIZE - 1 {
+                return Err(TsError::TruncatedAdaptationField(self.length));
+            }
+            packet::set_pcr_clock(pkt, pcr);
+        }
+        if let Some(opcr) = self.opcr {
+            packet::set_opcr(pkt, opcr.base(), opcr.ext())?;
+        }
+        if let Some(countdown) = self.splice_countdown {
+            packet::set_splice_countdown(pkt, countdown)?;
+        }
+        if let Some(ref data) = self.private_data {
+            packet::set_transport_private_data(pkt, data.as_slice())?;
+        }
+        if let Some(ref extension) = self.extension {
+            packet::set_adaptation_field_extension(pkt)?;
+            if let Some(offset) = extension.ltw_offset {
+                packet::set_ltw(pkt, extension.ltw_valid.unwrap_or(false), offset)?;
+            }
+            if let Some(rate) = extension.piecewise_rate {
+                packet::set_piecewise_rate(pkt, rate)?;
+            }
+            if extension.splice_type.is_some() || extension.dts_next_au.is_some() {
+                packet::set_seamless_splice(
+                    pkt,
+                    extension.splice_type.unwrap_or(0),
+                    extension.dts_next_au.unwrap_or(0),
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Take a `raw` packet as is, checking only its length and sync byte
+fn raw_packet(raw: &[u8]) -> Result<Packet, TsError> {
+    let mut pkt = [0; PACKET_SIZE];
+    if raw.len() != PACKET_SIZE {
+        return Err(TsError::BadLength(raw.len()));
+    }
+    if raw[0] != SYNC_BYTE {
+        return Err(TsError::BadSync(raw[0]));
+    }
+    pkt.copy_from_slice(raw);
+    Ok(pkt)
+}
+
+fn serialize_packet<S: Serializer>(pkt: &Packet, serializer: S) -> Result<S::Ok, S::Error> {
+    PacketRecord::from_packet(pkt).serialize(serializer)
+}
+
+/// Serialized as its decoded header, adaptation field and payload
+impl Serialize for PacketInfo {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_packet(self.as_packet(), serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for PacketInfo {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        PacketRecord::deserialize(deserializer)?
+            .to_packet()
+            .map(PacketInfo::from)
+            .map_err(de::Error::custom)
+    }
+}
+
+impl<'a> Serialize for PacketRef<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_packet(self.as_packet(), serializer)
+    }
+}
+
+impl<'a> Serialize for PacketMut<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_packet(self.as_packet(), serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpeg::continuity::ContinuityEvent;
+    use crate::mpeg::pid::Pid;
+    use std::string::String;
+
+    fn round_trip(info: &PacketInfo) -> (String, PacketInfo) {
+        let json = serde_json::to_string(info).unwrap();
+        let back: PacketInfo = serde_json::from_str(&json).unwrap();
+        (json, back)
+    }
+
+    #[test]
+    fn test_null_packet() {
+        let info = PacketInfo::null_packet();
+        let (json, back) = round_trip(&info);
+        assert!(json.starts_with(
+            r#"{"pid":8191,"transport_error":false,"unit_start":false,"transport_priority":false,"scrambling":"Clear","continuity_counter":0,"payload":[255,"#
+        ));
+        assert_eq!(back.as_packet()[..], info.as_packet()[..]);
+    }
+
+    #[test]
+    fn test_adaptation_field() {
+        let mut pkt = packet::null_packet();
+        packet::set_pid(&mut pkt, 0x100);
+        packet::set_unit_start(&mut pkt);
+        packet::set_continuity_counter(&mut pkt, 3);
+        packet::set_adaptation_field(&mut pkt, 40);
+        packet::set_random_access(&mut pkt);
+        packet::set_pcr_clock(&mut pkt, Pcr::new(12345, 67));
+        packet::set_opcr(&mut pkt, 999, 1).unwrap();
+        packet::set_splice_countdown(&mut pkt, -2).unwrap();
+        packet::set_transport_private_data(&mut pkt, b"abc").unwrap();
+        packet::set_ltw(&mut pkt, true, 0x1234).unwrap();
+        packet::set_seamless_splice(&mut pkt, 2, 0x1_2345_6789).unwrap();
+        let info = PacketInfo::from(pkt);
+        let (json, back) = round_trip(&info);
+        assert!(json.contains(r#""pcr":3703567,"#));
+        assert!(json.contains(r#""private_data":[97,98,99]"#));
+        assert!(json.contains(r#""extension":{"ltw_valid":true,"ltw_offset":4660,"splice_type":2,"dts_next_au":4886718345}"#));
+        assert_eq!(back.as_packet()[..], pkt[..]);
+    }
+
+    #[test]
+    fn test_truncated_extension() {
+        let mut pkt = packet::null_packet();
+        packet::set_adaptation_field(&mut pkt, 183);
+        pkt[5] = 0x03;
+        pkt[6] = 180;
+        pkt[packet::PACKET_SIZE - 1] = 0;
+        let info = PacketInfo::from(pkt);
+        let (json, back) = round_trip(&info);
+        assert!(json.starts_with(
+            r#"{"pid":8191,"transport_error":false,"unit_start":false,"transport_priority":false,"scrambling":"Clear","continuity_counter":0,"raw":[71,31,255,48,183,3,180,"#
+        ));
+        assert!(json.ends_with(r#",255,0],"error":{"TruncatedAdaptationField":183}}"#));
+        assert!(!json.contains("adaptation_field"));
+        assert_eq!(back.as_packet()[..], pkt[..]);
+
+        let view = PacketRef::new(&pkt).unwrap();
+        assert_eq!(serde_json::to_string(&view).unwrap(), json);
+        let bad_sync = json.replacen("[71,", "[72,", 1);
+        assert!(serde_json::from_str::<PacketInfo>(&bad_sync).is_err());
+    }
+
+    #[test]
+    fn test_adaptation_only() {
+        let mut pkt = packet::null_packet();
+        pkt[3] &= !0x10;
+        packet::set_adaptation_field(&mut pkt, packet::MAX_ADAPTATION_FIELD_LENGTH);
+        packet::set_discontinuity(&mut pkt);
+        let info = PacketInfo::from(pkt);
+        let (json, back) = round_trip(&info);
+        assert!(!json.contains("payload"));
+        assert_eq!(back.as_packet()[..], pkt[..]);
+    }
+
+    #[test]
+    fn test_bad_records() {
+        let short = r#"{"pid":1,"transport_error":false,"unit_start":false,"transport_priority":false,"scrambling":"Clear","continuity_counter":0,"payload":[1,2,3]}"#;
+        assert!(serde_json::from_str::<PacketInfo>(short).is_err());
+        let bad_pid = r#"{"pid":9000,"transport_error":false,"unit_start":false,"transport_priority":false,"scrambling":"Clear","continuity_counter":0}"#;
+        assert!(serde_json::from_str::<PacketInfo>(bad_pid).is_err());
+    }
+
+    #[test]
+    fn test_value_types() {
+        assert_eq!(serde_json::to_string(&Pid::SDT_BAT).unwrap(), "17");
+        assert!(serde_json::from_str::<Pid>("8192").is_err());
+        assert_eq!(serde_json::from_str::<Pcr>("27000000").unwrap(), Pcr::from_27mhz(27_000_000));
+        let event = ContinuityEvent::Gap {
+            expected: 4,
+            found: 7,
+            missing: 3,
+        };
+        let json = serde_json::to_string(&event).unwrap();
+        assert_eq!(json, r#"{"Gap":{"expected":4,"found":7,"missing":3}}"#);
+        assert_eq!(serde_json::from_str::<ContinuityEvent>(&json).unwrap(), event);
+        assert_eq!(serde_json::to_string(&TsError::BadSync(0)).unwrap(), r#"{"BadSync":0}"#);
+    }
+
+    #[test]
+    fn test_packet_ref() {
+        let pkt = packet::null_packet();
+        let view = PacketRef::new(&pkt).unwrap();
+        let info = PacketInfo::from(pkt);
+        assert_eq!(serde_json::to_string(&view).unwrap(), serde_json::to_string(&info).unwrap());
+    }
+}
@@ -0,0 +1,923 @@
+use core::convert::TryFrom;
+use core::fmt;
+
+use super::dump::{self, Hexdump};
+use super::packet::{self, ScramblingControl};
+use super::packet_info::PacketInfo;
+use super::pcr::Pcr;
+use crate::error::TsError;
+
+/// A borrowed, read-only view of a transport stream packet.
+#[derive(Clone, Copy)]
+pub struct PacketRef<'a> {
+    packet: &'a packet::Packet,
+}
+
+impl<'a> PacketRef<'a> {
+    /// Create a view over a slice of exactly one packet.
+    /// Fails if the length or sync byte is wrong.
+    pub fn new(bytes: &'a [u8]) -> Result<Self, TsError> {
+        let packet = <&packet::Packet>::try_from(bytes)
+            .map_err(|_| TsError::BadLength(bytes.len()))?;
+        if packet[0] != packet::SYNC_BYTE {
+            return Err(TsError::BadSync(packet[0]));
+        }
+        Ok(PacketRef { packet })
+    }
+
+    /// Get the underlying packet bytes
+    pub fn as_packet(&self) -> &'a packet::Packet {
+        self.packet
+    }
+
+    /// Check the sync byte and the adaptation field layout
+    pub fn validate(&self) -> Result<(), TsError> {
+        packet::validate(self.packet)
+    }
+
+    /// Is the transport error indicator set?
+    pub fn has_transport_error(&self) -> bool {
+        packet::has_transport_error(self.packet)
+    }
+
+    /// Is the unit start indicator set?
+    pub fn has_unit_start(&self) -> bool {
+        packet::has_unit_start(self.packet)
+    }
+
+    /// Get the MPEG section, if present
+    pub fn section(&self) -> Option<&'a [u8]> {
+        packet::section(self.packet)
+    }
+
+    /// Get the MPEG section, if present, checking the adaptation field length
+    pub fn try_section(&self) -> Result<Option<&'a [u8]>, TsError> {
+        packet::try_section(self.packet)
+    }
+
+    /// Get the pointer_field of a packet starting a section, if present
+    pub fn pointer_field(&self) -> Option<u8> {
+        packet::pointer_field(self.packet)
+    }
+
+    /// Get the pointer_field of a packet starting a section, if present,
+    /// checking it points inside the payload
+    pub fn try_pointer_field(&self) -> Result<Option<u8>, TsError> {
+        packet::try_pointer_field(self.packet)
+    }
+
+    /// Is the transport priority indicator set?
+    pub fn has_transport_priority(&self) -> bool {
+        packet::has_transport_priority(self.packet)
+    }
+
+    /// Is the payload present indicator set?
+    pub fn has_payload(&self) -> bool {
+        packet::has_payload(self.packet)
+    }
+
+    /// Get the payload as a slice of bytes
+    pub fn payload(&self) -> Option<&'a [u8]> {
+        packet::payload(self.packet)
+    }
+
+    /// Get the payload as a slice of bytes, checking the adaptation field length
+    pub fn try_payload(&self) -> Result<Option<&'a [u8]>, TsError> {
+        packet::try_payload(self.packet)
+    }
+
+    /// Get the value of the pid
+    pub fn pid(&self) -> u16 {
+        packet::pid(self.packet)
+    }
+
+    /// Get the value of the continuity counter
+    pub fn continuity_counter(&self) -> u8 {
+        packet::continuity_counter(self.packet)
+    }
+
+    /// Is the adaptation field indicator set?
+    pub fn has_adaptation_field(&self) -> bool {
+        packet::has_adaptation_field(self.packet)
+    }
+
+    /// Get the adaptation field length
+    pub fn adaptation_field(&self) -> u8 {
+        packet::adaptation_field(self.packet)
+    }
+
+    /// Get the adaptation field length, checking it is present and fits in the packet
+    pub fn try_adaptation_field(&self) -> Result<u8, TsError> {
+        packet::try_adaptation_field(self.packet)
+    }
+
+    /// Get the scrambling mode
+    pub fn scrambling(&self) -> u8 {
+        packet::scrambling(self.packet)
+    }
+
+    /// Get the scrambling control field
+    pub fn scrambling_control(&self) -> ScramblingControl {
+        packet::scrambling_control(self.packet)
+    }
+
+    /// Get the scrambling mode, rejecting the reserved value
+    pub fn try_scrambling(&self) -> Result<u8, TsError> {
+        packet::try_scrambling(self.packet)
+    }
+
+    /// Is the discontinuity indicator set?
+    pub fn has_discontinuity(&self) -> bool {
+        packet::has_discontinuity(self.packet)
+    }
+
+    /// Is the discontinuity indicator set? Fails without an adaptation field.
+    pub fn try_has_discontinuity(&self) -> Result<bool, TsError> {
+        packet::try_has_discontinuity(self.packet)
+    }
+
+    /// Is the random access indicator set?
+    pub fn has_random_access(&self) -> bool {
+        packet::has_random_access(self.packet)
+    }
+
+    /// Is the random access indicator set? Fails without an adaptation field.
+    pub fn try_has_random_access(&self) -> Result<bool, TsError> {
+        packet::try_has_random_access(self.packet)
+    }
+
+    /// Is the stream priority indicator set?
+    pub fn has_stream_priority(&self) -> bool {
+        packet::has_stream_priority(self.packet)
+    }
+
+    /// Is the stream priority indicator set? Fails without an adaptation field.
+    pub fn try_has_stream_priority(&self) -> Result<bool, TsError> {
+        packet::try_has_stream_priority(self.packet)
+    }
+
+    /// Does the packet have a programme clock reference field?
+    /// Only valid if it has an adaptation field.
+    pub fn has_pcr(&self) -> bool {
+        packet::has_pcr(self.packet)
+    }
+
+    /// Does the packet have a programme clock reference field?
+    /// Fails without an adaptation field.
+    pub fn try_has_pcr(&self) -> Result<bool, TsError> {
+        packet::try_has_pcr(self.packet)
+    }
+
+    /// Get the programme clock reference
+    pub fn pcr(&self) -> u64 {
+        packet::pcr(self.packet)
+    }
+
+    /// Get the programme clock reference, if present and fully contained
+    /// in the adaptation field
+    pub fn try_pcr(&self) -> Result<Option<u64>, TsError> {
+        packet::try_pcr(self.packet)
+    }
+
+    /// Get the programme clock reference extension
+    pub fn pcr_ext(&self) -> u16 {
+        packet::pcr_ext(self.packet)
+    }
+
+    /// Get the programme clock reference extension, if present and fully
+    /// contained in the adaptation field
+    pub fn try_pcr_ext(&self) -> Result<Option<u16>, TsError> {
+        packet::try_pcr_ext(self.packet)
+    }
+
+    /// Get the programme clock reference base and extension together, if present
+    pub fn pcr_clock(&self) -> Option<Pcr> {
+        packet::pcr_clock(self.packet)
+    }
+
+    /// Get the programme clock reference base and extension together, if
+    /// present and fully contained in the adaptation field
+    pub fn try_pcr_clock(&self) -> Result<Option<Pcr>, TsError> {
+        packet::try_pcr_clock(self.packet)
+    }
+
+    /// Does the packet have an original programme clock reference field?
+    /// Only valid if it has an adaptation field.
+    pub fn has_opcr(&self) -> bool {
+        packet::has_opcr(self.packet)
+    }
+
+    /// Get the original programme clock reference, if present
+    pub fn opcr(&self) -> Option<u64> {
+        packet::opcr(self.packet)
+    }
+
+    /// Get the original programme clock reference, if present, checking the
+    /// adaptation field layout
+    pub fn try_opcr(&self) -> Result<Option<u64>, TsError> {
+        packet::try_opcr(self.packet)
+    }
+
+    /// Get the original programme clock reference extension, if present
+    pub fn opcr_ext(&self) -> Option<u16> {
+        packet::opcr_ext(self.packet)
+    }
+
+    /// Get the original programme clock reference extension, if present,
+    /// checking the adaptation field layout
+    pub fn try_opcr_ext(&self) -> Result<Option<u16>, TsError> {
+        packet::try_opcr_ext(self.packet)
+    }
+
+    /// Get the original programme clock reference base and extension together,
+    /// if present
+    pub fn opcr_clock(&self) -> Option<Pcr> {
+        packet::opcr_clock(self.packet)
+    }
+
+    /// Get the original programme clock reference base and extension together,
+    /// if present, checking the adaptation field layout
+    pub fn try_opcr_clock(&self) -> Result<Option<Pcr>, TsError> {
+        packet::try_opcr_clock(self.packet)
+    }
+
+    /// Is the splicing point flag set?
+    /// Only valid if it has an adaptation field.
+    pub fn has_splicing_point(&self) -> bool {
+        packet::has_splicing_point(self.packet)
+    }
+
+    /// Get the splice countdown, if present
+    pub fn splice_countdown(&self) -> Option<i8> {
+        packet::splice_countdown(self.packet)
+    }
+
+    /// Get the splice countdown, if present, checking the adaptation field layout
+    pub fn try_splice_countdown(&self) -> Result<Option<i8>, TsError> {
+        packet::try_splice_countdown(self.packet)
+    }
+
+    /// Is the transport private data flag set?
+    /// Only valid if it has an adaptation field.
+    pub fn has_transport_private_data(&self) -> bool {
+        packet::has_transport_private_data(self.packet)
+    }
+
+    /// Get the transport private data, if present
+    pub fn transport_private_data(&self) -> Option<&'a [u8]> {
+        packet::transport_private_data(self.packet)
+    }
+
+    /// Get the transport private data, if present, checking the adaptation
+    /// field layout
+    pub fn try_transport_private_data(&self) -> Result<Option<&'a [u8]>, TsError> {
+        packet::try_transport_private_data(self.packet)
+    }
+
+    /// Is the adaptation field extension flag set?
+    /// Only valid if it has an adaptation field.
+    pub fn has_adaptation_field_extension(&self) -> bool {
+        packet::has_adaptation_field_extension(self.packet)
+    }
+
+    /// Get the legal time window valid flag, if present
+    pub fn ltw_valid(&self) -> Option<bool> {
+        packet::ltw_valid(self.packet)
+    }
+
+    /// Get the legal time window valid flag, if present, checking the
+    /// adaptation field layout
+    pub fn try_ltw_valid(&self) -> Result<Option<bool>, TsError> {
+        packet::try_ltw_valid(self.packet)
+    }
+
+    /// Get the legal time window offset, if present
+    pub fn ltw_offset(&self) -> Option<u16> {
+        packet::ltw_offset(self.packet)
+    }
+
+    /// Get the legal time window offset, if present, checking the adaptation
+    /// field layout
+    pub fn try_ltw_offset(&self) -> Result<Option<u16>, TsError> {
+        packet::try_ltw_offset(self.packet)
+    }
+
+    /// Get the piecewise rate, if present
+    pub fn piecewise_rate(&self) -> Option<u32> {
+        packet::piecewise_rate(self.packet)
+    }
+
+    /// Get the piecewise rate, if present, checking the adaptation field layout
+    pub fn try_piecewise_rate(&self) -> Result<Option<u32>, TsError> {
+        packet::try_piecewise_rate(self.packet)
+    }
+
+    /// Get the seamless splice type, if present
+    pub fn splice_type(&self) -> Option<u8> {
+        packet::splice_type(self.packet)
+    }
+
+    /// Get the seamless splice type, if present, checking the adaptation field
+    /// layout
+    pub fn try_splice_type(&self) -> Result<Option<u8>, TsError> {
+        packet::try_splice_type(self.packet)
+    }
+
+    /// Get the DTS of the next access unit, if present
+    pub fn dts_next_au(&self) -> Option<u64> {
+        packet::dts_next_au(self.packet)
+    }
+
+    /// Get the DTS of the next access unit, if present, checking the
+    /// adaptation field layout
+    pub fn try_dts_next_au(&self) -> Result<Option<u64>, TsError> {
+        packet::try_dts_next_au(self.packet)
+    }
+
+    /// Get a hexdump view of the payload, if present
+    pub fn payload_hexdump(&self) -> Option<Hexdump<'a>> {
+        self.payload().map(Hexdump::new)
+    }
+}
+
+impl<'a> From<PacketRef<'a>> for PacketInfo {
+    fn from(view: PacketRef<'a>) -> Self {
+        PacketInfo::from(*view.packet)
+    }
+}
+
+impl<'a> fmt::Debug for PacketRef<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        dump::fmt_debug("PacketRef", self.packet, f)
+    }
+}
+
+impl<'a> fmt::Display for PacketRef<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        dump::fmt_summary(self.packet, f)
+    }
+}
+
+/// A borrowed, mutable view of a transport stream packet.
+pub struct PacketMut<'a> {
+    packet: &'a mut packet::Packet,
+}
+
+impl<'a> PacketMut<'a> {
+    /// Create a view over a slice of exactly one packet.
+    /// Fails if the length or sync byte is wrong.
+    pub fn new(bytes: &'a mut [u8]) -> Result<Self, TsError> {
+        let len = bytes.len();
+        let packet = <&mut packet::Packet>::try_from(bytes).map_err(|_| TsError::BadLength(len))?;
+        if packet[0] != packet::SYNC_BYTE {
+            return Err(TsError::BadSync(packet[0]));
+        }
+        Ok(PacketMut { packet })
+    }
+
+    /// Get a read-only view of the same packet
+    pub fn as_ref(&self) -> PacketRef<'_> {
+        PacketRef {
+            packet: self.packet,
+        }
+    }
+
+    /// Get the underlying packet bytes
+    pub fn as_packet(&self) -> &packet::Packet {
+        self.packet
+    }
+
+    /// Get the underlying packet bytes for modification
+    pub fn as_packet_mut(&mut self) -> &mut packet::Packet {
+        self.packet
+    }
+
+    /// Check the sync byte and the adaptation field layout
+    pub fn validate(&self) -> Result<(), TsError> {
+        packet::validate(self.packet)
+    }
+
+    /// Set the transport error indicator
+    pub fn set_transport_error(&mut self) {
+        packet::set_transport_error(self.packet);
+    }
+
+    /// Is the transport error indicator set?
+    pub fn has_transport_error(&self) -> bool {
+        packet::has_transport_error(self.packet)
+    }
+
+    /// Set the unit start indicator
+    pub fn set_unit_start(&mut self) {
+        packet::set_unit_start(self.packet);
+    }
+
+    /// Is the unit start indicator set?
+    pub fn has_unit_start(&self) -> bool {
+        packet::has_unit_start(self.packet)
+    }
+
+    /// Get the MPEG section, if present
+    pub fn section(&self) -> Option<&[u8]> {
+        packet::section(self.packet)
+    }
+
+    /// Get the MPEG section, if present, checking the adaptation field length
+    pub fn try_section(&self) -> Result<Option<&[u8]>, TsError> {
+        packet::try_section(self.packet)
+    }
+
+    /// Get the pointer_field of a packet starting a section, if present
+    pub fn pointer_field(&self) -> Option<u8> {
+        packet::pointer_field(self.packet)
+    }
+
+    /// Get the pointer_field of a packet starting a section, if present,
+    /// checking it points inside the payload
+    pub fn try_pointer_field(&self) -> Result<Option<u8>, TsError> {
+        packet::try_pointer_field(self.packet)
+    }
+
+    /// Set the transport priority indicator
+    pub fn set_transport_priority(&mut self) {
+        packet::set_transport_priority(self.packet);
+    }
+
+    /// Is the transport priority indicator set?
+    pub fn has_transport_priority(&self) -> bool {
+        packet::has_transport_priority(self.packet)
+    }
+
+    /// Set the payload present indicator
+    pub fn set_payload(&mut self) {
+        packet::set_payload(self.packet);
+    }
+
+    /// Is the payload present indicator set?
+    pub fn has_payload(&self) -> bool {
+        packet::has_payload(self.packet)
+    }
+
+    /// Get the payload as a slice of bytes
+    pub fn payload(&self) -> Option<&[u8]> {
+        packet::payload(self.packet)
+    }
+
+    /// Get the payload as a slice of bytes, checking the adaptation field length
+    pub fn try_payload(&self) -> Result<Option<&[u8]>, TsError> {
+        packet::try_payload(self.packet)
+    }
+
+    /// Set the PID. Max: 8191 (0x1fff)
+    pub fn set_pid(&mut self, pid: u16) {
+        packet::set_pid(self.packet, pid);
+    }
+
+    /// Set the PID, failing if it is above 8191 (0x1fff)
+    pub fn try_set_pid(&mut self, pid: u16) -> Result<(), TsError> {
+        packet::try_set_pid(self.packet, pid)
+    }
+
+    /// Get the value of the pid
+    pub fn pid(&self) -> u16 {
+        packet::pid(self.packet)
+    }
+
+    /// Set the continuity counter. Max: 15
+    pub fn set_continuity_counter(&mut self, cc: u8) {
+        packet::set_continuity_counter(self.packet, cc);
+    }
+
+    /// Reset the continuity counter to zero
+    pub fn zero_continuity_counter(&mut self) {
+        packet::zero_continuity_counter(self.packet);
+    }
+
+    /// Get the value of the continuity counter
+    pub fn continuity_counter(&self) -> u8 {
+        packet::continuity_counter(self.packet)
+    }
+
+    /// Set the adaptation field length (&indicator)
+    pub fn set_adaptation_field(&mut self, length: u8) {
+        packet::set_adaptation_field(self.packet, length);
+    }
+
+    /// Set the adaptation field length (&indicator).
+    /// Fails if the length does not fit in the packet.
+    pub fn try_set_adaptation_field(&mut self, length: u8) -> Result<(), TsError> {
+        packet::try_set_adaptation_field(self.packet, length)
+    }
+
+    /// Is the adaptation field indicator set?
+    pub fn has_adaptation_field(&self) -> bool {
+        packet::has_adaptation_field(self.packet)
+    }
+
+    /// Get the adaptation field length
+    pub fn adaptation_field(&self) -> u8 {
+        packet::adaptation_field(self.packet)
+    }
+
+    /// Get the adaptation field length, checking it is present and fits in the packet
+    pub fn try_adaptation_field(&self) -> Result<u8, TsError> {
+        packet::try_adaptation_field(self.packet)
+    }
+
+    /// Set the scrambling mode
+    pub fn set_scrambling(&mut self, scrambling: u8) {
+        packet::set_scrambling(self.packet, scrambling);
+    }
+
+    /// Set the scrambling control field
+    pub fn set_scrambling_control(&mut self, scrambling: ScramblingControl) {
+        packet::set_scrambling_control(self.packet, scrambling);
+    }
+
+    /// Get the scrambling mode
+    pub fn scrambling(&self) -> u8 {
+        packet::scrambling(self.packet)
+    }
+
+    /// Get the scrambling control field
+    pub fn scrambling_control(&self) -> ScramblingControl {
+        packet::scrambling_control(self.packet)
+    }
+
+    /// Get the scrambling mode, rejecting the reserved value
+    pub fn try_scrambling(&self) -> Result<u8, TsError> {
+        packet::try_scrambling(self.packet)
+    }
+
+    /// Set the discontinuity indicator
+    pub fn set_discontinuity(&mut self) {
+        packet::set_discontinuity(self.packet);
+    }
+
+    /// Clear the discontinuity indicator
+    pub fn clear_discontinuity(&mut self) {
+        packet::clear_discontinuity(self.packet);
+    }
+
+    /// Is the discontinuity indicator set?
+    pub fn has_discontinuity(&self) -> bool {
+        packet::has_discontinuity(self.packet)
+    }
+
+    /// Is the discontinuity indicator set? Fails without an adaptation field.
+    pub fn try_has_discontinuity(&self) -> Result<bool, TsError> {
+        packet::try_has_discontinuity(self.packet)
+    }
+
+    /// Set the random access indicator
+    pub fn set_random_access(&mut self) {
+        packet::set_random_access(self.packet);
+    }
+
+    /// Is the random access indicator set?
+    pub fn has_random_access(&self) -> bool {
+        packet::has_random_access(self.packet)
+    }
+
+    /// Is the random access indicator set? Fails without an adaptation field.
+    pub fn try_has_random_access(&self) -> Result<bool, TsError> {
+        packet::try_has_random_access(self.packet)
+    }
+
+    /// Set the stream priority indicator
+    pub fn set_stream_priority(&mut self) {
+        packet::set_stream_priority(self.packet);
+    }
+
+    /// Is the stream priority indicator set?
+    pub fn has_stream_priority(&self) -> bool {
+        packet::has_stream_priority(self.packet)
+    }
+
+    /// Is the stream priority indicator set? Fails without an adaptation field.
+    pub fn try_has_stream_priority(&self) -> Result<bool, TsError> {
+        packet::try_has_stream_priority(self.packet)
+    }
+
+    /// Set the programme clock reference
+    pub fn set_pcr(&mut self, pcr: u64) {
+        packet::set_pcr(self.packet, pcr);
+    }
+
+    /// Does the packet have a programme clock reference field?
+    /// Only valid if it has an adaptation field.
+    pub fn has_pcr(&self) -> bool {
+        packet::has_pcr(self.packet)
+    }
+
+    /// Does the packet have a programme clock reference field?
+    /// Fails without an adaptation field.
+    pub fn try_has_pcr(&self) -> Result<bool, TsError> {
+        packet::try_has_pcr(self.packet)
+    }
+
+    /// Get the programme clock reference
+    pub fn pcr(&self) -> u64 {
+        packet::pcr(self.packet)
+    }
+
+    /// Get the programme clock reference, if present and fully contained
+    /// in the adaptation field
+    pub fn try_pcr(&self) -> Result<Option<u64>, TsError> {
+        packet::try_pcr(self.packet)
+    }
+
+    /// Set the programme clock reference extension
+    pub fn set_pcr_ext(&mut self, ext: u16) {
+        packet::set_pcr_ext(self.packet, ext);
+    }
+
+    /// Get the programme clock reference extension
+    pub fn pcr_ext(&self) -> u16 {
+        packet::pcr_ext(self.packet)
+    }
+
+    /// Get the programme clock reference extension, if present and fully
+    /// contained in the adaptation field
+    pub fn try_pcr_ext(&self) -> Result<Option<u16>, TsError> {
+        packet::try_pcr_ext(self.packet)
+    }
+
+    /// Set the programme clock reference base and extension together
+    pub fn set_pcr_clock(&mut self, pcr: Pcr) {
+        packet::set_pcr_clock(self.packet, pcr);
+    }
+
+    /// Get the programme clock reference base and extension together, if present
+    pub fn pcr_clock(&self) -> Option<Pcr> {
+        packet::pcr_clock(self.packet)
+    }
+
+    /// Get the programme clock reference base and extension together, if
+    /// present and fully contained in the adaptation field
+    pub fn try_pcr_clock(&self) -> Result<Option<Pcr>, TsError> {
+        packet::try_pcr_clock(self.packet)
+    }
+
+    /// Set the original programme clock reference, inserting it into the
+    /// adaptation field if it is not already present
+    pub fn set_opcr(&mut self, opcr: u64, ext: u16) -> Result<(), TsError> {
+        packet::set_opcr(self.packet, opcr, ext)
+    }
+
+    /// Does the packet have an original programme clock reference field?
+    /// Only valid if it has an adaptation field.
+    pub fn has_opcr(&self) -> bool {
+        packet::has_opcr(self.packet)
+    }
+
+    /// Get the original programme clock reference, if present
+    pub fn opcr(&self) -> Option<u64> {
+        packet::opcr(self.packet)
+    }
+
+    /// Get the original programme clock reference, if present, checking the
+    /// adaptation field layout
+    pub fn try_opcr(&self) -> Result<Option<u64>, TsError> {
+        packet::try_opcr(self.packet)
+    }
+
+    /// Get the original programme clock reference extension, if present
+    pub fn opcr_ext(&self) -> Option<u16> {
+        packet::opcr_ext(self.packet)
+    }
+
+    /// Get the original programme clock reference extension, if present,
+    /// checking the adaptation field layout
+    pub fn try_opcr_ext(&self) -> Result<Option<u16>, TsError> {
+        packet::try_opcr_ext(self.packet)
+    }
+
+    /// Get the original programme clock reference base and extension together,
+    /// if present
+    pub fn opcr_clock(&self) -> Option<Pcr> {
+        packet::opcr_clock(self.packet)
+    }
+
+    /// Get the original programme clock reference base and extension together,
+    /// if present, checking the adaptation field layout
+    pub fn try_opcr_clock(&self) -> Result<Option<Pcr>, TsError> {
+        packet::try_opcr_clock(self.packet)
+    }
+
+    /// Set the splicing point flag and the splice countdown
+    pub fn set_splice_countdown(&mut self, countdown: i8) -> Result<(), TsError> {
+        packet::set_splice_countdown(self.packet, countdown)
+    }
+
+    /// Is the splicing point flag set?
+    /// Only valid if it has an adaptation field.
+    pub fn has_splicing_point(&self) -> bool {
+        packet::has_splicing_point(self.packet)
+    }
+
+    /// Get the splice countdown, if present
+    pub fn splice_countdown(&self) -> Option<i8> {
+        packet::splice_countdown(self.packet)
+    }
+
+    /// Get the splice countdown, if present, checking the adaptation field layout
+    pub fn try_splice_countdown(&self) -> Result<Option<i8>, TsError> {
+        packet::try_splice_countdown(self.packet)
+    }
+
+    /// Set the transport private data, replacing any already present
+    pub fn set_transport_private_data(&mut self, data: &[u8]) -> Result<(), TsError> {
+        packet::set_transport_private_data(self.packet, data)
+    }
+
+    /// Is the transport private data flag set?
+    /// Only valid if it has an adaptation field.
+    pub fn has_transport_private_data(&self) -> bool {
+        packet::has_transport_private_data(self.packet)
+    }
+
+    /// Get the transport private data, if present
+    pub fn transport_private_data(&self) -> Option<&[u8]> {
+        packet::transport_private_data(self.packet)
+    }
+
+    /// Get the transport private data, if present, checking the adaptation
+    /// field layout
+    pub fn try_transport_private_data(&self) -> Result<Option<&[u8]>, TsError> {
+        packet::try_transport_private_data(self.packet)
+    }
+
+    /// Add an empty adaptation field extension, if not already present
+    pub fn set_adaptation_field_extension(&mut self) -> Result<(), TsError> {
+        packet::set_adaptation_field_extension(self.packet)
+    }
+
+    /// Is the adaptation field extension flag set?
+    /// Only valid if it has an adaptation field.
+    pub fn has_adaptation_field_extension(&self) -> bool {
+        packet::has_adaptation_field_extension(self.packet)
+    }
+
+    /// Set the legal time window, adding the extension if needed
+    pub fn set_ltw(&mut self, valid: bool, offset: u16) -> Result<(), TsError> {
+        packet::set_ltw(self.packet, valid, offset)
+    }
+
+    /// Get the legal time window valid flag, if present
+    pub fn ltw_valid(&self) -> Option<bool> {
+        packet::ltw_valid(self.packet)
+    }
+
+    /// Get the legal time window valid flag, if present, checking the
+    /// adaptation field layout
+    pub fn try_ltw_valid(&self) -> Result<Option<bool>, TsError> {
+        packet::try_ltw_valid(self.packet)
+    }
+
+    /// Get the legal time window offset, if present
+    pub fn ltw_offset(&self) -> Option<u16> {
+        packet::ltw_offset(self.packet)
+    }
+
+    /// Get the legal time window offset, if present, checking the adaptation
+    /// field layout
+    pub fn try_ltw_offset(&self) -> Result<Option<u16>, TsError> {
+        packet::try_ltw_offset(self.packet)
+    }
+
+    /// Set the piecewise rate (22 bits), adding the extension if needed
+    pub fn set_piecewise_rate(&mut self, rate: u32) -> Result<(), TsError> {
+        packet::set_piecewise_rate(self.packet, rate)
+    }
+
+    /// Get the piecewise rate, if present
+    pub fn piecewise_rate(&self) -> Option<u32> {
+        packet::piecewise_rate(self.packet)
+    }
+
+    /// Get the piecewise rate, if present, checking the adaptation field layout
+    pub fn try_piecewise_rate(&self) -> Result<Option<u32>, TsError> {
+        packet::try_piecewise_rate(self.packet)
+    }
+
+    /// Set the seamless splice type and DTS of the next access unit,
+    /// adding the extension if needed
+    pub fn set_seamless_splice(&mut self, splice_type: u8, dts_next_au: u64) -> Result<(), TsError> {
+        packet::set_seamless_splice(self.packet, splice_type, dts_next_au)
+    }
+
+    /// Get the seamless splice type, if present
+    pub fn splice_type(&self) -> Option<u8> {
+        packet::splice_type(self.packet)
+    }
+
+    /// Get the seamless splice type, if present, checking the adaptation field
+    /// layout
+    pub fn try_splice_type(&self) -> Result<Option<u8>, TsError> {
+        packet::try_splice_type(self.packet)
+    }
+
+    /// Get the DTS of the next access unit, if present
+    pub fn dts_next_au(&self) -> Option<u64> {
+        packet::dts_next_au(self.packet)
+    }
+
+    /// Get the DTS of the next access unit, if present, checking the
+    /// adaptation field layout
+    pub fn try_dts_next_au(&self) -> Result<Option<u64>, TsError> {
+        packet::try_dts_next_au(self.packet)
+    }
+
+    /// Get a hexdump view of the payload, if present
+    pub fn payload_hexdump(&self) -> Option<Hexdump<'_>> {
+        self.payload().map(Hexdump::new)
+    }
+}
+
+impl<'a> From<PacketMut<'a>> for PacketInfo {
+    fn from(view: PacketMut<'a>) -> Self {
+        PacketInfo::from(*view.packet)
+    }
+}
+
+impl<'a> fmt::Debug for PacketMut<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        dump::fmt_debug("PacketMut", self.packet, f)
+    }
+}
+
+impl<'a> fmt::Display for PacketMut<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        dump::fmt_summary(self.packet, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_valid() {
+        let packet = packet::null_packet();
+        let view = PacketRef::new(&packet[..]).unwrap();
+        assert_eq!(view.pid(), packet::NULL_PACKET_PID);
+        assert_eq!(view.as_packet().as_ptr(), packet.as_ptr());
+    }
+
+    #[test]
+    fn test_new_bad_sync() {
+        let mut packet = packet::null_packet();
+        packet[0] = 0x00;
+        assert_eq!(PacketRef::new(&packet[..]).err(), Some(TsError::BadSync(0x00)));
+        assert!(PacketMut::new(&mut packet[..]).is_err());
+    }
+
+    #[test]
+    fn test_new_bad_length() {
+        let buffer = [packet::SYNC_BYTE; packet::PACKET_SIZE + 1];
+        assert_eq!(
+            PacketRef::new(&buffer[..]).err(),
+            Some(TsError::BadLength(packet::PACKET_SIZE + 1))
+        );
+        assert!(PacketRef::new(&buffer[..packet::PACKET_SIZE - 1]).is_err());
+    }
+
+    #[test]
+    fn test_slices_of_buffer() {
+        let mut buffer = [0u8; packet::PACKET_SIZE * 2];
+        buffer[..packet::PACKET_SIZE].copy_from_slice(&packet::null_packet());
+        buffer[packet::PACKET_SIZE..].copy_from_slice(&packet::null_packet());
+        for chunk in buffer.chunks_exact_mut(packet::PACKET_SIZE) {
+            let mut view = PacketMut::new(chunk).unwrap();
+            view.set_pid(0x100);
+            view.set_unit_start();
+        }
+        for chunk in buffer.chunks_exact(packet::PACKET_SIZE) {
+            let view = PacketRef::new(chunk).unwrap();
+            assert_eq!(view.pid(), 0x100);
+            assert!(view.has_unit_start());
+        }
+    }
+
+    #[test]
+    fn test_mut_writes_through() {
+        let mut packet = packet::null_packet();
+        {
+            let mut view = PacketMut::new(&mut packet[..]).unwrap();
+            view.set_continuity_counter(7);
+            view.set_transport_priority();
+            assert_eq!(view.as_ref().continuity_counter(), 7);
+        }
+        assert_eq!(packet::continuity_counter(&packet), 7);
+        assert!(packet::has_transport_priority(&packet));
+    }
+
+    #[test]
+    fn test_into_packet_info() {
+        let mut packet = packet::null_packet();
+        packet::set_pid(&mut packet, 0x42);
+        let info: PacketInfo = PacketRef::new(&packet[..]).unwrap().into();
+        assert_eq!(info.pid(), 0x42);
+    }
+}
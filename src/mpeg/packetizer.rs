@@ -0,0 +1,251 @@
+use super::packet::{self, Packet, HEADER_SIZE, PACKET_SIZE, PAYLOAD_SIZE};
+use super::section::Section;
+use crate::error::TsError;
+
+/// Splits complete PSI/SI sections into the packets of one PID.
+#[derive(Clone, Copy, Debug)]
+pub struct SectionPacketizer {
+    pid: u16,
+    continuity_counter: u8,
+    pack_sections: bool,
+}
+
+impl SectionPacketizer {
+    /// Packetize onto `pid`, starting the continuity counter at zero
+    pub fn new(pid: u16) -> Self {
+        SectionPacketizer {
+            pid,
+            continuity_counter: 0,
+            pack_sections: true,
+        }
+    }
+
+    /// Allow a section to start in the same packet as the end of the previous
+    /// one. Otherwise every section starts a new packet.
+    pub fn pack_sections(mut self, pack: bool) -> Self {
+        self.pack_sections = pack;
+        self
+    }
+
+    /// Get the PID packets are made on
+    pub fn pid(&self) -> u16 {
+        self.pid
+    }
+
+    /// Get the continuity counter the next packet will carry
+    pub fn continuity_counter(&self) -> u8 {
+        self.continuity_counter
+    }
+
+    /// Set the continuity counter the next packet will carry. Max: 15
+    pub fn set_continuity_counter(&mut self, cc: u8) {
+        self.continuity_counter = cc & 0x0f;
+    }
+
+    /// Iterate over the packets carrying `sections`, after checking each
+    /// is a single complete section
+    pub fn packetize<'p, 'a>(
+        &'p mut self,
+        sections: &'a [&'a [u8]],
+    ) -> Result<SectionPackets<'p, 'a>, TsError> {
+        for bytes in sections {
+            let section = Section::new(bytes)?;
+            if section.as_bytes().len() != bytes.len() {
+                return Err(TsError::BadLength(bytes.len()));
+            }
+        }
+        Ok(SectionPackets {
+            packetizer: self,
+            sections,
+            index: 0,
+            offset: 0,
+        })
+    }
+}
+
+/// Iterator over the packets carrying some sections, created by
+/// `SectionPacketizer::packetize`.
+pub struct SectionPackets<'p, 'a> {
+    packetizer: &'p mut SectionPacketizer,
+    sections: &'a [&'a [u8]],
+    index: usize,
+    offset: usize,
+}
+
+impl<'p, 'a> SectionPackets<'p, 'a> {
+    /// Copy as much of the current section as fits at `pos`, returning the
+    /// new position
+    fn copy_section(&mut self, pkt: &mut Packet, pos: usize) -> usize {
+        let section = self.sections[self.index];
+        let count = (section.len() - self.offset).min(PACKET_SIZE - pos);
+        pkt[pos..pos + count].copy_from_slice(&section[self.offset..self.offset + count]);
+        self.offset += count;
+        if self.offset == section.len() {
+            self.index += 1;
+            self.offset = 0;
+        }
+        pos + count
+    }
+}
+
+impl<'p, 'a> Iterator for SectionPackets<'p, 'a> {
+    type Item = Packet;
+
+    fn next(&mut self) -> Option<Packet> {
+        if self.index == self.sections.len() {
+            return None;
+        }
+        let mut pkt = packet::null_packet();
+        packet::set_pid(&mut pkt, self.packetizer.pid);
+        packet::set_continuity_counter(&mut pkt, self.packetizer.continuity_counter);
+        self.packetizer.continuity_counter = (self.packetizer.continuity_counter + 1) & 0x0f;
+
+        let pack = self.packetizer.pack_sections;
+        let remaining = self.sections[self.index].len() - self.offset;
+        let starts_section = if self.offset == 0 {
+            Some(0)
+        } else if pack && self.index + 1 < self.sections.len() && remaining + 1 < PAYLOAD_SIZE {
+            Some(remaining)
+        } else {
+            None
+        };
+        let mut pos = HEADER_SIZE;
+        if let Some(pointer) = starts_section {
+            packet::set_unit_start(&mut pkt);
+            pkt[pos] = pointer as u8;
+            pos += 1;
+        }
+        if self.offset > 0 {
+            pos = self.copy_section(&mut pkt, pos);
+            if !pack || starts_section.is_none() {
+                return Some(pkt);
+            }
+        }
+        // anything left after the last section stays as null_packet stuffing
+        while pos < PACKET_SIZE && self.index < self.sections.len() {
+            pos = self.copy_section(&mut pkt, pos);
+            if !pack {
+                break;
+            }
+        }
+        Some(pkt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpeg::section::SectionAssembler;
+    use crate::mpeg::section_builder::{SectionBuffer, SectionBuilder};
+
+    const PID: u16 = 0x20;
+
+    fn make_section(table_id: u8, data_len: usize) -> (SectionBuffer, usize) {
+        let mut data = [0u8; 1024];
+        for (i, byte) in data.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        SectionBuilder::new(table_id).data(&data[..data_len]).build().unwrap()
+    }
+
+    /// Packetize and reassemble, checking the sections come back unchanged
+    fn round_trip(packetizer: &mut SectionPacketizer, sections: &[&[u8]]) -> usize {
+        let mut assembler = SectionAssembler::new(PID).verify_crc(true);
+        let mut found = 0;
+        let mut packets = 0;
+        for pkt in packetizer.packetize(sections).unwrap() {
+            packets += 1;
+            assembler.push(&pkt).unwrap();
+            while let Some(section) = assembler.next_section() {
+                assert_eq!(section, Ok(sections[found]));
+                found += 1;
+            }
+        }
+        assert_eq!(found, sections.len());
+        assert_eq!(assembler.continuity_errors(), 0);
+        packets
+    }
+
+    #[test]
+    fn test_single_short_section() {
+        let (section, len) = make_section(0x00, 4);
+        let mut packetizer = SectionPacketizer::new(PID);
+        let sections = [&section[..len]];
+        let mut packets = packetizer.packetize(&sections).unwrap();
+        let pkt = packets.next().unwrap();
+        assert!(packets.next().is_none());
+        assert_eq!(packet::pid(&pkt), PID);
+        assert!(packet::has_unit_start(&pkt));
+        assert_eq!(packet::pointer_field(&pkt), Some(0));
+        assert_eq!(packet::section(&pkt).unwrap()[..len], section[..len]);
+        assert!(pkt[HEADER_SIZE + 1 + len..].iter().all(|&byte| byte == 0xff));
+        assert_eq!(packetizer.continuity_counter(), 1);
+    }
+
+    #[test]
+    fn test_spanning_section() {
+        let (section, len) = make_section(0x02, 500);
+        let mut packetizer = SectionPacketizer::new(PID);
+        let packets = round_trip(&mut packetizer, &[&section[..len]]);
+        assert_eq!(packets, 3);
+        assert_eq!(packetizer.continuity_counter(), 3);
+    }
+
+    #[test]
+    fn test_packed_sections() {
+        let (a, a_len) = make_section(0x42, 30);
+        let (b, b_len) = make_section(0x46, 200);
+        let (c, c_len) = make_section(0x4a, 10);
+        let sections = [&a[..a_len], &b[..b_len], &c[..c_len]];
+        let mut packetizer = SectionPacketizer::new(PID);
+        assert_eq!(round_trip(&mut packetizer, &sections), 2);
+
+        let mut packetizer = SectionPacketizer::new(PID);
+        let mut packets = packetizer.packetize(&sections).unwrap();
+        packets.next();
+        let second = packets.next().unwrap();
+        let tail = a_len + b_len - (PAYLOAD_SIZE - 1);
+        assert_eq!(packet::pointer_field(&second), Some(tail as u8));
+        assert_eq!(packet::section(&second).unwrap()[..c_len], c[..c_len]);
+    }
+
+    #[test]
+    fn test_unpacked_sections() {
+        let (a, a_len) = make_section(0x42, 30);
+        let (b, b_len) = make_section(0x46, 200);
+        let (c, c_len) = make_section(0x4a, 10);
+        let sections = [&a[..a_len], &b[..b_len], &c[..c_len]];
+        let mut packetizer = SectionPacketizer::new(PID).pack_sections(false);
+        assert_eq!(round_trip(&mut packetizer, &sections), 4);
+    }
+
+    #[test]
+    fn test_continuation_fills_packet() {
+        // the tail of the first section leaves no room for a pointer_field
+        let (a, a_len) = make_section(0x42, PAYLOAD_SIZE - 1 - 12 + PAYLOAD_SIZE - 1);
+        let (b, b_len) = make_section(0x42, 5);
+        let mut packetizer = SectionPacketizer::new(PID);
+        assert_eq!(round_trip(&mut packetizer, &[&a[..a_len], &b[..b_len]]), 3);
+    }
+
+    #[test]
+    fn test_continuity_across_calls() {
+        let (a, a_len) = make_section(0x00, 4);
+        let mut packetizer = SectionPacketizer::new(PID);
+        packetizer.set_continuity_counter(15);
+        let sections = [&a[..a_len]];
+        let first = packetizer.packetize(&sections).unwrap().next().unwrap();
+        let second = packetizer.packetize(&sections).unwrap().next().unwrap();
+        assert_eq!(packet::continuity_counter(&first), 15);
+        assert_eq!(packet::continuity_counter(&second), 0);
+    }
+
+    #[test]
+    fn test_bad_sections() {
+        let (a, a_len) = make_section(0x00, 4);
+        let mut packetizer = SectionPacketizer::new(PID);
+        assert_eq!(packetizer.packetize(&[&a[..a_len + 1]]).err(), Some(TsError::BadLength(a_len + 1)));
+        assert_eq!(packetizer.packetize(&[&a[..a_len - 1]]).err(), Some(TsError::BadLength(a_len - 1)));
+        assert!(packetizer.packetize(&[]).unwrap().next().is_none());
+    }
+}
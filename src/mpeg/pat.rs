@@ -0,0 +1,441 @@
+#[cfg(feature = "serde")]
+use core::convert::TryFrom;
+
+use super::crc::CRC_SIZE;
+use super::packet::SECTION_MAX_SIZE;
+use super::section::LONG_SECTION_HEADER_SIZE;
+use super::section_builder::{SectionBuffer, SectionBuilder};
+use super::table::{parse_table_section, section_numbers, FixedVec, SectionAssembly, PSI_SECTION_MAX_SIZE};
+use crate::error::TsError;
+
+/// table_id of a program_association_section
+pub const PAT_TABLE_ID: u8 = 0x00;
+/// Most programs a `Pat` can hold
+pub const PAT_MAX_PROGRAMS: usize = 1024;
+
+const ENTRY_SIZE: usize = 4;
+const ENTRIES_PER_SECTION: usize = (PSI_SECTION_MAX_SIZE - LONG_SECTION_HEADER_SIZE - CRC_SIZE) / ENTRY_SIZE;
+
+/// One program of a PAT and the PID carrying its PMT.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PatProgram {
+    pub program_number: u16,
+    pub pmt_pid: u16,
+}
+
+/// A program association table, possibly gathered from several sections.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "PatRecord"))]
+pub struct Pat {
+    transport_stream_id: u16,
+    version_number: u8,
+    current_next_indicator: bool,
+    network_pid: Option<u16>,
+    programs: FixedVec<PatProgram, PAT_MAX_PROGRAMS>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    assembly: Option<SectionAssembly>,
+}
+
+impl Pat {
+    /// Start an empty, current PAT at version 0
+    pub fn new(transport_stream_id: u16) -> Self {
+        Pat {
+            transport_stream_id,
+            version_number: 0,
+            current_next_indicator: true,
+            network_pid: None,
+            programs: FixedVec::new(),
+            assembly: None,
+        }
+    }
+
+    /// Parse one program_association_section, checking its CRC_32. If it is
+    /// one of several, add the others with `add_section`.
+    pub fn parse(bytes: &[u8]) -> Result<Self, TsError> {
+        let section = parse_table_section(bytes, PAT_TABLE_ID)?;
+        let mut pat = Pat::new(section.table_id_extension().unwrap_or(0));
+        pat.version_number = section.version_number().unwrap_or(0);
+        pat.current_next_indicator = section.current_next_indicator().unwrap_or(true);
+        let (number, last) = section_numbers(&section)?;
+        pat.assembly = Some(SectionAssembly::new(last));
+        pat.add_entries(number, section.data())?;
+        Ok(pat)
+    }
+
+    /// Add another section of the same table, ignoring one already seen
+    pub fn add_section(&mut self, bytes: &[u8]) -> Result<(), TsError> {
+        let section = parse_table_section(bytes, PAT_TABLE_ID)?;
+        if section.table_id_extension() != Some(self.transport_stream_id)
+            || section.version_number() != Some(self.version_number)
+            || section.current_next_indicator() != Some(self.current_next_indicator)
+        {
+            return Err(TsError::InconsistentSection);
+        }
+        let (number, last) = section_numbers(&section)?;
+        match self.assembly.as_ref() {
+            Some(assembly) if assembly.check(number, last)? => self.add_entries(number, section.data()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Insert the programs of section `number` after those of the earlier
+    /// sections
+    fn add_entries(&mut self, number: u8, data: &[u8]) -> Result<(), TsError> {
+        if !data.len().is_multiple_of(ENTRY_SIZE) {
+            return Err(TsError::BadLength(data.len()));
+        }
+        let start = self.assembly.as_ref().map_or(self.programs.len(), |a| a.offset(number));
+        let mut index = start;
+        for entry in data.chunks_exact(ENTRY_SIZE) {
+            let program_number = u16::from_be_bytes([entry[0], entry[1]]);
+            let pmt_pid = u16::from_be_bytes([entry[2], entry[3]]) & 0x1fff;
+            if program_number == 0 || self.pmt_pid(program_number).is_some() {
+                self.add_program(program_number, pmt_pid)?;
+            } else {
+                let program = PatProgram {
+                    program_number,
+                    pmt_pid,
+                };
+                self.programs.splice(index, 0, &[program])?;
+                index += 1;
+            }
+        }
+        if let Some(assembly) = self.assembly.as_mut() {
+            assembly.mark_seen(number, index - start);
+            if assembly.is_complete() {
+                self.assembly = None;
+            }
+        }
+        Ok(())
+    }
+
+    /// Have all the sections of the table been seen?
+    pub fn is_complete(&self) -> bool {
+        self.assembly.is_none()
+    }
+
+    /// Get the transport_stream_id
+    pub fn transport_stream_id(&self) -> u16 {
+        self.transport_stream_id
+    }
+
+    /// Set the transport_stream_id
+    pub fn set_transport_stream_id(&mut self, id: u16) {
+        self.transport_stream_id = id;
+    }
+
+    /// Get the version_number
+    pub fn version_number(&self) -> u8 {
+        self.version_number
+    }
+
+    /// Set the version_number. Max: 31
+    pub fn set_version_number(&mut self, version: u8) {
+        self.version_number = version & 0x1f;
+    }
+
+    /// Get the current_next_indicator
+    pub fn current_next_indicator(&self) -> bool {
+        self.current_next_indicator
+    }
+
+    /// Set the current_next_indicator
+    pub fn set_current_next_indicator(&mut self, current: bool) {
+        self.current_next_indicator = current;
+    }
+
+    /// Get the network_PID, carried as program 0
+    pub fn network_pid(&self) -> Option<u16> {
+        self.network_pid
+    }
+
+    /// Set or clear the network_PID
+    pub fn set_network_pid(&mut self, pid: Option<u16>) {
+        self.network_pid = pid.map(|pid| pid & 0x1fff);
+    }
+
+    /// Get the programs in table order, not including the network_PID
+    pub fn programs(&self) -> &[PatProgram] {
+        self.programs.as_slice()
+    }
+
+    /// Get the PMT PID of a program
+    pub fn pmt_pid(&self, program_number: u16) -> Option<u16> {
+        self.programs()
+            .iter()
+            .find(|p| p.program_number == program_number)
+            .map(|p| p.pmt_pid)
+    }
+
+    /// Add a program or move it to a new PMT PID. Program 0 sets the
+    /// network_PID.
+    pub fn add_program(&mut self, program_number: u16, pmt_pid: u16) -> Result<(), TsError> {
+        let pmt_pid = pmt_pid & 0x1fff;
+        if program_number == 0 {
+            self.network_pid = Some(pmt_pid);
+            return Ok(());
+        }
+        match self
+            .programs
+            .as_mut_slice()
+            .iter_mut()
+            .find(|p| p.program_number == program_number)
+        {
+            Some(program) => program.pmt_pid = pmt_pid,
+            None => self.programs.push(PatProgram {
+                program_number,
+                pmt_pid,
+            })?,
+        }
+        Ok(())
+    }
+
+    /// Remove a program, returning its PMT PID
+    pub fn remove_program(&mut self, program_number: u16) -> Option<u16> {
+        if program_number == 0 {
+            return self.network_pid.take();
+        }
+        let index = self
+            .programs()
+            .iter()
+            .position(|p| p.program_number == program_number)?;
+        Some(self.programs.remove(index).pmt_pid)
+    }
+
+    fn entry_count(&self) -> usize {
+        self.network_pid.is_some() as usize + self.programs.len()
+    }
+
+    fn entry(&self, index: usize) -> PatProgram {
+        match (self.network_pid, index) {
+            (Some(pmt_pid), 0) => PatProgram {
+                program_number: 0,
+                pmt_pid,
+            },
+            (Some(_), _) => self.programs.as_slice()[index - 1],
+            (None, _) => self.programs.as_slice()[index],
+        }
+    }
+
+    /// Get the number of sections needed to carry the table
+    pub fn section_count(&self) -> usize {
+        self.entry_count().div_ceil(ENTRIES_PER_SECTION).max(1)
+    }
+
+    /// Write section `number` of the table to the start of `out`, returning
+    /// its length
+    pub fn write_section(&self, number: usize, out: &mut [u8]) -> Result<usize, TsError> {
+        let count = self.section_count();
+        if number >= count {
+            return Err(TsError::TooManyEntries(count));
+        }
+        let start = number * ENTRIES_PER_SECTION;
+        let end = (start + ENTRIES_PER_SECTION).min(self.entry_count());
+        let mut data = [0u8; ENTRIES_PER_SECTION * ENTRY_SIZE];
+        for (i, chunk) in data.chunks_exact_mut(ENTRY_SIZE).take(end - start).enumerate() {
+            let entry = self.entry(start + i);
+            chunk[..2].copy_from_slice(&entry.program_number.to_be_bytes());
+            chunk[2..].copy_from_slice(&(0xe000 | entry.pmt_pid).to_be_bytes());
+        }
+        SectionBuilder::new(PAT_TABLE_ID)
+            .table_id_extension(self.transport_stream_id)
+            .version_number(self.version_number)
+            .current_next_indicator(self.current_next_indicator)
+            .section_number(number as u8)
+            .last_section_number((count - 1) as u8)
+            .data(&data[..(end - start) * ENTRY_SIZE])
+            .write(out)
+    }
+
+    /// Build section `number` of the table, returning it with its length
+    pub fn build_section(&self, number: usize) -> Result<(SectionBuffer, usize), TsError> {
+        let mut section: SectionBuffer = [0xff; SECTION_MAX_SIZE];
+        let len = self.write_section(number, &mut section)?;
+        Ok((section, len))
+    }
+}
+
+/// A deserialized `Pat`, rebuilt through `set_network_pid` and
+/// `add_program` once its PIDs and program numbers are checked
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct PatRecord {
+    transport_stream_id: u16,
+    version_number: u8,
+    current_next_indicator: bool,
+    network_pid: Option<u16>,
+    programs: FixedVec<PatProgram, PAT_MAX_PROGRAMS>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<PatRecord> for Pat {
+    type Error = TsError;
+
+    fn try_from(record: PatRecord) -> Result<Self, TsError> {
+        let mut pat = Pat::new(record.transport_stream_id);
+        pat.set_version_number(record.version_number);
+        pat.set_current_next_indicator(record.current_next_indicator);
+        if let Some(pid) = record.network_pid.filter(|&pid| pid > 0x1fff) {
+            return Err(TsError::PidOutOfRange(pid));
+        }
+        pat.set_network_pid(record.network_pid);
+        for program in record.programs.as_slice() {
+            if program.program_number == 0 {
+                return Err(TsError::Reserved("program_number"));
+            }
+            if program.pmt_pid > 0x1fff {
+                return Err(TsError::PidOutOfRange(program.pmt_pid));
+            }
+            if pat.pmt_pid(program.program_number).is_some() {
+                return Err(TsError::InconsistentSection);
+            }
+            pat.add_program(program.program_number, program.pmt_pid)?;
+        }
+        Ok(pat)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FFMPEG_PAT: [u8; 16] = [
+        0x00, 0xb0, 0x0d, 0x00, 0x01, 0xc1, 0x00, 0x00, 0x00, 0x01, 0xf0, 0x00, 0x2a, 0xb1, 0x04, 0xb2,
+    ];
+
+    #[test]
+    fn test_parse() {
+        let pat = Pat::parse(&FFMPEG_PAT).unwrap();
+        assert!(pat.is_complete());
+        assert_eq!(pat.transport_stream_id(), 1);
+        assert_eq!(pat.version_number(), 0);
+        assert!(pat.current_next_indicator());
+        assert_eq!(pat.network_pid(), None);
+        assert_eq!(
+            pat.programs(),
+            &[PatProgram {
+                program_number: 1,
+                pmt_pid: 0x1000
+            }]
+        );
+        assert_eq!(pat.pmt_pid(1), Some(0x1000));
+        assert_eq!(pat.pmt_pid(2), None);
+    }
+
+    #[test]
+    fn test_build() {
+        let mut pat = Pat::new(1);
+        pat.add_program(1, 0x1000).unwrap();
+        assert_eq!(pat.section_count(), 1);
+        let (section, len) = pat.build_section(0).unwrap();
+        assert_eq!(&section[..len], &FFMPEG_PAT);
+        assert_eq!(pat.build_section(1).err(), Some(TsError::TooManyEntries(1)));
+    }
+
+    #[test]
+    fn test_edit_programs() {
+        let mut pat = Pat::new(7);
+        pat.set_version_number(33);
+        pat.add_program(0, 0x10).unwrap();
+        pat.add_program(3, 0x100).unwrap();
+        pat.add_program(4, 0x200).unwrap();
+        pat.add_program(3, 0x300).unwrap();
+        assert_eq!(pat.version_number(), 1);
+        assert_eq!(pat.network_pid(), Some(0x10));
+        assert_eq!(pat.programs().len(), 2);
+        assert_eq!(pat.pmt_pid(3), Some(0x300));
+        assert_eq!(pat.remove_program(3), Some(0x300));
+        assert_eq!(pat.remove_program(3), None);
+        assert_eq!(pat.remove_program(0), Some(0x10));
+        assert_eq!(pat.programs().len(), 1);
+
+        pat.set_network_pid(Some(0x10));
+        let (section, len) = pat.build_section(0).unwrap();
+        assert_eq!(Pat::parse(&section[..len]).unwrap(), pat);
+        assert_eq!(&section[8..12], &[0x00, 0x00, 0xe0, 0x10]);
+    }
+
+    #[test]
+    fn test_multi_section() {
+        let mut pat = Pat::new(0x1234);
+        pat.set_network_pid(Some(0x1f));
+        for n in 1..=600 {
+            pat.add_program(n, 0x100 + n).unwrap();
+        }
+        assert_eq!(pat.section_count(), 3);
+
+        let (first, first_len) = pat.build_section(0).unwrap();
+        let (second, second_len) = pat.build_section(1).unwrap();
+        let (third, third_len) = pat.build_section(2).unwrap();
+        assert_eq!(first_len, PSI_SECTION_MAX_SIZE);
+
+        let mut parsed = Pat::parse(&third[..third_len]).unwrap();
+        assert!(!parsed.is_complete());
+        parsed.add_section(&first[..first_len]).unwrap();
+        parsed.add_section(&first[..first_len]).unwrap();
+        assert!(!parsed.is_complete());
+        parsed.add_section(&second[..second_len]).unwrap();
+        assert!(parsed.is_complete());
+        assert_eq!(parsed, pat);
+        assert_eq!(parsed.programs()[0].program_number, 1);
+        assert_eq!(parsed.network_pid(), Some(0x1f));
+
+        let other = Pat::new(0x4321).build_section(0).unwrap();
+        assert_eq!(parsed.add_section(&other.0[..other.1]), Err(TsError::InconsistentSection));
+    }
+
+    #[test]
+    fn test_bad_sections() {
+        let mut pat = FFMPEG_PAT;
+        pat[10] ^= 0x01;
+        assert!(matches!(Pat::parse(&pat), Err(TsError::BadCrc { .. })));
+
+        let (section, len) = SectionBuilder::new(0x02).build().unwrap();
+        assert_eq!(Pat::parse(&section[..len]), Err(TsError::BadTableId(0x02)));
+
+        let (section, len) = SectionBuilder::new(PAT_TABLE_ID).data(&[0, 1, 0xe1]).build().unwrap();
+        assert_eq!(Pat::parse(&section[..len]), Err(TsError::BadLength(3)));
+
+        let builder = SectionBuilder::new(PAT_TABLE_ID).section_number(3).last_section_number(1);
+        let (section, len) = builder.build().unwrap();
+        assert_eq!(Pat::parse(&section[..len]), Err(TsError::BadSectionNumber(3)));
+        let (first, first_len) = builder.section_number(0).build().unwrap();
+        let mut pat = Pat::parse(&first[..first_len]).unwrap();
+        assert_eq!(pat.add_section(&section[..len]), Err(TsError::BadSectionNumber(3)));
+        let (other, other_len) = builder.section_number(1).last_section_number(2).build().unwrap();
+        assert_eq!(pat.add_section(&other[..other_len]), Err(TsError::InconsistentSection));
+        assert!(!pat.is_complete());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let pat = Pat::parse(&FFMPEG_PAT).unwrap();
+        let json = serde_json::to_string(&pat).unwrap();
+        assert_eq!(
+            json,
+            r#"{"transport_stream_id":1,"version_number":0,"current_next_indicator":true,"network_pid":null,"programs":[{"program_number":1,"pmt_pid":4096}]}"#
+        );
+        assert_eq!(serde_json::from_str::<Pat>(&json).unwrap(), pat);
+
+        let bad = [
+            (r#""network_pid":8192,"programs":[]"#, "PID 0x2000 out of range"),
+            (r#""network_pid":null,"programs":[{"program_number":0,"pmt_pid":16}]"#, "reserved value in program_number"),
+            (r#""network_pid":null,"programs":[{"program_number":1,"pmt_pid":8192}]"#, "PID 0x2000 out of range"),
+            (
+                r#""network_pid":null,"programs":[{"program_number":1,"pmt_pid":256},{"program_number":1,"pmt_pid":257}]"#,
+                "section does not match the rest of the table",
+            ),
+        ];
+        for &(fields, message) in bad.iter() {
+            let json = std::format!(
+                r#"{{"transport_stream_id":1,"version_number":0,"current_next_indicator":true,{}}}"#,
+                fields
+            );
+            let err = serde_json::from_str::<Pat>(&json).unwrap_err();
+            assert!(std::format!("{}", err).contains(message), "{}", err);
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use core::ops::{Add, Sub};
+use core::time::Duration;
+
+use super::packet::{PCR_MAX, PCR_RATE};
+use crate::error::TsError;
+
+/// Ticks of the 27 MHz system clock per tick of the 90 kHz base clock
+pub const PCR_EXT_RATE: u64 = 300;
+
+/// A programme clock reference, held as its 33-bit base and 9-bit
+/// extension. Its value is 27 MHz ticks modulo 2^33 * 300.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Pcr {
+    base: u64,
+    ext: u16,
+}
+
+impl Pcr {
+    /// Make a clock value from the 33-bit base and 9-bit extension, keeping
+    /// an extension of 300 or more as it is so that it can be written back
+    pub fn new(base: u64, ext: u16) -> Self {
+        Pcr {
+            base: base & 0x1_ffff_ffff,
+            ext: ext & 0x1ff,
+        }
+    }
+
+    /// Make a clock value from the 33-bit base and 9-bit extension,
+    /// rejecting an extension of 300 or more
+    pub fn try_new(base: u64, ext: u16) -> Result<Self, TsError> {
+        if ext as u64 >= PCR_EXT_RATE {
+            return Err(TsError::Reserved("program_clock_reference_extension"));
+        }
+        Ok(Pcr::new(base, ext))
+    }
+
+    /// Make a clock value from 27 MHz ticks, wrapping at `PCR_MAX`
+    pub fn from_27mhz(ticks: u64) -> Self {
+        let ticks = ticks % PCR_MAX;
+        Pcr {
+            base: ticks / PCR_EXT_RATE,
+            ext: (ticks % PCR_EXT_RATE) as u16,
+        }
+    }
+
+    /// Make a clock value from 90 kHz ticks, wrapping at 2^33
+    pub fn from_90khz(ticks: u64) -> Self {
+        Pcr::from_27mhz((ticks % (PCR_MAX / PCR_EXT_RATE)) * PCR_EXT_RATE)
+    }
+
+    /// Make a clock value from a duration, wrapping at `PCR_MAX`
+    pub fn from_duration(duration: Duration) -> Self {
+        let ticks = duration.as_nanos() * PCR_RATE as u128 / 1_000_000_000;
+        Pcr::from_27mhz((ticks % PCR_MAX as u128) as u64)
+    }
+
+    /// Get the value in 27 MHz ticks, wrapping at `PCR_MAX`
+    pub fn as_27mhz(self) -> u64 {
+        (self.base * PCR_EXT_RATE + self.ext as u64) % PCR_MAX
+    }
+
+    /// Get the value in 90 kHz ticks, discarding the extension
+    pub fn as_90khz(self) -> u64 {
+        self.as_27mhz() / PCR_EXT_RATE
+    }
+
+    /// Get the value as a duration since the clock last wrapped
+    pub fn as_duration(self) -> Duration {
+        let nanos = self.as_27mhz() as u128 * 1_000_000_000 / PCR_RATE as u128;
+        Duration::from_nanos(nanos as u64)
+    }
+
+    /// Get the 33-bit base
+    pub fn base(self) -> u64 {
+        self.base
+    }
+
+    /// Get the 9-bit extension
+    pub fn ext(self) -> u16 {
+        self.ext
+    }
+
+    /// Add two clock values, wrapping at `PCR_MAX`
+    pub fn wrapping_add(self, other: Pcr) -> Pcr {
+        Pcr::from_27mhz(self.as_27mhz() + other.as_27mhz())
+    }
+
+    /// Get the time from `earlier` to `self`, assuming the clock has
+    /// wrapped at most once in between
+    pub fn wrapping_sub(self, earlier: Pcr) -> Pcr {
+        Pcr::from_27mhz(self.as_27mhz() + PCR_MAX - earlier.as_27mhz())
+    }
+}
+
+impl Add for Pcr {
+    type Output = Pcr;
+
+    fn add(self, other: Pcr) -> Pcr {
+        self.wrapping_add(other)
+    }
+}
+
+impl Sub for Pcr {
+    type Output = Pcr;
+
+    fn sub(self, other: Pcr) -> Pcr {
+        self.wrapping_sub(other)
+    }
+}
+
+impl From<Duration> for Pcr {
+    fn from(duration: Duration) -> Self {
+        Pcr::from_duration(duration)
+    }
+}
+
+impl From<Pcr> for Duration {
+    fn from(pcr: Pcr) -> Self {
+        pcr.as_duration()
+    }
+}
+
+/// Serialized as the 27 MHz tick count
+#[cfg(feature = "serde")]
+impl serde::Serialize for Pcr {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.as_27mhz())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Pcr {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        u64::deserialize(deserializer).map(Pcr::from_27mhz)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base_ext() {
+        let pcr = Pcr::new(0x1_2345_6789, 299);
+        assert_eq!(pcr.base(), 0x1_2345_6789);
+        assert_eq!(pcr.ext(), 299);
+        assert_eq!(pcr.as_27mhz(), 0x1_2345_6789 * 300 + 299);
+        assert_eq!(pcr.as_90khz(), 0x1_2345_6789);
+    }
+
+    #[test]
+    fn test_wrap_on_construction() {
+        assert_eq!(Pcr::from_27mhz(PCR_MAX), Pcr::from_27mhz(0));
+        assert_eq!(Pcr::from_27mhz(PCR_MAX + 5).as_27mhz(), 5);
+        assert_eq!(Pcr::from_90khz(1 << 33), Pcr::default());
+        assert_eq!(Pcr::new((1 << 33) - 1, 300).as_27mhz(), 0);
+    }
+
+    #[test]
+    fn test_reserved_ext() {
+        assert_eq!(Pcr::try_new(5, 299), Ok(Pcr::new(5, 299)));
+        assert_eq!(
+            Pcr::try_new(5, 300),
+            Err(TsError::Reserved("program_clock_reference_extension"))
+        );
+        let pcr = Pcr::new(5, 400);
+        assert_eq!(pcr.base(), 5);
+        assert_eq!(pcr.ext(), 400);
+        assert_eq!(pcr.as_27mhz(), 5 * 300 + 400);
+        assert_eq!(pcr.as_90khz(), 6);
+        assert_ne!(pcr, Pcr::from_27mhz(5 * 300 + 400));
+    }
+
+    #[test]
+    fn test_duration() {
+        let second = Pcr::from_duration(Duration::from_secs(1));
+        assert_eq!(second.as_27mhz(), PCR_RATE);
+        assert_eq!(second.as_90khz(), 90_000);
+        assert_eq!(second.as_duration(), Duration::from_secs(1));
+        assert_eq!(Duration::from(Pcr::from_27mhz(27)), Duration::from_micros(1));
+        let max = Pcr::from_27mhz(PCR_MAX - 1).as_duration();
+        assert_eq!(max.as_secs(), 95443);
+    }
+
+    #[test]
+    fn test_sub_across_wrap() {
+        let before = Pcr::from_27mhz(PCR_MAX - 100);
+        let after = Pcr::from_27mhz(50);
+        assert_eq!((after - before).as_27mhz(), 150);
+        assert_eq!((before - after).as_27mhz(), PCR_MAX - 150);
+        assert_eq!(before - before, Pcr::default());
+    }
+
+    #[test]
+    fn test_add_across_wrap() {
+        let before = Pcr::from_27mhz(PCR_MAX - 100);
+        assert_eq!((before + Pcr::from_27mhz(150)).as_27mhz(), 50);
+    }
+}
@@ -0,0 +1,145 @@
+use core::convert::TryFrom;
+use core::fmt;
+
+use super::packet::MAX_PID;
+use crate::error::TsError;
+
+/// A 13-bit packet identifier.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "u16", into = "u16"))]
+pub struct Pid(u16);
+
+/// What a PID is allocated to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PidClass {
+    /// Assigned or reserved by ISO-13818-1 (0x0000 - 0x000F)
+    Reserved,
+    /// Reserved for DVB service information (0x0010 - 0x001F)
+    DvbSi,
+    /// The ATSC PSIP base PID (0x1FFB)
+    AtscPsip,
+    /// The null packet PID (0x1FFF)
+    Null,
+    /// Free for PMTs, elementary streams and other uses
+    Elementary,
+}
+
+impl Pid {
+    /// Program Association Table
+    pub const PAT: Pid = Pid(0x0000);
+    /// Conditional Access Table
+    pub const CAT: Pid = Pid(0x0001);
+    /// Transport Stream Description Table
+    pub const TSDT: Pid = Pid(0x0002);
+    /// IPMP Control Information
+    pub const IPMP: Pid = Pid(0x0003);
+    /// Network Information Table
+    pub const NIT: Pid = Pid(0x0010);
+    /// Service Description Table and Bouquet Association Table
+    pub const SDT_BAT: Pid = Pid(0x0011);
+    /// Event Information Table
+    pub const EIT: Pid = Pid(0x0012);
+    /// Running Status Table
+    pub const RST: Pid = Pid(0x0013);
+    /// Time and Date Table and Time Offset Table
+    pub const TDT_TOT: Pid = Pid(0x0014);
+    /// Discontinuity Information Table
+    pub const DIT: Pid = Pid(0x001E);
+    /// Selection Information Table
+    pub const SIT: Pid = Pid(0x001F);
+    /// ATSC PSIP base PID
+    pub const ATSC_BASE: Pid = Pid(0x1FFB);
+    /// Null packets
+    pub const NULL: Pid = Pid(MAX_PID);
+
+    /// Make a PID, failing if it is above 8191 (0x1fff)
+    pub fn new(pid: u16) -> Result<Self, TsError> {
+        if pid > MAX_PID {
+            return Err(TsError::PidOutOfRange(pid));
+        }
+        Ok(Pid(pid))
+    }
+
+    /// Get the numeric value
+    pub fn value(self) -> u16 {
+        self.0
+    }
+
+    /// Classify the PID by its allocation
+    pub fn class(self) -> PidClass {
+        match self.0 {
+            0x0000..=0x000F => PidClass::Reserved,
+            0x0010..=0x001F => PidClass::DvbSi,
+            0x1FFB => PidClass::AtscPsip,
+            MAX_PID => PidClass::Null,
+            _ => PidClass::Elementary,
+        }
+    }
+
+    /// Is the PID free for PMTs and elementary streams?
+    pub fn is_elementary(self) -> bool {
+        self.class() == PidClass::Elementary
+    }
+
+    /// Is this the null packet PID?
+    pub fn is_null(self) -> bool {
+        self == Pid::NULL
+    }
+}
+
+impl TryFrom<u16> for Pid {
+    type Error = TsError;
+
+    fn try_from(pid: u16) -> Result<Self, Self::Error> {
+        Pid::new(pid)
+    }
+}
+
+impl From<Pid> for u16 {
+    fn from(pid: Pid) -> Self {
+        pid.0
+    }
+}
+
+impl fmt::Display for Pid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{:04x}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        assert_eq!(Pid::new(0x100).unwrap().value(), 0x100);
+        assert_eq!(Pid::new(MAX_PID), Ok(Pid::NULL));
+        assert_eq!(Pid::new(0x2000), Err(TsError::PidOutOfRange(0x2000)));
+        assert_eq!(Pid::try_from(0xffff), Err(TsError::PidOutOfRange(0xffff)));
+        assert_eq!(u16::from(Pid::SIT), 0x1f);
+    }
+
+    #[test]
+    fn test_class() {
+        assert_eq!(Pid::PAT.class(), PidClass::Reserved);
+        assert_eq!(Pid::IPMP.class(), PidClass::Reserved);
+        assert_eq!(Pid::new(0x000F).unwrap().class(), PidClass::Reserved);
+        assert_eq!(Pid::NIT.class(), PidClass::DvbSi);
+        assert_eq!(Pid::SIT.class(), PidClass::DvbSi);
+        assert_eq!(Pid::ATSC_BASE.class(), PidClass::AtscPsip);
+        assert_eq!(Pid::NULL.class(), PidClass::Null);
+        assert!(Pid::new(0x0020).unwrap().is_elementary());
+        assert!(Pid::new(0x1FFE).unwrap().is_elementary());
+        assert!(!Pid::EIT.is_elementary());
+        assert!(Pid::NULL.is_null());
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(std::format!("{}", Pid::TDT_TOT), "0x0014");
+        assert_eq!(std::format!("{}", Pid::NULL), "0x1fff");
+    }
+}
@@ -0,0 +1,582 @@
+#[cfg(feature = "serde")]
+use core::convert::TryFrom;
+#[cfg(feature = "serde")]
+use core::fmt;
+use core::ops::Range;
+use core::slice;
+
+use super::ca::CaDescriptors;
+use super::crc::CRC_SIZE;
+use super::descriptor::Descriptors;
+use super::packet::SECTION_MAX_SIZE;
+use super::section::LONG_SECTION_HEADER_SIZE;
+use super::section_builder::{SectionBuffer, SectionBuilder};
+use super::stream_type::StreamType;
+use super::table::{parse_table_section, FixedVec, PSI_SECTION_MAX_SIZE};
+use crate::error::TsError;
+
+/// table_id of a TS_program_map_section
+pub const PMT_TABLE_ID: u8 = 0x02;
+/// Most elementary streams a `Pmt` can hold
+pub const PMT_MAX_STREAMS: usize = 256;
+
+// PCR_PID and program_info_length
+const PMT_FIXED_SIZE: usize = 4;
+// stream_type, elementary_PID and ES_info_length
+const STREAM_HEADER_SIZE: usize = 5;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct StreamEntry {
+    stream_type: u8,
+    elementary_pid: u16,
+    es_info_length: u16,
+}
+
+/// One elementary stream of a PMT.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ElementaryStream<'a> {
+    stream_type: StreamType,
+    elementary_pid: u16,
+    es_info: &'a [u8],
+}
+
+impl<'a> ElementaryStream<'a> {
+    /// Get the stream_type
+    pub fn stream_type(&self) -> StreamType {
+        self.stream_type
+    }
+
+    /// Get the elementary_PID
+    pub fn elementary_pid(&self) -> u16 {
+        self.elementary_pid
+    }
+
+    /// Get the raw ES_info descriptor loop
+    pub fn es_info(&self) -> &'a [u8] {
+        self.es_info
+    }
+
+    /// Iterate over the ES_info descriptors
+    pub fn descriptors(&self) -> Descriptors<'a> {
+        Descriptors::new_unchecked(self.es_info)
+    }
+
+    /// Iterate over the CA_descriptors, each naming an ECM PID for the stream
+    pub fn ca_descriptors(&self) -> CaDescriptors<'a> {
+        CaDescriptors::new(self.descriptors())
+    }
+}
+
+/// Iterator over the elementary streams of a PMT, created by `Pmt::streams`.
+#[derive(Clone, Debug)]
+pub struct ElementaryStreams<'a> {
+    entries: slice::Iter<'a, StreamEntry>,
+    es_info: &'a [u8],
+}
+
+impl<'a> Iterator for ElementaryStreams<'a> {
+    type Item = ElementaryStream<'a>;
+
+    fn next(&mut self) -> Option<ElementaryStream<'a>> {
+        let entry = self.entries.next()?;
+        let (es_info, rest) = self.es_info.split_at(entry.es_info_length as usize);
+        self.es_info = rest;
+        Some(ElementaryStream {
+            stream_type: entry.stream_type.into(),
+            elementary_pid: entry.elementary_pid,
+            es_info,
+        })
+    }
+}
+
+/// A program map table. The descriptor loops are kept in one buffer, the
+/// program_info followed by each stream's ES_info in table order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "PmtRecord"))]
+pub struct Pmt {
+    program_number: u16,
+    version_number: u8,
+    current_next_indicator: bool,
+    pcr_pid: u16,
+    program_info_length: u16,
+    streams: FixedVec<StreamEntry, PMT_MAX_STREAMS>,
+    descriptors: FixedVec<u8, PSI_SECTION_MAX_SIZE>,
+}
+
+impl Pmt {
+    /// Start a current PMT at version 0 with no descriptors or streams
+    pub fn new(program_number: u16, pcr_pid: u16) -> Self {
+        Pmt {
+            program_number,
+            version_number: 0,
+            current_next_indicator: true,
+            pcr_pid: pcr_pid & 0x1fff,
+            program_info_length: 0,
+            streams: FixedVec::new(),
+            descriptors: FixedVec::new(),
+        }
+    }
+
+    /// Parse a TS_program_map_section, checking its CRC_32 and descriptor
+    /// loops
+    pub fn parse(bytes: &[u8]) -> Result<Self, TsError> {
+        let section = parse_table_section(bytes, PMT_TABLE_ID)?;
+        let data = section.data();
+        if data.len() < PMT_FIXED_SIZE {
+            return Err(TsError::BadLength(data.len()));
+        }
+        let mut pmt = Pmt::new(
+            section.table_id_extension().unwrap_or(0),
+            u16::from_be_bytes([data[0], data[1]]),
+        );
+        pmt.version_number = section.version_number().unwrap_or(0);
+        pmt.current_next_indicator = section.current_next_indicator().unwrap_or(true);
+
+        let program_info_end = PMT_FIXED_SIZE + (u16::from_be_bytes([data[2], data[3]]) & 0x0fff) as usize;
+        if program_info_end > data.len() {
+            return Err(TsError::BadLength(data.len()));
+        }
+        pmt.set_program_info(&data[PMT_FIXED_SIZE..program_info_end])?;
+
+        let mut rest = &data[program_info_end..];
+        while !rest.is_empty() {
+            if rest.len() < STREAM_HEADER_SIZE {
+                return Err(TsError::BadLength(rest.len()));
+            }
+            let es_info_end = STREAM_HEADER_SIZE + (u16::from_be_bytes([rest[3], rest[4]]) & 0x0fff) as usize;
+            if es_info_end > rest.len() {
+                return Err(TsError::BadLength(rest.len()));
+            }
+            pmt.add_stream(
+                rest[0].into(),
+                u16::from_be_bytes([rest[1], rest[2]]),
+                &rest[STREAM_HEADER_SIZE..es_info_end],
+            )?;
+            rest = &rest[es_info_end..];
+        }
+        Ok(pmt)
+    }
+
+    /// Get the program_number
+    pub fn program_number(&self) -> u16 {
+        self.program_number
+    }
+
+    /// Set the program_number
+    pub fn set_program_number(&mut self, program_number: u16) {
+        self.program_number = program_number;
+    }
+
+    /// Get the version_number
+    pub fn version_number(&self) -> u8 {
+        self.version_number
+    }
+
+    /// Set the version_number. Max: 31
+    pub fn set_version_number(&mut self, version: u8) {
+        self.version_number = version & 0x1f;
+    }
+
+    /// Get the current_next_indicator
+    pub fn current_next_indicator(&self) -> bool {
+        self.current_next_indicator
+    }
+
+    /// Set the current_next_indicator
+    pub fn set_current_next_indicator(&mut self, current: bool) {
+        self.current_next_indicator = current;
+    }
+
+    /// Get the PCR_PID, 0x1fff if the program has no PCR
+    pub fn pcr_pid(&self) -> u16 {
+        self.pcr_pid
+    }
+
+    /// Set the PCR_PID
+    pub fn set_pcr_pid(&mut self, pid: u16) {
+        self.pcr_pid = pid & 0x1fff;
+    }
+
+    /// Get the raw program_info descriptor loop
+    pub fn program_info(&self) -> &[u8] {
+        &self.descriptors.as_slice()[..self.program_info_length as usize]
+    }
+
+    /// Iterate over the program_info descriptors
+    pub fn program_descriptors(&self) -> Descriptors<'_> {
+        Descriptors::new_unchecked(self.program_info())
+    }
+
+    /// Iterate over the CA_descriptors of the program_info and every ES_info
+    /// loop, each naming an ECM PID
+    pub fn ca_descriptors(&self) -> CaDescriptors<'_> {
+        // the loops are stored back to back, which is itself a valid loop
+        CaDescriptors::new(Descriptors::new_unchecked(self.descriptors.as_slice()))
+    }
+
+    /// Replace the program_info descriptor loop
+    pub fn set_program_info(&mut self, program_info: &[u8]) -> Result<(), TsError> {
+        Descriptors::new(program_info)?;
+        self.descriptors
+            .splice(0, self.program_info_length as usize, program_info)?;
+        self.program_info_length = program_info.len() as u16;
+        Ok(())
+    }
+
+    /// Iterate over the elementary streams in table order
+    pub fn streams(&self) -> ElementaryStreams<'_> {
+        ElementaryStreams {
+            entries: self.streams.as_slice().iter(),
+            es_info: &self.descriptors.as_slice()[self.program_info_length as usize..],
+        }
+    }
+
+    /// Find the elementary stream on a PID
+    pub fn stream(&self, elementary_pid: u16) -> Option<ElementaryStream<'_>> {
+        self.streams().find(|s| s.elementary_pid == elementary_pid)
+    }
+
+    /// Add an elementary stream with its ES_info descriptor loop, replacing
+    /// any stream already on the PID
+    pub fn add_stream(&mut self, stream_type: StreamType, elementary_pid: u16, es_info: &[u8]) -> Result<(), TsError> {
+        Descriptors::new(es_info)?;
+        let elementary_pid = elementary_pid & 0x1fff;
+        let entry = StreamEntry {
+            stream_type: stream_type.into(),
+            elementary_pid,
+            es_info_length: es_info.len() as u16,
+        };
+        match self.position(elementary_pid) {
+            Some(index) => {
+                let range = self.es_info_range(index);
+                self.descriptors.splice(range.start, range.len(), es_info)?;
+                self.streams.as_mut_slice()[index] = entry;
+            }
+            None => {
+                self.streams.push(entry)?;
+                let end = self.descriptors.len();
+                if let Err(err) = self.descriptors.splice(end, 0, es_info) {
+                    self.streams.remove(self.streams.len() - 1);
+                    return Err(err);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove the elementary stream on a PID, returning whether there was one
+    pub fn remove_stream(&mut self, elementary_pid: u16) -> bool {
+        match self.position(elementary_pid) {
+            Some(index) => {
+                let range = self.es_info_range(index);
+                // removing bytes always fits
+                let _ = self.descriptors.splice(range.start, range.len(), &[]);
+                self.streams.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn position(&self, elementary_pid: u16) -> Option<usize> {
+        self.streams
+            .as_slice()
+            .iter()
+            .position(|s| s.elementary_pid == elementary_pid)
+    }
+
+    fn es_info_range(&self, index: usize) -> Range<usize> {
+        let entries = self.streams.as_slice();
+        let start = self.program_info_length as usize
+            + entries[..index]
+                .iter()
+                .map(|s| s.es_info_length as usize)
+                .sum::<usize>();
+        start..start + entries[index].es_info_length as usize
+    }
+
+    /// Get the size of the section carrying the table
+    pub fn section_size(&self) -> usize {
+        LONG_SECTION_HEADER_SIZE
+            + PMT_FIXED_SIZE
+            + self.streams.len() * STREAM_HEADER_SIZE
+            + self.descriptors.len()
+            + CRC_SIZE
+    }
+
+    /// Write the table's section to the start of `out`, returning its length
+    pub fn write_section(&self, out: &mut [u8]) -> Result<usize, TsError> {
+        let size = self.section_size();
+        if size > PSI_SECTION_MAX_SIZE {
+            return Err(TsError::SectionTooLong(size));
+        }
+        let mut data = [0u8; PSI_SECTION_MAX_SIZE];
+        data[0..2].copy_from_slice(&(0xe000 | self.pcr_pid).to_be_bytes());
+        data[2..4].copy_from_slice(&(0xf000 | self.program_info_length).to_be_bytes());
+        let mut pos = PMT_FIXED_SIZE + self.program_info_length as usize;
+        data[PMT_FIXED_SIZE..pos].copy_from_slice(self.program_info());
+        for stream in self.streams() {
+            data[pos] = stream.stream_type.into();
+            data[pos + 1..pos + 3].copy_from_slice(&(0xe000 | stream.elementary_pid).to_be_bytes());
+            data[pos + 3..pos + 5].copy_from_slice(&(0xf000 | stream.es_info.len() as u16).to_be_bytes());
+            pos += STREAM_HEADER_SIZE;
+            data[pos..pos + stream.es_info.len()].copy_from_slice(stream.es_info);
+            pos += stream.es_info.len();
+        }
+        SectionBuilder::new(PMT_TABLE_ID)
+            .table_id_extension(self.program_number)
+            .version_number(self.version_number)
+            .current_next_indicator(self.current_next_indicator)
+            .data(&data[..pos])
+            .write(out)
+    }
+
+    /// Build the table's section, returning it with its length
+    pub fn build_section(&self) -> Result<(SectionBuffer, usize), TsError> {
+        let mut section: SectionBuffer = [0xff; SECTION_MAX_SIZE];
+        let len = self.write_section(&mut section)?;
+        Ok((section, len))
+    }
+}
+
+/// Serialized with each stream's ES_info alongside it
+#[cfg(feature = "serde")]
+impl serde::Serialize for Pmt {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        struct Streams<'a>(ElementaryStreams<'a>);
+
+        impl<'a> serde::Serialize for Streams<'a> {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_seq(self.0.clone())
+            }
+        }
+
+        let mut pmt = serializer.serialize_struct("Pmt", 6)?;
+        pmt.serialize_field("program_number", &self.program_number)?;
+        pmt.serialize_field("version_number", &self.version_number)?;
+        pmt.serialize_field("current_next_indicator", &self.current_next_indicator)?;
+        pmt.serialize_field("pcr_pid", &self.pcr_pid)?;
+        pmt.serialize_field("program_info", self.program_info())?;
+        pmt.serialize_field("streams", &Streams(self.streams()))?;
+        pmt.end()
+    }
+}
+
+/// A deserialized `Pmt`, rebuilt through `set_program_info` and `add_stream`
+/// so that its descriptor loops are checked
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct PmtRecord {
+    program_number: u16,
+    version_number: u8,
+    current_next_indicator: bool,
+    pcr_pid: u16,
+    program_info: FixedVec<u8, PSI_SECTION_MAX_SIZE>,
+    streams: StreamRecords,
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct StreamRecord {
+    stream_type: StreamType,
+    elementary_pid: u16,
+    es_info: FixedVec<u8, PSI_SECTION_MAX_SIZE>,
+}
+
+/// The streams of a `PmtRecord`, their ES_info loops kept back to back
+#[cfg(feature = "serde")]
+#[derive(Default)]
+struct StreamRecords {
+    entries: FixedVec<StreamEntry, PMT_MAX_STREAMS>,
+    es_info: FixedVec<u8, PSI_SECTION_MAX_SIZE>,
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for StreamRecords {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct StreamRecordsVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for StreamRecordsVisitor {
+            type Value = StreamRecords;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "a sequence of at most {} elementary streams", PMT_MAX_STREAMS)
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut streams = StreamRecords::default();
+                while let Some(stream) = seq.next_element::<StreamRecord>()? {
+                    let es_info = stream.es_info.as_slice();
+                    let entry = StreamEntry {
+                        stream_type: stream.stream_type.into(),
+                        elementary_pid: stream.elementary_pid,
+                        es_info_length: es_info.len() as u16,
+                    };
+                    let end = streams.es_info.len();
+                    streams
+                        .entries
+                        .push(entry)
+                        .and_then(|_| streams.es_info.splice(end, 0, es_info))
+                        .map_err(serde::de::Error::custom)?;
+                }
+                Ok(streams)
+            }
+        }
+
+        deserializer.deserialize_seq(StreamRecordsVisitor)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<PmtRecord> for Pmt {
+    type Error = TsError;
+
+    fn try_from(record: PmtRecord) -> Result<Self, TsError> {
+        let mut pmt = Pmt::new(record.program_number, record.pcr_pid);
+        pmt.set_version_number(record.version_number);
+        pmt.set_current_next_indicator(record.current_next_indicator);
+        pmt.set_program_info(record.program_info.as_slice())?;
+        let mut es_info = record.streams.es_info.as_slice();
+        for entry in record.streams.entries.as_slice() {
+            let (info, rest) = es_info.split_at(entry.es_info_length as usize);
+            pmt.add_stream(entry.stream_type.into(), entry.elementary_pid, info)?;
+            es_info = rest;
+        }
+        Ok(pmt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // FFmpeg's PMT for program 1: H.264 on 0x100 carrying the PCR, AAC on 0x101
+    const FFMPEG_PMT: [u8; 26] = [
+        0x02, 0xb0, 0x17, 0x00, 0x01, 0xc1, 0x00, 0x00, 0xe1, 0x00, 0xf0, 0x00, 0x1b, 0xe1, 0x00, 0xf0, 0x00,
+        0x0f, 0xe1, 0x01, 0xf0, 0x00, 0x2f, 0x44, 0xb9, 0x9b,
+    ];
+
+    #[test]
+    fn test_parse() {
+        let pmt = Pmt::parse(&FFMPEG_PMT).unwrap();
+        assert_eq!(pmt.program_number(), 1);
+        assert_eq!(pmt.version_number(), 0);
+        assert!(pmt.current_next_indicator());
+        assert_eq!(pmt.pcr_pid(), 0x100);
+        assert!(pmt.program_info().is_empty());
+        let mut streams = pmt.streams();
+        let video = streams.next().unwrap();
+        assert_eq!(video.stream_type(), StreamType::Avc);
+        assert_eq!(video.elementary_pid(), 0x100);
+        assert!(video.es_info().is_empty());
+        let audio = streams.next().unwrap();
+        assert_eq!(audio.stream_type(), StreamType::AacAdts);
+        assert_eq!(audio.elementary_pid(), 0x101);
+        assert!(streams.next().is_none());
+        assert_eq!(pmt.stream(0x101), Some(audio));
+        assert_eq!(pmt.stream(0x102), None);
+    }
+
+    #[test]
+    fn test_build() {
+        let mut pmt = Pmt::new(1, 0x100);
+        pmt.add_stream(StreamType::Avc, 0x100, &[]).unwrap();
+        pmt.add_stream(StreamType::AacAdts, 0x101, &[]).unwrap();
+        assert_eq!(pmt.section_size(), FFMPEG_PMT.len());
+        let (section, len) = pmt.build_section().unwrap();
+        assert_eq!(&section[..len], &FFMPEG_PMT);
+    }
+
+    #[test]
+    fn test_descriptors() {
+        let mut pmt = Pmt::new(0x0102, 0x1fff);
+        pmt.set_version_number(5);
+        pmt.add_stream(StreamType::Hevc, 0x200, &[0x38, 0x01, 0x00]).unwrap();
+        pmt.add_stream(StreamType::AacLatm, 0x201, &[0x0a, 0x04, b'e', b'n', b'g', 0x00]).unwrap();
+        pmt.add_stream(StreamType::Scte35, 0x202, &[]).unwrap();
+        pmt.set_program_info(&[0x05, 0x04, b'C', b'U', b'E', b'I']).unwrap();
+
+        let (section, len) = pmt.build_section().unwrap();
+        let parsed = Pmt::parse(&section[..len]).unwrap();
+        assert_eq!(parsed, pmt);
+        let registration = parsed.program_descriptors().next().unwrap();
+        assert_eq!(registration.tag(), 0x05);
+        assert_eq!(registration.data(), b"CUEI");
+        let language = parsed.stream(0x201).unwrap().descriptors().find_tag(0x0a).unwrap();
+        assert_eq!(language.data(), b"eng\0");
+        assert_eq!(parsed.stream(0x200).unwrap().es_info(), &[0x38, 0x01, 0x00]);
+
+        // replacing and removing streams keeps the other loops in place
+        pmt.add_stream(StreamType::Vvc, 0x200, &[]).unwrap();
+        assert_eq!(pmt.stream(0x200).unwrap().stream_type(), StreamType::Vvc);
+        assert_eq!(pmt.stream(0x201).unwrap().es_info().len(), 6);
+        assert!(pmt.remove_stream(0x201));
+        assert!(!pmt.remove_stream(0x201));
+        assert_eq!(pmt.streams().count(), 2);
+        assert_eq!(pmt.program_descriptors().next(), Some(registration));
+        pmt.set_program_info(&[]).unwrap();
+        let (section, len) = pmt.build_section().unwrap();
+        assert_eq!(Pmt::parse(&section[..len]).unwrap(), pmt);
+    }
+
+    #[test]
+    fn test_bad_sections() {
+        let mut pmt = FFMPEG_PMT;
+        pmt[12] ^= 0x01;
+        assert!(matches!(Pmt::parse(&pmt), Err(TsError::BadCrc { .. })));
+
+        let (section, len) = SectionBuilder::new(PMT_TABLE_ID).data(&[0xe1, 0x00, 0xf0, 0x01]).build().unwrap();
+        assert_eq!(Pmt::parse(&section[..len]), Err(TsError::BadLength(4)));
+
+        let data = [0xe1, 0x00, 0xf0, 0x00, 0x1b, 0xe1, 0x00, 0xf0, 0x02, 0x0a, 0x04];
+        let (section, len) = SectionBuilder::new(PMT_TABLE_ID).data(&data).build().unwrap();
+        assert_eq!(Pmt::parse(&section[..len]), Err(TsError::TruncatedDescriptor(0x0a)));
+
+        let mut pmt = Pmt::new(1, 0x100);
+        assert_eq!(pmt.add_stream(StreamType::Avc, 0x100, &[0x0a]), Err(TsError::TruncatedDescriptor(0x0a)));
+        let descriptor = [0u8; 257];
+        for pid in 0x100..0x104 {
+            pmt.add_stream(StreamType::PrivateData, pid, &descriptor[..254]).unwrap();
+        }
+        assert_eq!(pmt.build_section().err(), Some(TsError::SectionTooLong(pmt.section_size())));
+        assert_eq!(
+            pmt.add_stream(StreamType::PrivateData, 0x200, &descriptor[..10]),
+            Err(TsError::TooManyEntries(PSI_SECTION_MAX_SIZE))
+        );
+        assert_eq!(pmt.streams().count(), 4);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let mut pmt = Pmt::parse(&FFMPEG_PMT).unwrap();
+        pmt.set_program_info(&[0x05, 0x00]).unwrap();
+        let json = serde_json::to_string(&pmt).unwrap();
+        assert_eq!(
+            json,
+            concat!(
+                r#"{"program_number":1,"version_number":0,"current_next_indicator":true,"pcr_pid":256,"#,
+                r#""program_info":[5,0],"streams":[{"stream_type":27,"elementary_pid":256,"es_info":[]},"#,
+                r#"{"stream_type":15,"elementary_pid":257,"es_info":[]}]}"#
+            )
+        );
+        assert_eq!(serde_json::from_str::<Pmt>(&json).unwrap(), pmt);
+
+        let bad = [
+            (r#""program_info":[5,50,0,0,0],"streams":[]"#, "descriptor 0x05 truncated"),
+            (r#""program_info":[],"streams":[{"stream_type":2,"elementary_pid":256,"es_info":[9]}]"#, "descriptor 0x09 truncated"),
+        ];
+        for &(fields, message) in bad.iter() {
+            let json = std::format!(
+                r#"{{"program_number":1,"version_number":0,"current_next_indicator":true,"pcr_pid":256,{}}}"#,
+                fields
+            );
+            let err = serde_json::from_str::<Pmt>(&json).unwrap_err();
+            assert!(std::format!("{}", err).contains(message), "{}", err);
+        }
+    }
+}
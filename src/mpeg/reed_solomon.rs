@@ -0,0 +1,306 @@
+use super::format::{rs_inner_mut, RsPacket, RS_PACKET_SIZE, RS_PARITY_SIZE};
+use super::packet::{self, Packet, PACKET_SIZE};
+use crate::error::TsError;
+
+/// Number of byte errors RS(204,188) can correct per packet
+pub const RS_MAX_CORRECTABLE: usize = RS_PARITY_SIZE / 2;
+
+// GF(2^8) with the DVB field generator x^8 + x^4 + x^3 + x^2 + 1
+const GF_POLY: u16 = 0x11d;
+
+const fn gf_tables() -> ([u8; 512], [u8; 256]) {
+    let mut exp = [0u8; 512];
+    let mut log = [0u8; 256];
+    let mut x: u16 = 1;
+    let mut i = 0;
+    while i < 255 {
+        exp[i] = x as u8;
+        exp[i + 255] = x as u8;
+        log[x as usize] = i as u8;
+        x <<= 1;
+        if x & 0x100 != 0 {
+            x ^= GF_POLY;
+        }
+        i += 1;
+    }
+    (exp, log)
+}
+
+const GF_TABLES: ([u8; 512], [u8; 256]) = gf_tables();
+const GF_EXP: [u8; 512] = GF_TABLES.0;
+const GF_LOG: [u8; 256] = GF_TABLES.1;
+
+const fn gf_mul_const(a: u8, b: u8) -> u8 {
+    if a == 0 || b == 0 {
+        return 0;
+    }
+    GF_EXP[GF_LOG[a as usize] as usize + GF_LOG[b as usize] as usize]
+}
+
+/// Coefficients of g(x) = (x + a^0)(x + a^1)...(x + a^15), highest degree first
+const fn generator() -> [u8; RS_PARITY_SIZE + 1] {
+    let mut g = [0u8; RS_PARITY_SIZE + 1];
+    g[0] = 1;
+    let mut i = 0;
+    while i < RS_PARITY_SIZE {
+        let root = GF_EXP[i];
+        let mut j = i + 1;
+        while j > 0 {
+            g[j] ^= gf_mul_const(g[j - 1], root);
+            j -= 1;
+        }
+        i += 1;
+    }
+    g
+}
+
+const GENERATOR: [u8; RS_PARITY_SIZE + 1] = generator();
+
+fn gf_mul(a: u8, b: u8) -> u8 {
+    gf_mul_const(a, b)
+}
+
+fn gf_div(a: u8, b: u8) -> u8 {
+    if a == 0 {
+        return 0;
+    }
+    GF_EXP[GF_LOG[a as usize] as usize + 255 - GF_LOG[b as usize] as usize]
+}
+
+/// Get a^power for any power, including negative ones
+fn gf_pow_alpha(power: i32) -> u8 {
+    GF_EXP[power.rem_euclid(255) as usize]
+}
+
+/// Compute the Reed-Solomon parity bytes for a packet
+pub fn rs_encode(packet: &Packet) -> [u8; RS_PARITY_SIZE] {
+    let mut parity = [0u8; RS_PARITY_SIZE];
+    for &byte in packet.iter() {
+        let feedback = byte ^ parity[0];
+        parity.copy_within(1.., 0);
+        parity[RS_PARITY_SIZE - 1] = 0;
+        if feedback != 0 {
+            for (p, &g) in parity.iter_mut().zip(GENERATOR[1..].iter()) {
+                *p ^= gf_mul(feedback, g);
+            }
+        }
+    }
+    parity
+}
+
+/// Make an RS packet from a transport stream packet, computing the parity
+pub fn rs_encode_packet(packet: &Packet) -> RsPacket {
+    let mut rs = [0; RS_PACKET_SIZE];
+    rs[..PACKET_SIZE].copy_from_slice(packet);
+    rs[PACKET_SIZE..].copy_from_slice(&rs_encode(packet));
+    rs
+}
+
+/// Correct up to 8 byte errors in place, returning the number corrected.
+/// If the packet cannot be corrected, its transport error indicator is set.
+pub fn rs_decode(rs: &mut RsPacket) -> Result<usize, TsError> {
+    let result = correct(rs);
+    if result.is_err() {
+        packet::set_transport_error(rs_inner_mut(rs));
+    }
+    result
+}
+
+fn correct(rs: &mut RsPacket) -> Result<usize, TsError> {
+    // syndromes S_j = r(a^j)
+    let mut syndromes = [0u8; RS_PARITY_SIZE];
+    for (j, s) in syndromes.iter_mut().enumerate() {
+        let root = GF_EXP[j];
+        *s = rs.iter().fold(0, |acc, &byte| gf_mul(acc, root) ^ byte);
+    }
+    if syndromes.iter().all(|&s| s == 0) {
+        return Ok(0);
+    }
+
+    // Berlekamp-Massey for the error locator, lowest degree first
+    let mut locator = [0u8; RS_PARITY_SIZE + 1];
+    let mut previous = [0u8; RS_PARITY_SIZE + 1];
+    locator[0] = 1;
+    previous[0] = 1;
+    let mut errors = 0;
+    let mut shift = 1;
+    let mut last_discrepancy = 1u8;
+    for n in 0..RS_PARITY_SIZE {
+        let mut discrepancy = syndromes[n];
+        for i in 1..=errors {
+            discrepancy ^= gf_mul(locator[i], syndromes[n - i]);
+        }
+        if discrepancy == 0 {
+            shift += 1;
+            continue;
+        }
+        let scale = gf_div(discrepancy, last_discrepancy);
+        let saved = locator;
+        for i in shift..=RS_PARITY_SIZE {
+            locator[i] ^= gf_mul(scale, previous[i - shift]);
+        }
+        if 2 * errors <= n {
+            errors = n + 1 - errors;
+            previous = saved;
+            last_discrepancy = discrepancy;
+            shift = 1;
+        } else {
+            shift += 1;
+        }
+    }
+    if errors > RS_MAX_CORRECTABLE {
+        return Err(TsError::Uncorrectable);
+    }
+
+    // error evaluator Omega(x) = S(x) Lambda(x) mod x^16
+    let mut evaluator = [0u8; RS_PARITY_SIZE];
+    for (i, e) in evaluator.iter_mut().enumerate() {
+        for j in 0..=i.min(errors) {
+            *e ^= gf_mul(locator[j], syndromes[i - j]);
+        }
+    }
+
+    // Chien search over the shortened codeword, then Forney for the
+    // magnitudes, only changing bytes once every error has been located
+    let mut positions = [0usize; RS_MAX_CORRECTABLE];
+    let mut magnitudes = [0u8; RS_MAX_CORRECTABLE];
+    let mut found = 0;
+    for index in 0..RS_PACKET_SIZE {
+        let degree = (RS_PACKET_SIZE - 1 - index) as i32;
+        let x_inv = gf_pow_alpha(-degree);
+        let value = evaluate(&locator[..=errors], x_inv);
+        if value != 0 {
+            continue;
+        }
+        if found == errors {
+            return Err(TsError::Uncorrectable);
+        }
+        // formal derivative keeps only the odd powers
+        let mut derivative = 0u8;
+        let mut power = 1u8;
+        let x_inv_sq = gf_mul(x_inv, x_inv);
+        for i in (1..=errors).step_by(2) {
+            derivative ^= gf_mul(locator[i], power);
+            power = gf_mul(power, x_inv_sq);
+        }
+        if derivative == 0 {
+            return Err(TsError::Uncorrectable);
+        }
+        let omega = evaluate(&evaluator, x_inv);
+        positions[found] = index;
+        magnitudes[found] = gf_mul(gf_pow_alpha(degree), gf_div(omega, derivative));
+        found += 1;
+    }
+    if found != errors {
+        return Err(TsError::Uncorrectable);
+    }
+    for (&index, &magnitude) in positions[..found].iter().zip(magnitudes.iter()) {
+        rs[index] ^= magnitude;
+    }
+    Ok(found)
+}
+
+/// Evaluate a polynomial, lowest degree first, at x
+fn evaluate(poly: &[u8], x: u8) -> u8 {
+    poly.iter().rev().fold(0, |acc, &c| gf_mul(acc, x) ^ c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpeg::format::rs_inner;
+
+    fn test_packet() -> Packet {
+        let mut pkt = packet::null_packet();
+        packet::set_pid(&mut pkt, 0x123);
+        for (i, byte) in pkt[4..].iter_mut().enumerate() {
+            *byte = (i * 7 + 3) as u8;
+        }
+        pkt
+    }
+
+    #[test]
+    fn test_generator() {
+        // g(x) for the DVB code, as listed in EN 300 421 implementations
+        let expected = [
+            1, 59, 13, 104, 189, 68, 209, 30, 8, 163, 65, 41, 229, 98, 50, 36, 59,
+        ];
+        assert_eq!(GENERATOR, expected);
+    }
+
+    #[test]
+    fn test_null_packet_parity() {
+        let rs = rs_encode_packet(&packet::null_packet());
+        let mut copy = rs;
+        assert_eq!(rs_decode(&mut copy), Ok(0));
+        assert_eq!(copy[..], rs[..]);
+    }
+
+    #[test]
+    fn test_no_errors() {
+        let mut rs = rs_encode_packet(&test_packet());
+        assert_eq!(rs_decode(&mut rs), Ok(0));
+        assert!(!packet::has_transport_error(rs_inner(&rs)));
+    }
+
+    #[test]
+    fn test_correct_single_error() {
+        let original = rs_encode_packet(&test_packet());
+        for &index in [0usize, 1, 100, 187, 188, 203].iter() {
+            let mut rs = original;
+            rs[index] ^= 0x5a;
+            assert_eq!(rs_decode(&mut rs), Ok(1), "index {}", index);
+            assert_eq!(rs[..], original[..]);
+        }
+    }
+
+    #[test]
+    fn test_correct_max_errors() {
+        let original = rs_encode_packet(&test_packet());
+        let mut rs = original;
+        for (n, &index) in [3usize, 17, 40, 99, 150, 187, 190, 203].iter().enumerate() {
+            rs[index] ^= (n as u8 + 1) * 31;
+        }
+        assert_eq!(rs_decode(&mut rs), Ok(RS_MAX_CORRECTABLE));
+        assert_eq!(rs[..], original[..]);
+        assert!(!packet::has_transport_error(rs_inner(&rs)));
+    }
+
+    #[test]
+    fn test_uncorrectable() {
+        let original = rs_encode_packet(&test_packet());
+        let mut rs = original;
+        for byte in rs[10..20].iter_mut() {
+            *byte ^= 0xff;
+        }
+        assert_eq!(rs_decode(&mut rs), Err(TsError::Uncorrectable));
+        assert!(packet::has_transport_error(rs_inner(&rs)));
+    }
+
+    #[test]
+    fn test_uncorrectable_unchanged() {
+        let original = rs_encode_packet(&test_packet());
+        let mut seed = 0x1234_5678u32;
+        let mut next = || {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            (seed >> 16) as usize
+        };
+        let mut failures = 0;
+        for _ in 0..500 {
+            let mut corrupted = original;
+            for _ in 0..9 + next() % 6 {
+                corrupted[next() % RS_PACKET_SIZE] ^= 1 + (next() % 255) as u8;
+            }
+            let mut rs = corrupted;
+            if rs_decode(&mut rs).is_ok() {
+                continue;
+            }
+            failures += 1;
+            // only the transport error indicator may change
+            rs[1] &= !0x80;
+            corrupted[1] &= !0x80;
+            assert_eq!(rs[..], corrupted[..]);
+        }
+        assert!(failures > 400);
+    }
+}
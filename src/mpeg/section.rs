@@ -0,0 +1,558 @@
+use super::crc::{verify_section, CRC_SIZE};
+use super::packet::{self, Packet, PAYLOAD_SIZE, SECTION_MAX_SIZE};
+use crate::error::TsError;
+
+/// Bytes before section_length is known: table_id and the 16 bits holding it
+pub const SECTION_HEADER_SIZE: usize = 3;
+/// Bytes in a long-form header, up to and including last_section_number
+pub const LONG_SECTION_HEADER_SIZE: usize = 8;
+
+/// Get the total length of a section from its first three bytes
+pub fn section_total_length(header: &[u8]) -> usize {
+    SECTION_HEADER_SIZE + (((header[1] as usize & 0x0f) << 8) | header[2] as usize)
+}
+
+/// A borrowed view of one complete PSI/SI section. It serializes as its
+/// bytes; there is nothing to borrow from when deserializing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Section<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Section<'a> {
+    /// View the section at the start of `bytes`, checking its length fits
+    /// both the buffer and `SECTION_MAX_SIZE`
+    pub fn new(bytes: &'a [u8]) -> Result<Self, TsError> {
+        if bytes.len() < SECTION_HEADER_SIZE {
+            return Err(TsError::BadLength(bytes.len()));
+        }
+        let total = section_total_length(bytes);
+        if total > SECTION_MAX_SIZE {
+            return Err(TsError::SectionTooLong(total));
+        }
+        if total > bytes.len() {
+            return Err(TsError::BadLength(bytes.len()));
+        }
+        if bytes[1] & 0x80 != 0 && total < LONG_SECTION_HEADER_SIZE + CRC_SIZE {
+            return Err(TsError::BadLength(total));
+        }
+        Ok(Section {
+            bytes: &bytes[..total],
+        })
+    }
+
+    /// Get the whole section, header and CRC_32 included
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Get the table_id
+    pub fn table_id(&self) -> u8 {
+        self.bytes[0]
+    }
+
+    /// Is the section_syntax_indicator set, marking a long-form section?
+    pub fn section_syntax_indicator(&self) -> bool {
+        self.bytes[1] & 0x80 != 0
+    }
+
+    /// Is the private_indicator set?
+    pub fn private_indicator(&self) -> bool {
+        self.bytes[1] & 0x40 != 0
+    }
+
+    /// Get the section_length: the number of bytes after it
+    pub fn section_length(&self) -> u16 {
+        (self.bytes.len() - SECTION_HEADER_SIZE) as u16
+    }
+
+    /// Get the table_id_extension of a long-form section
+    pub fn table_id_extension(&self) -> Option<u16> {
+        self.long_header().map(|h| u16::from_be_bytes([h[3], h[4]]))
+    }
+
+    /// Get the version_number of a long-form section
+    pub fn version_number(&self) -> Option<u8> {
+        self.long_header().map(|h| (h[5] >> 1) & 0x1f)
+    }
+
+    /// Get the current_next_indicator of a long-form section
+    pub fn current_next_indicator(&self) -> Option<bool> {
+        self.long_header().map(|h| h[5] & 0x01 != 0)
+    }
+
+    /// Get the section_number of a long-form section
+    pub fn section_number(&self) -> Option<u8> {
+        self.long_header().map(|h| h[6])
+    }
+
+    /// Get the last_section_number of a long-form section
+    pub fn last_section_number(&self) -> Option<u8> {
+        self.long_header().map(|h| h[7])
+    }
+
+    /// Get the bytes between the header and the CRC_32, if any
+    pub fn data(&self) -> &'a [u8] {
+        if self.section_syntax_indicator() {
+            &self.bytes[LONG_SECTION_HEADER_SIZE..self.bytes.len() - CRC_SIZE]
+        } else {
+            &self.bytes[SECTION_HEADER_SIZE..]
+        }
+    }
+
+    /// Get the CRC_32 of a long-form section
+    pub fn crc(&self) -> Option<u32> {
+        self.long_header().map(|b| {
+            let end = b.len() - CRC_SIZE;
+            u32::from_be_bytes([b[end], b[end + 1], b[end + 2], b[end + 3]])
+        })
+    }
+
+    /// Check the CRC_32 of a long-form section; short-form sections have none
+    pub fn verify_crc(&self) -> Result<(), TsError> {
+        if self.section_syntax_indicator() {
+            verify_section(self.bytes)?;
+        }
+        Ok(())
+    }
+
+    fn long_header(&self) -> Option<&'a [u8]> {
+        if self.section_syntax_indicator() {
+            Some(self.bytes)
+        } else {
+            None
+        }
+    }
+}
+
+/// Reassembles PSI/SI sections from the packets of one PID.
+///
+/// Call `push` with each packet, then `next_section` until it returns `None`
+/// before pushing the next packet. Sections may span packets and several may
+/// share one packet. A partial section is discarded when the continuity
+/// counter shows lost packets.
+pub struct SectionAssembler {
+    pid: u16,
+    verify_crc: bool,
+    section: [u8; SECTION_MAX_SIZE],
+    len: usize,
+    in_section: bool,
+    pending: [u8; PAYLOAD_SIZE],
+    pending_pos: usize,
+    pending_len: usize,
+    start_at: Option<usize>,
+    last_cc: Option<u8>,
+    continuity_errors: u64,
+}
+
+impl SectionAssembler {
+    /// Make an assembler for the sections on `pid`
+    pub fn new(pid: u16) -> Self {
+        SectionAssembler {
+            pid,
+            verify_crc: false,
+            section: [0; SECTION_MAX_SIZE],
+            len: 0,
+            in_section: false,
+            pending: [0; PAYLOAD_SIZE],
+            pending_pos: 0,
+            pending_len: 0,
+            start_at: None,
+            last_cc: None,
+            continuity_errors: 0,
+        }
+    }
+
+    /// Check the CRC_32 of long-form sections, yielding an error for bad ones
+    pub fn verify_crc(mut self, verify: bool) -> Self {
+        self.verify_crc = verify;
+        self
+    }
+
+    /// Get the PID being assembled
+    pub fn pid(&self) -> u16 {
+        self.pid
+    }
+
+    /// Get the number of times packets were lost, discarding any partial section
+    pub fn continuity_errors(&self) -> u64 {
+        self.continuity_errors
+    }
+
+    /// Discard any partial section and wait for the next unit start
+    pub fn reset(&mut self) {
+        self.len = 0;
+        self.in_section = false;
+        self.pending_pos = 0;
+        self.pending_len = 0;
+        self.start_at = None;
+        self.last_cc = None;
+    }
+
+    /// Take the payload of the next packet. Packets on other PIDs, with the
+    /// transport error indicator set, or repeated are ignored.
+    pub fn push(&mut self, pkt: &Packet) -> Result<(), TsError> {
+        if packet::pid(pkt) != self.pid || packet::has_transport_error(pkt) {
+            return Ok(());
+        }
+        self.pending_pos = 0;
+        self.pending_len = 0;
+        self.start_at = None;
+        if !packet::has_payload(pkt) {
+            return Ok(());
+        }
+        let cc = packet::continuity_counter(pkt);
+        if let Some(last) = self.last_cc {
+            if cc == last {
+                return Ok(());
+            }
+            if cc != (last + 1) & 0x0f {
+                self.continuity_errors += 1;
+                self.len = 0;
+                self.in_section = false;
+            }
+        }
+        self.last_cc = Some(cc);
+
+        let pointer = packet::try_pointer_field(pkt)?;
+        let data = match packet::try_payload(pkt)? {
+            Some(data) => data,
+            None => return Ok(()),
+        };
+        let data = match pointer {
+            Some(pointer) => {
+                self.start_at = Some(pointer as usize);
+                &data[1..]
+            }
+            None => data,
+        };
+        self.pending[..data.len()].copy_from_slice(data);
+        self.pending_len = data.len();
+        Ok(())
+    }
+
+    /// Get the next complete section from the pushed packets
+    pub fn next_section(&mut self) -> Option<Result<&[u8], TsError>> {
+        loop {
+            if self.start_at == Some(self.pending_pos) {
+                self.start_at = None;
+                if self.in_section && self.len > 0 {
+                    let len = self.len;
+                    self.len = 0;
+                    return Some(Err(TsError::TruncatedSection(len)));
+                }
+                self.in_section = true;
+                self.len = 0;
+            }
+            let limit = self.start_at.unwrap_or(self.pending_len);
+            if self.pending_pos >= limit {
+                return None;
+            }
+            if !self.in_section {
+                self.pending_pos = limit;
+                continue;
+            }
+            if self.len == 0 && self.pending[self.pending_pos] == 0xff {
+                // stuffing fills the rest of the packet
+                self.in_section = false;
+                self.pending_pos = limit;
+                continue;
+            }
+            let total = if self.len < SECTION_HEADER_SIZE {
+                SECTION_HEADER_SIZE
+            } else {
+                section_total_length(&self.section)
+            };
+            let take = (total - self.len).min(limit - self.pending_pos);
+            self.section[self.len..self.len + take]
+                .copy_from_slice(&self.pending[self.pending_pos..self.pending_pos + take]);
+            self.len += take;
+            self.pending_pos += take;
+            if self.len == SECTION_HEADER_SIZE {
+                let total = section_total_length(&self.section);
+                if total > SECTION_MAX_SIZE {
+                    self.len = 0;
+                    self.in_section = false;
+                    return Some(Err(TsError::SectionTooLong(total)));
+                }
+            }
+            if self.len >= SECTION_HEADER_SIZE && self.len == section_total_length(&self.section) {
+                let len = self.len;
+                self.len = 0;
+                let section = &self.section[..len];
+                if self.verify_crc && section[1] & 0x80 != 0 {
+                    if let Err(e) = verify_section(section) {
+                        return Some(Err(e));
+                    }
+                }
+                return Some(Ok(section));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpeg::crc::finalize_section;
+    use crate::mpeg::packet::HEADER_SIZE;
+
+    const PID: u16 = 0x100;
+
+    /// Make a section of `len` bytes whose body counts up from `seed`
+    fn make_section(table_id: u8, len: usize, seed: u8) -> [u8; SECTION_MAX_SIZE] {
+        let mut section = [0u8; SECTION_MAX_SIZE];
+        section[0] = table_id;
+        let body = len - SECTION_HEADER_SIZE;
+        section[1] = 0xb0 | (body >> 8) as u8;
+        section[2] = body as u8;
+        for (i, byte) in section[SECTION_HEADER_SIZE..len].iter_mut().enumerate() {
+            *byte = seed.wrapping_add(i as u8);
+        }
+        section
+    }
+
+    fn make_packet(cc: u8, pointer: Option<u8>, data: &[u8]) -> Packet {
+        let mut pkt = packet::null_packet();
+        packet::set_pid(&mut pkt, PID);
+        packet::set_continuity_counter(&mut pkt, cc);
+        let mut at = HEADER_SIZE;
+        if let Some(pointer) = pointer {
+            packet::set_unit_start(&mut pkt);
+            pkt[at] = pointer;
+            at += 1;
+        }
+        pkt[at..at + data.len()].copy_from_slice(data);
+        pkt
+    }
+
+    /// Push packets, collecting the lengths and first bytes of the sections
+    fn run(assembler: &mut SectionAssembler, packets: &[Packet]) -> ([(usize, u8); 8], usize, usize) {
+        let mut found = [(0, 0); 8];
+        let mut count = 0;
+        let mut errors = 0;
+        for pkt in packets {
+            assembler.push(pkt).unwrap();
+            while let Some(result) = assembler.next_section() {
+                match result {
+                    Ok(section) => {
+                        found[count] = (section.len(), section[SECTION_HEADER_SIZE]);
+                        count += 1;
+                    }
+                    Err(_) => errors += 1,
+                }
+            }
+        }
+        (found, count, errors)
+    }
+
+    #[test]
+    fn test_section_view() {
+        let pat = [
+            0x00, 0xb0, 0x0d, 0x00, 0x01, 0xc1, 0x00, 0x00, 0x00, 0x01, 0xf0, 0x00, 0x2a, 0xb1, 0x04, 0xb2, 0xff,
+        ];
+        let section = Section::new(&pat).unwrap();
+        assert_eq!(section.as_bytes().len(), 16);
+        assert_eq!(section.table_id(), 0);
+        assert!(section.section_syntax_indicator());
+        assert!(!section.private_indicator());
+        assert_eq!(section.section_length(), 13);
+        assert_eq!(section.table_id_extension(), Some(1));
+        assert_eq!(section.version_number(), Some(0));
+        assert_eq!(section.current_next_indicator(), Some(true));
+        assert_eq!(section.section_number(), Some(0));
+        assert_eq!(section.last_section_number(), Some(0));
+        assert_eq!(section.data(), &[0x00, 0x01, 0xf0, 0x00]);
+        assert_eq!(section.crc(), Some(0x2ab1_04b2));
+        assert_eq!(section.verify_crc(), Ok(()));
+    }
+
+    #[test]
+    fn test_short_section_view() {
+        let tdt = [0x70, 0x70, 0x05, 0xe4, 0x2a, 0x12, 0x34, 0x56];
+        let section = Section::new(&tdt).unwrap();
+        assert!(!section.section_syntax_indicator());
+        assert!(section.private_indicator());
+        assert_eq!(section.table_id_extension(), None);
+        assert_eq!(section.crc(), None);
+        assert_eq!(section.data(), &tdt[3..]);
+        assert_eq!(section.verify_crc(), Ok(()));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let tdt = [0x70, 0x70, 0x05, 0xe4, 0x2a, 0x12, 0x34, 0x56, 0xff];
+        let json = serde_json::to_string(&Section::new(&tdt).unwrap()).unwrap();
+        assert_eq!(json, r#"{"bytes":[112,112,5,228,42,18,52,86]}"#);
+    }
+
+    #[test]
+    fn test_section_view_lengths() {
+        assert_eq!(Section::new(&[0x00, 0xb0]), Err(TsError::BadLength(2)));
+        assert_eq!(Section::new(&[0x00, 0xb0, 0x0d, 0x00]), Err(TsError::BadLength(4)));
+        assert_eq!(Section::new(&[0x00, 0xb0, 0x03, 0, 0, 0]), Err(TsError::BadLength(6)));
+        let long = [0x80, 0x7f, 0xff];
+        assert_eq!(Section::new(&long), Err(TsError::SectionTooLong(SECTION_MAX_SIZE + 2)));
+    }
+
+    #[test]
+    fn test_single_packet() {
+        let section = make_section(0x00, 20, 7);
+        let mut assembler = SectionAssembler::new(PID);
+        let (found, count, errors) = run(&mut assembler, &[make_packet(0, Some(0), &section[..20])]);
+        assert_eq!((count, errors), (1, 0));
+        assert_eq!(found[0], (20, 7));
+    }
+
+    #[test]
+    fn test_spanning_packets() {
+        let section = make_section(0x02, 400, 1);
+        let first = PAYLOAD_SIZE - 1;
+        let second = first + PAYLOAD_SIZE;
+        let packets = [
+            make_packet(5, Some(0), &section[..first]),
+            make_packet(6, None, &section[first..second]),
+            make_packet(7, None, &section[second..400]),
+        ];
+        let mut assembler = SectionAssembler::new(PID);
+        assembler.push(&packets[0]).unwrap();
+        assert!(assembler.next_section().is_none());
+        assembler.push(&packets[1]).unwrap();
+        assert!(assembler.next_section().is_none());
+        assembler.push(&packets[2]).unwrap();
+        assert_eq!(assembler.next_section(), Some(Ok(&section[..400])));
+        assert!(assembler.next_section().is_none());
+    }
+
+    #[test]
+    fn test_several_per_packet_with_stuffing() {
+        let a = make_section(0x42, 30, 10);
+        let b = make_section(0x46, 40, 20);
+        let mut data = [0u8; 70];
+        data[..30].copy_from_slice(&a[..30]);
+        data[30..].copy_from_slice(&b[..40]);
+        let mut assembler = SectionAssembler::new(PID);
+        let (found, count, errors) = run(&mut assembler, &[make_packet(0, Some(0), &data)]);
+        assert_eq!((count, errors), (2, 0));
+        assert_eq!(found[0], (30, 10));
+        assert_eq!(found[1], (40, 20));
+    }
+
+    #[test]
+    fn test_pointer_field_completes_previous() {
+        let a = make_section(0x42, 200, 1);
+        let b = make_section(0x42, 50, 2);
+        let tail = 200 - (PAYLOAD_SIZE - 1);
+        let mut data = [0u8; PAYLOAD_SIZE - 1];
+        data[..tail].copy_from_slice(&a[PAYLOAD_SIZE - 1..200]);
+        data[tail..tail + 50].copy_from_slice(&b[..50]);
+        data[tail + 50..].iter_mut().for_each(|byte| *byte = 0xff);
+        let packets = [
+            make_packet(0, Some(0), &a[..PAYLOAD_SIZE - 1]),
+            make_packet(1, Some(tail as u8), &data[..PAYLOAD_SIZE - 1]),
+        ];
+        let mut assembler = SectionAssembler::new(PID);
+        let (found, count, errors) = run(&mut assembler, &packets);
+        assert_eq!((count, errors), (2, 0));
+        assert_eq!(found[0], (200, 1));
+        assert_eq!(found[1], (50, 2));
+    }
+
+    #[test]
+    fn test_header_split_across_packets() {
+        let a = make_section(0x42, PAYLOAD_SIZE - 2, 1);
+        let b = make_section(0x42, 20, 9);
+        let mut data = [0xffu8; PAYLOAD_SIZE - 1];
+        data[..PAYLOAD_SIZE - 2].copy_from_slice(&a[..PAYLOAD_SIZE - 2]);
+        data[PAYLOAD_SIZE - 2] = b[0];
+        let packets = [make_packet(0, Some(0), &data), make_packet(1, None, &b[1..20])];
+        let mut assembler = SectionAssembler::new(PID);
+        let (found, count, errors) = run(&mut assembler, &packets);
+        assert_eq!((count, errors), (2, 0));
+        assert_eq!(found[1], (20, 9));
+    }
+
+    #[test]
+    fn test_continuity_error_discards() {
+        let a = make_section(0x02, 300, 1);
+        let b = make_section(0x02, 20, 5);
+        let packets = [
+            make_packet(0, Some(0), &a[..PAYLOAD_SIZE - 1]),
+            make_packet(2, None, &a[PAYLOAD_SIZE - 1..300]),
+            make_packet(3, Some(0), &b[..20]),
+        ];
+        let mut assembler = SectionAssembler::new(PID);
+        let (found, count, errors) = run(&mut assembler, &packets);
+        assert_eq!((count, errors), (1, 0));
+        assert_eq!(found[0], (20, 5));
+        assert_eq!(assembler.continuity_errors(), 1);
+    }
+
+    #[test]
+    fn test_duplicate_and_other_pid_ignored() {
+        let a = make_section(0x02, 300, 1);
+        let mut other = make_packet(1, None, &[0; 10]);
+        packet::set_pid(&mut other, PID + 1);
+        let packets = [
+            make_packet(0, Some(0), &a[..PAYLOAD_SIZE - 1]),
+            make_packet(0, Some(0), &a[..PAYLOAD_SIZE - 1]),
+            other,
+            make_packet(1, None, &a[PAYLOAD_SIZE - 1..300]),
+        ];
+        let mut assembler = SectionAssembler::new(PID);
+        let (found, count, _) = run(&mut assembler, &packets);
+        assert_eq!(count, 1);
+        assert_eq!(found[0], (300, 1));
+        assert_eq!(assembler.continuity_errors(), 0);
+    }
+
+    #[test]
+    fn test_truncated_and_too_long() {
+        let a = make_section(0x02, 300, 1);
+        let mut long = [0xffu8; 3];
+        long[0] = 0x40;
+        long[1] = 0x0f;
+        let packets = [
+            make_packet(0, Some(0), &a[..PAYLOAD_SIZE - 1]),
+            make_packet(1, Some(0), &long),
+        ];
+        let mut assembler = SectionAssembler::new(PID);
+        assembler.push(&packets[0]).unwrap();
+        assert!(assembler.next_section().is_none());
+        assembler.push(&packets[1]).unwrap();
+        assert_eq!(assembler.next_section(), Some(Err(TsError::TruncatedSection(PAYLOAD_SIZE - 1))));
+        assert_eq!(assembler.next_section(), Some(Err(TsError::SectionTooLong(4098))));
+        assert!(assembler.next_section().is_none());
+    }
+
+    #[test]
+    fn test_verify_crc() {
+        let mut a = make_section(0x02, 40, 1);
+        finalize_section(&mut a[..40]).unwrap();
+        let mut b = a;
+        b[10] ^= 0xff;
+        let mut short = make_section(0x70, 8, 1);
+        short[1] &= 0x7f;
+        let mut data = [0u8; 88];
+        data[..40].copy_from_slice(&a[..40]);
+        data[40..80].copy_from_slice(&b[..40]);
+        data[80..].copy_from_slice(&short[..8]);
+        let mut assembler = SectionAssembler::new(PID).verify_crc(true);
+        assembler.push(&make_packet(0, Some(0), &data)).unwrap();
+        assert_eq!(assembler.next_section(), Some(Ok(&a[..40])));
+        assert!(matches!(assembler.next_section(), Some(Err(TsError::BadCrc { .. }))));
+        assert_eq!(assembler.next_section(), Some(Ok(&short[..8])));
+        assert!(assembler.next_section().is_none());
+    }
+
+    #[test]
+    fn test_waits_for_unit_start() {
+        let a = make_section(0x02, 20, 1);
+        let packets = [make_packet(0, None, &a[..20]), make_packet(1, Some(0), &a[..20])];
+        let mut assembler = SectionAssembler::new(PID);
+        let (_, count, errors) = run(&mut assembler, &packets);
+        assert_eq!((count, errors), (1, 0));
+    }
+}
@@ -0,0 +1,205 @@
+use super::crc::{finalize_section, CRC_SIZE};
+use super::packet::SECTION_MAX_SIZE;
+use super::section::{LONG_SECTION_HEADER_SIZE, SECTION_HEADER_SIZE};
+use crate::error::TsError;
+
+/// A buffer big enough for any section
+pub type SectionBuffer = [u8; SECTION_MAX_SIZE];
+
+/// Composes a PSI/SI section from its header fields and data, filling in
+/// section_length and, for long-form sections, the CRC_32.
+#[derive(Clone, Copy, Debug)]
+pub struct SectionBuilder<'a> {
+    table_id: u8,
+    long: bool,
+    private_indicator: bool,
+    table_id_extension: u16,
+    version_number: u8,
+    current_next_indicator: bool,
+    section_number: u8,
+    last_section_number: u8,
+    data: &'a [u8],
+}
+
+impl<'a> SectionBuilder<'a> {
+    /// Start a long-form section with the given table_id, current and
+    /// numbered 0 of 0
+    pub fn new(table_id: u8) -> Self {
+        SectionBuilder {
+            table_id,
+            long: true,
+            private_indicator: false,
+            table_id_extension: 0,
+            version_number: 0,
+            current_next_indicator: true,
+            section_number: 0,
+            last_section_number: 0,
+            data: &[],
+        }
+    }
+
+    /// Make a short-form section, with no extended header or CRC_32
+    pub fn short_form(mut self) -> Self {
+        self.long = false;
+        self
+    }
+
+    /// Set the table_id
+    pub fn table_id(mut self, table_id: u8) -> Self {
+        self.table_id = table_id;
+        self
+    }
+
+    /// Set the private_indicator
+    pub fn private_indicator(mut self) -> Self {
+        self.private_indicator = true;
+        self
+    }
+
+    /// Set the table_id_extension
+    pub fn table_id_extension(mut self, extension: u16) -> Self {
+        self.table_id_extension = extension;
+        self
+    }
+
+    /// Set the version_number. Max: 31
+    pub fn version_number(mut self, version: u8) -> Self {
+        self.version_number = version;
+        self
+    }
+
+    /// Set the current_next_indicator
+    pub fn current_next_indicator(mut self, current: bool) -> Self {
+        self.current_next_indicator = current;
+        self
+    }
+
+    /// Set the section_number
+    pub fn section_number(mut self, number: u8) -> Self {
+        self.section_number = number;
+        self
+    }
+
+    /// Set the last_section_number
+    pub fn last_section_number(mut self, number: u8) -> Self {
+        self.last_section_number = number;
+        self
+    }
+
+    /// Set the bytes between the header and the CRC_32
+    pub fn data(mut self, data: &'a [u8]) -> Self {
+        self.data = data;
+        self
+    }
+
+    /// Get the total size of the section
+    pub fn size(&self) -> usize {
+        if self.long {
+            LONG_SECTION_HEADER_SIZE + self.data.len() + CRC_SIZE
+        } else {
+            SECTION_HEADER_SIZE + self.data.len()
+        }
+    }
+
+    /// Build the section, returning it with its length
+    pub fn build(&self) -> Result<(SectionBuffer, usize), TsError> {
+        let mut section = [0xff; SECTION_MAX_SIZE];
+        let len = self.write(&mut section)?;
+        Ok((section, len))
+    }
+
+    /// Write the section to the start of `out`, returning its length
+    pub fn write(&self, out: &mut [u8]) -> Result<usize, TsError> {
+        let size = self.size();
+        if size > SECTION_MAX_SIZE {
+            return Err(TsError::SectionTooLong(size));
+        }
+        if size > out.len() {
+            return Err(TsError::BadLength(out.len()));
+        }
+        let section = &mut out[..size];
+        let section_length = size - SECTION_HEADER_SIZE;
+        section[0] = self.table_id;
+        section[1] = ((self.long as u8) << 7)
+            | ((self.private_indicator as u8) << 6)
+            | 0x30
+            | (section_length >> 8) as u8;
+        section[2] = section_length as u8;
+        if !self.long {
+            section[SECTION_HEADER_SIZE..].copy_from_slice(self.data);
+            return Ok(size);
+        }
+        section[3..5].copy_from_slice(&self.table_id_extension.to_be_bytes());
+        section[5] = 0xc0 | ((self.version_number & 0x1f) << 1) | self.current_next_indicator as u8;
+        section[6] = self.section_number;
+        section[7] = self.last_section_number;
+        section[LONG_SECTION_HEADER_SIZE..size - CRC_SIZE].copy_from_slice(self.data);
+        finalize_section(section)?;
+        Ok(size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpeg::section::Section;
+
+    #[test]
+    fn test_long_form() {
+        let (section, len) = SectionBuilder::new(0x00)
+            .table_id_extension(1)
+            .data(&[0x00, 0x01, 0xf0, 0x00])
+            .build()
+            .unwrap();
+        assert_eq!(
+            &section[..len],
+            &[0x00, 0xb0, 0x0d, 0x00, 0x01, 0xc1, 0x00, 0x00, 0x00, 0x01, 0xf0, 0x00, 0x2a, 0xb1, 0x04, 0xb2]
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let data = [1, 2, 3, 4, 5];
+        let builder = SectionBuilder::new(0x42)
+            .private_indicator()
+            .table_id_extension(0xbeef)
+            .version_number(17)
+            .current_next_indicator(false)
+            .section_number(2)
+            .last_section_number(3)
+            .data(&data);
+        let mut out = [0u8; 64];
+        let len = builder.write(&mut out).unwrap();
+        assert_eq!(len, builder.size());
+        let section = Section::new(&out).unwrap();
+        assert_eq!(section.as_bytes().len(), len);
+        assert_eq!(section.table_id(), 0x42);
+        assert!(section.private_indicator());
+        assert_eq!(section.table_id_extension(), Some(0xbeef));
+        assert_eq!(section.version_number(), Some(17));
+        assert_eq!(section.current_next_indicator(), Some(false));
+        assert_eq!(section.section_number(), Some(2));
+        assert_eq!(section.last_section_number(), Some(3));
+        assert_eq!(section.data(), &data);
+        assert_eq!(section.verify_crc(), Ok(()));
+    }
+
+    #[test]
+    fn test_short_form() {
+        let (section, len) = SectionBuilder::new(0x70)
+            .short_form()
+            .data(&[0xe4, 0x2a, 0x12, 0x34, 0x56])
+            .build()
+            .unwrap();
+        assert_eq!(&section[..len], &[0x70, 0x30, 0x05, 0xe4, 0x2a, 0x12, 0x34, 0x56]);
+    }
+
+    #[test]
+    fn test_too_long() {
+        let data = [0u8; SECTION_MAX_SIZE];
+        let builder = SectionBuilder::new(0x80).data(&data[..SECTION_MAX_SIZE - 11]);
+        assert_eq!(builder.build().err(), Some(TsError::SectionTooLong(SECTION_MAX_SIZE + 1)));
+        let builder = SectionBuilder::new(0x80).data(&data[..4]);
+        assert_eq!(builder.write(&mut [0u8; 10]), Err(TsError::BadLength(10)));
+    }
+}
@@ -0,0 +1,427 @@
+use core::fmt;
+
+/// The stream_type of an elementary stream in a PMT, from ISO 13818-1
+/// table 2-34 plus common user private assignments.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "u8", into = "u8"))]
+pub enum StreamType {
+    /// ISO 11172-2 video (0x01)
+    Mpeg1Video,
+    /// ISO 13818-2 video (0x02)
+    Mpeg2Video,
+    /// ISO 11172-3 audio (0x03)
+    Mpeg1Audio,
+    /// ISO 13818-3 audio (0x04)
+    Mpeg2Audio,
+    /// ISO 13818-1 private_sections (0x05)
+    PrivateSections,
+    /// ISO 13818-1 PES packets with private data (0x06)
+    PrivateData,
+    /// ISO 13522 MHEG (0x07)
+    Mheg,
+    /// ISO 13818-1 Annex A DSM-CC (0x08)
+    DsmCc,
+    /// ITU-T H.222.1 (0x09)
+    H2221,
+    /// ISO 13818-6 type A, multi-protocol encapsulation (0x0A)
+    DsmCcTypeA,
+    /// ISO 13818-6 type B, U-N messages (0x0B)
+    DsmCcTypeB,
+    /// ISO 13818-6 type C, stream descriptors (0x0C)
+    DsmCcTypeC,
+    /// ISO 13818-6 type D, sections (0x0D)
+    DsmCcTypeD,
+    /// ISO 13818-1 auxiliary (0x0E)
+    Auxiliary,
+    /// ISO 13818-7 AAC audio with ADTS transport syntax (0x0F)
+    AacAdts,
+    /// ISO 14496-2 visual (0x10)
+    Mpeg4Visual,
+    /// ISO 14496-3 AAC audio with LATM transport syntax (0x11)
+    AacLatm,
+    /// ISO 14496-1 SL-packetized stream or FlexMux in PES packets (0x12)
+    SlPes,
+    /// ISO 14496-1 SL-packetized stream or FlexMux in sections (0x13)
+    SlSections,
+    /// ISO 13818-6 synchronized download protocol (0x14)
+    SynchronizedDownload,
+    /// Metadata in PES packets (0x15)
+    MetadataPes,
+    /// Metadata in metadata_sections (0x16)
+    MetadataSections,
+    /// Metadata in an ISO 13818-6 data carousel (0x17)
+    MetadataDataCarousel,
+    /// Metadata in an ISO 13818-6 object carousel (0x18)
+    MetadataObjectCarousel,
+    /// Metadata in an ISO 13818-6 synchronized download protocol (0x19)
+    MetadataSynchronizedDownload,
+    /// ISO 13818-11 IPMP stream (0x1A)
+    Mpeg2Ipmp,
+    /// ITU-T H.264 / ISO 14496-10 AVC video (0x1B)
+    Avc,
+    /// ISO 14496-3 audio without additional transport syntax (0x1C)
+    Mpeg4AudioRaw,
+    /// ISO 14496-17 text (0x1D)
+    Mpeg4Text,
+    /// ISO 23002-3 auxiliary video (0x1E)
+    AuxiliaryVideo,
+    /// AVC SVC sub-bitstream (0x1F)
+    Svc,
+    /// AVC MVC sub-bitstream (0x20)
+    Mvc,
+    /// ITU-T T.800 / ISO 15444 JPEG 2000 video (0x21)
+    Jpeg2000,
+    /// ISO 13818-2 additional view for stereoscopic video (0x22)
+    Mpeg2StereoView,
+    /// AVC additional view for stereoscopic video (0x23)
+    AvcStereoView,
+    /// ITU-T H.265 / ISO 23008-2 HEVC video (0x24)
+    Hevc,
+    /// HEVC temporal video subset (0x25)
+    HevcTemporalSubset,
+    /// AVC MVCD sub-bitstream (0x26)
+    Mvcd,
+    /// Timeline and external media information (0x27)
+    Temi,
+    /// HEVC enhancement sub-partition, Annex G (0x28)
+    HevcEnhancementG,
+    /// HEVC temporal enhancement sub-partition, Annex G (0x29)
+    HevcTemporalEnhancementG,
+    /// HEVC enhancement sub-partition, Annex H (0x2A)
+    HevcEnhancementH,
+    /// HEVC temporal enhancement sub-partition, Annex H (0x2B)
+    HevcTemporalEnhancementH,
+    /// Green access units (0x2C)
+    GreenAccessUnits,
+    /// ISO 23008-3 MPEG-H 3D audio, main stream (0x2D)
+    MpegHAudioMain,
+    /// ISO 23008-3 MPEG-H 3D audio, auxiliary stream (0x2E)
+    MpegHAudioAuxiliary,
+    /// Quality access units (0x2F)
+    QualityAccessUnits,
+    /// Media orchestration access units (0x30)
+    MediaOrchestration,
+    /// HEVC motion constrained tile set substream (0x31)
+    HevcTileSubstream,
+    /// ISO 21122 JPEG XS video (0x32)
+    JpegXs,
+    /// ITU-T H.266 / ISO 23090-3 VVC video (0x33)
+    Vvc,
+    /// VVC temporal video subset (0x34)
+    VvcTemporalSubset,
+    /// ISO 23094-1 EVC video (0x35)
+    Evc,
+    /// ISO 13818-11 IPMP stream (0x7F)
+    Ipmp,
+    /// ATSC A/52 AC-3 audio (0x81)
+    Ac3,
+    /// SCTE-35 splice information (0x86)
+    Scte35,
+    /// ATSC A/52 E-AC-3 audio (0x87)
+    Eac3,
+    /// Reserved by ISO 13818-1
+    Reserved(u8),
+    /// User private (0x80 - 0xFF) without a common assignment
+    UserPrivate(u8),
+}
+
+impl StreamType {
+    /// Get a short human readable name, such as "H.264 video"
+    pub fn name(self) -> &'static str {
+        match self {
+            StreamType::Mpeg1Video => "MPEG-1 video",
+            StreamType::Mpeg2Video => "MPEG-2 video",
+            StreamType::Mpeg1Audio => "MPEG-1 audio",
+            StreamType::Mpeg2Audio => "MPEG-2 audio",
+            StreamType::PrivateSections => "private sections",
+            StreamType::PrivateData => "private data",
+            StreamType::Mheg => "MHEG",
+            StreamType::DsmCc => "DSM-CC",
+            StreamType::H2221 => "H.222.1",
+            StreamType::DsmCcTypeA => "DSM-CC multi-protocol encapsulation",
+            StreamType::DsmCcTypeB => "DSM-CC U-N messages",
+            StreamType::DsmCcTypeC => "DSM-CC stream descriptors",
+            StreamType::DsmCcTypeD => "DSM-CC sections",
+            StreamType::Auxiliary => "auxiliary",
+            StreamType::AacAdts => "AAC audio",
+            StreamType::Mpeg4Visual => "MPEG-4 video",
+            StreamType::AacLatm => "AAC LATM audio",
+            StreamType::SlPes => "MPEG-4 SL PES",
+            StreamType::SlSections => "MPEG-4 SL sections",
+            StreamType::SynchronizedDownload => "DSM-CC synchronized download",
+            StreamType::MetadataPes => "metadata PES",
+            StreamType::MetadataSections => "metadata sections",
+            StreamType::MetadataDataCarousel => "metadata data carousel",
+            StreamType::MetadataObjectCarousel => "metadata object carousel",
+            StreamType::MetadataSynchronizedDownload => "metadata synchronized download",
+            StreamType::Mpeg2Ipmp => "MPEG-2 IPMP",
+            StreamType::Avc => "H.264 video",
+            StreamType::Mpeg4AudioRaw => "MPEG-4 audio",
+            StreamType::Mpeg4Text => "MPEG-4 text",
+            StreamType::AuxiliaryVideo => "auxiliary video",
+            StreamType::Svc => "H.264 SVC video",
+            StreamType::Mvc => "H.264 MVC video",
+            StreamType::Jpeg2000 => "JPEG 2000 video",
+            StreamType::Mpeg2StereoView => "MPEG-2 stereo view",
+            StreamType::AvcStereoView => "H.264 stereo view",
+            StreamType::Hevc => "H.265 video",
+            StreamType::HevcTemporalSubset => "H.265 temporal subset",
+            StreamType::Mvcd => "H.264 MVCD video",
+            StreamType::Temi => "TEMI",
+            StreamType::HevcEnhancementG => "H.265 enhancement (G)",
+            StreamType::HevcTemporalEnhancementG => "H.265 temporal enhancement (G)",
+            StreamType::HevcEnhancementH => "H.265 enhancement (H)",
+            StreamType::HevcTemporalEnhancementH => "H.265 temporal enhancement (H)",
+            StreamType::GreenAccessUnits => "green access units",
+            StreamType::MpegHAudioMain => "MPEG-H 3D audio",
+            StreamType::MpegHAudioAuxiliary => "MPEG-H 3D auxiliary audio",
+            StreamType::QualityAccessUnits => "quality access units",
+            StreamType::MediaOrchestration => "media orchestration",
+            StreamType::HevcTileSubstream => "H.265 tile substream",
+            StreamType::JpegXs => "JPEG XS video",
+            StreamType::Vvc => "H.266 video",
+            StreamType::VvcTemporalSubset => "H.266 temporal subset",
+            StreamType::Evc => "EVC video",
+            StreamType::Ipmp => "IPMP",
+            StreamType::Ac3 => "AC-3 audio",
+            StreamType::Scte35 => "SCTE-35",
+            StreamType::Eac3 => "E-AC-3 audio",
+            StreamType::Reserved(_) => "reserved",
+            StreamType::UserPrivate(_) => "user private",
+        }
+    }
+
+    /// Does the stream carry video?
+    pub fn is_video(self) -> bool {
+        matches!(
+            self,
+            StreamType::Mpeg1Video
+                | StreamType::Mpeg2Video
+                | StreamType::Mpeg4Visual
+                | StreamType::Avc
+                | StreamType::AuxiliaryVideo
+                | StreamType::Svc
+                | StreamType::Mvc
+                | StreamType::Jpeg2000
+                | StreamType::Mpeg2StereoView
+                | StreamType::AvcStereoView
+                | StreamType::Hevc
+                | StreamType::HevcTemporalSubset
+                | StreamType::Mvcd
+                | StreamType::HevcEnhancementG
+                | StreamType::HevcTemporalEnhancementG
+                | StreamType::HevcEnhancementH
+                | StreamType::HevcTemporalEnhancementH
+                | StreamType::HevcTileSubstream
+                | StreamType::JpegXs
+                | StreamType::Vvc
+                | StreamType::VvcTemporalSubset
+                | StreamType::Evc
+        )
+    }
+
+    /// Does the stream carry audio?
+    pub fn is_audio(self) -> bool {
+        matches!(
+            self,
+            StreamType::Mpeg1Audio
+                | StreamType::Mpeg2Audio
+                | StreamType::AacAdts
+                | StreamType::AacLatm
+                | StreamType::Mpeg4AudioRaw
+                | StreamType::MpegHAudioMain
+                | StreamType::MpegHAudioAuxiliary
+                | StreamType::Ac3
+                | StreamType::Eac3
+        )
+    }
+}
+
+impl From<u8> for StreamType {
+    fn from(value: u8) -> Self {
+        match value {
+            0x01 => StreamType::Mpeg1Video,
+            0x02 => StreamType::Mpeg2Video,
+            0x03 => StreamType::Mpeg1Audio,
+            0x04 => StreamType::Mpeg2Audio,
+            0x05 => StreamType::PrivateSections,
+            0x06 => StreamType::PrivateData,
+            0x07 => StreamType::Mheg,
+            0x08 => StreamType::DsmCc,
+            0x09 => StreamType::H2221,
+            0x0a => StreamType::DsmCcTypeA,
+            0x0b => StreamType::DsmCcTypeB,
+            0x0c => StreamType::DsmCcTypeC,
+            0x0d => StreamType::DsmCcTypeD,
+            0x0e => StreamType::Auxiliary,
+            0x0f => StreamType::AacAdts,
+            0x10 => StreamType::Mpeg4Visual,
+            0x11 => StreamType::AacLatm,
+            0x12 => StreamType::SlPes,
+            0x13 => StreamType::SlSections,
+            0x14 => StreamType::SynchronizedDownload,
+            0x15 => StreamType::MetadataPes,
+            0x16 => StreamType::MetadataSections,
+            0x17 => StreamType::MetadataDataCarousel,
+            0x18 => StreamType::MetadataObjectCarousel,
+            0x19 => StreamType::MetadataSynchronizedDownload,
+            0x1a => StreamType::Mpeg2Ipmp,
+            0x1b => StreamType::Avc,
+            0x1c => StreamType::Mpeg4AudioRaw,
+            0x1d => StreamType::Mpeg4Text,
+            0x1e => StreamType::AuxiliaryVideo,
+            0x1f => StreamType::Svc,
+            0x20 => StreamType::Mvc,
+            0x21 => StreamType::Jpeg2000,
+            0x22 => StreamType::Mpeg2StereoView,
+            0x23 => StreamType::AvcStereoView,
+            0x24 => StreamType::Hevc,
+            0x25 => StreamType::HevcTemporalSubset,
+            0x26 => StreamType::Mvcd,
+            0x27 => StreamType::Temi,
+            0x28 => StreamType::HevcEnhancementG,
+            0x29 => StreamType::HevcTemporalEnhancementG,
+            0x2a => StreamType::HevcEnhancementH,
+            0x2b => StreamType::HevcTemporalEnhancementH,
+            0x2c => StreamType::GreenAccessUnits,
+            0x2d => StreamType::MpegHAudioMain,
+            0x2e => StreamType::MpegHAudioAuxiliary,
+            0x2f => StreamType::QualityAccessUnits,
+            0x30 => StreamType::MediaOrchestration,
+            0x31 => StreamType::HevcTileSubstream,
+            0x32 => StreamType::JpegXs,
+            0x33 => StreamType::Vvc,
+            0x34 => StreamType::VvcTemporalSubset,
+            0x35 => StreamType::Evc,
+            0x7f => StreamType::Ipmp,
+            0x81 => StreamType::Ac3,
+            0x86 => StreamType::Scte35,
+            0x87 => StreamType::Eac3,
+            0x80..=0xff => StreamType::UserPrivate(value),
+            _ => StreamType::Reserved(value),
+        }
+    }
+}
+
+impl From<StreamType> for u8 {
+    fn from(value: StreamType) -> Self {
+        match value {
+            StreamType::Mpeg1Video => 0x01,
+            StreamType::Mpeg2Video => 0x02,
+            StreamType::Mpeg1Audio => 0x03,
+            StreamType::Mpeg2Audio => 0x04,
+            StreamType::PrivateSections => 0x05,
+            StreamType::PrivateData => 0x06,
+            StreamType::Mheg => 0x07,
+            StreamType::DsmCc => 0x08,
+            StreamType::H2221 => 0x09,
+            StreamType::DsmCcTypeA => 0x0a,
+            StreamType::DsmCcTypeB => 0x0b,
+            StreamType::DsmCcTypeC => 0x0c,
+            StreamType::DsmCcTypeD => 0x0d,
+            StreamType::Auxiliary => 0x0e,
+            StreamType::AacAdts => 0x0f,
+            StreamType::Mpeg4Visual => 0x10,
+            StreamType::AacLatm => 0x11,
+            StreamType::SlPes => 0x12,
+            StreamType::SlSections => 0x13,
+            StreamType::SynchronizedDownload => 0x14,
+            StreamType::MetadataPes => 0x15,
+            StreamType::MetadataSections => 0x16,
+            StreamType::MetadataDataCarousel => 0x17,
+            StreamType::MetadataObjectCarousel => 0x18,
+            StreamType::MetadataSynchronizedDownload => 0x19,
+            StreamType::Mpeg2Ipmp => 0x1a,
+            StreamType::Avc => 0x1b,
+            StreamType::Mpeg4AudioRaw => 0x1c,
+            StreamType::Mpeg4Text => 0x1d,
+            StreamType::AuxiliaryVideo => 0x1e,
+            StreamType::Svc => 0x1f,
+            StreamType::Mvc => 0x20,
+            StreamType::Jpeg2000 => 0x21,
+            StreamType::Mpeg2StereoView => 0x22,
+            StreamType::AvcStereoView => 0x23,
+            StreamType::Hevc => 0x24,
+            StreamType::HevcTemporalSubset => 0x25,
+            StreamType::Mvcd => 0x26,
+            StreamType::Temi => 0x27,
+            StreamType::HevcEnhancementG => 0x28,
+            StreamType::HevcTemporalEnhancementG => 0x29,
+            StreamType::HevcEnhancementH => 0x2a,
+            StreamType::HevcTemporalEnhancementH => 0x2b,
+            StreamType::GreenAccessUnits => 0x2c,
+            StreamType::MpegHAudioMain => 0x2d,
+            StreamType::MpegHAudioAuxiliary => 0x2e,
+            StreamType::QualityAccessUnits => 0x2f,
+            StreamType::MediaOrchestration => 0x30,
+            StreamType::HevcTileSubstream => 0x31,
+            StreamType::JpegXs => 0x32,
+            StreamType::Vvc => 0x33,
+            StreamType::VvcTemporalSubset => 0x34,
+            StreamType::Evc => 0x35,
+            StreamType::Ipmp => 0x7f,
+            StreamType::Ac3 => 0x81,
+            StreamType::Scte35 => 0x86,
+            StreamType::Eac3 => 0x87,
+            StreamType::Reserved(value) | StreamType::UserPrivate(value) => value,
+        }
+    }
+}
+
+impl fmt::Display for StreamType {
+    /// Write the name, with the number for reserved and user private types
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StreamType::Reserved(value) | StreamType::UserPrivate(value) => {
+                write!(f, "{} 0x{:02x}", self.name(), value)
+            }
+            _ => f.write_str(self.name()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::format;
+
+    #[test]
+    fn test_round_trip() {
+        for value in 0..=255u8 {
+            assert_eq!(u8::from(StreamType::from(value)), value);
+        }
+    }
+
+    #[test]
+    fn test_common_types() {
+        assert_eq!(StreamType::from(0x1b), StreamType::Avc);
+        assert_eq!(StreamType::from(0x24), StreamType::Hevc);
+        assert_eq!(StreamType::from(0x33), StreamType::Vvc);
+        assert_eq!(StreamType::from(0x0f), StreamType::AacAdts);
+        assert_eq!(StreamType::from(0x11), StreamType::AacLatm);
+        assert_eq!(StreamType::from(0x06), StreamType::PrivateData);
+        assert_eq!(StreamType::from(0x86), StreamType::Scte35);
+        assert_eq!(StreamType::from(0x00), StreamType::Reserved(0x00));
+        assert_eq!(StreamType::from(0x36), StreamType::Reserved(0x36));
+        assert_eq!(StreamType::from(0x90), StreamType::UserPrivate(0x90));
+    }
+
+    #[test]
+    fn test_classify() {
+        assert!(StreamType::Avc.is_video());
+        assert!(StreamType::Vvc.is_video());
+        assert!(!StreamType::Avc.is_audio());
+        assert!(StreamType::AacLatm.is_audio());
+        assert!(!StreamType::Scte35.is_video());
+        assert!(!StreamType::Scte35.is_audio());
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(format!("{}", StreamType::Avc), "H.264 video");
+        assert_eq!(format!("{}", StreamType::Scte35), "SCTE-35");
+        assert_eq!(format!("{}", StreamType::Reserved(0x40)), "reserved 0x40");
+        assert_eq!(format!("{}", StreamType::UserPrivate(0xc0)), "user private 0xc0");
+    }
+}
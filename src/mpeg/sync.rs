@@ -0,0 +1,307 @@
+use super::format::PacketFormat;
+use super::packet::SYNC_BYTE;
+
+pub const DEFAULT_LOCK_COUNT: usize = 3;
+pub const DEFAULT_MAX_MISSES: usize = 2;
+
+const ALL_FORMATS: [PacketFormat; 3] = [PacketFormat::Ts, PacketFormat::Rs, PacketFormat::M2ts];
+
+/// What the synchroniser found at the start of the data it was given.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum SyncEvent<'a> {
+    /// More data is needed before anything can be decided
+    NeedMore,
+    /// Bytes were discarded while searching for packet boundaries
+    Skipped(usize),
+    /// Enough consecutive sync bytes were seen to lock onto a packet format
+    Locked(PacketFormat),
+    /// A packet in the locked format, including any TP_extra_header or parity
+    Packet(&'a [u8]),
+    /// A packet slot whose sync byte was missing, within the miss tolerance
+    Missed(&'a [u8]),
+    /// Too many sync bytes were missed and the lock was dropped
+    Lost,
+}
+
+/// Finds packet boundaries in an unaligned byte stream.
+///
+/// Call `next` with the unconsumed data, advance past the number of bytes it
+/// returns, and repeat. On `NeedMore`, append more data before calling again.
+#[derive(Clone, Copy, Debug)]
+pub struct Synchroniser {
+    lock_count: usize,
+    max_misses: usize,
+    format: Option<PacketFormat>,
+    locked: Option<PacketFormat>,
+    misses: usize,
+    skipped_bytes: u64,
+    sync_losses: u64,
+}
+
+impl Synchroniser {
+    /// Lock after `lock_count` consecutive sync bytes and drop the lock after
+    /// more than `max_misses` consecutive missing ones
+    pub fn new(lock_count: usize, max_misses: usize) -> Self {
+        Synchroniser {
+            lock_count: lock_count.max(1),
+            max_misses,
+            format: None,
+            locked: None,
+            misses: 0,
+            skipped_bytes: 0,
+            sync_losses: 0,
+        }
+    }
+
+    /// Only look for packets of the given format
+    pub fn with_format(mut self, format: PacketFormat) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    /// Get the format currently locked onto, if any
+    pub fn locked_format(&self) -> Option<PacketFormat> {
+        self.locked
+    }
+
+    /// Get the total number of bytes discarded while searching
+    pub fn skipped_bytes(&self) -> u64 {
+        self.skipped_bytes
+    }
+
+    /// Get the number of times the lock has been lost
+    pub fn sync_losses(&self) -> u64 {
+        self.sync_losses
+    }
+
+    /// Drop any lock and start searching again
+    pub fn reset(&mut self) {
+        self.locked = None;
+        self.misses = 0;
+    }
+
+    /// Look at the start of `data`, returning the number of bytes consumed
+    /// and what was found there
+    pub fn next<'a>(&mut self, data: &'a [u8]) -> (usize, SyncEvent<'a>) {
+        match self.locked {
+            Some(format) => self.next_locked(format, data),
+            None => self.search(data),
+        }
+    }
+
+    fn next_locked<'a>(&mut self, format: PacketFormat, data: &'a [u8]) -> (usize, SyncEvent<'a>) {
+        let size = format.packet_size();
+        if data.len() < size {
+            return (0, SyncEvent::NeedMore);
+        }
+        let packet = &data[..size];
+        if packet[format.packet_offset()] == SYNC_BYTE {
+            self.misses = 0;
+            return (size, SyncEvent::Packet(packet));
+        }
+        self.misses += 1;
+        if self.misses > self.max_misses {
+            self.reset();
+            self.sync_losses += 1;
+            return (0, SyncEvent::Lost);
+        }
+        (size, SyncEvent::Missed(packet))
+    }
+
+    fn search<'a>(&mut self, data: &'a [u8]) -> (usize, SyncEvent<'a>) {
+        let formats = match self.format {
+            Some(ref format) => core::slice::from_ref(format),
+            None => &ALL_FORMATS[..],
+        };
+        for start in 0..data.len() {
+            let mut undecided = false;
+            for &format in formats {
+                match self.check(format, &data[start..]) {
+                    Some(true) if start == 0 => {
+                        self.locked = Some(format);
+                        self.misses = 0;
+                        return (0, SyncEvent::Locked(format));
+                    }
+                    Some(true) => return self.skip(start),
+                    Some(false) => {}
+                    None => undecided = true,
+                }
+            }
+            if undecided {
+                return match start {
+                    0 => (0, SyncEvent::NeedMore),
+                    _ => self.skip(start),
+                };
+            }
+        }
+        match data.len() {
+            0 => (0, SyncEvent::NeedMore),
+            len => self.skip(len),
+        }
+    }
+
+    fn skip<'a>(&mut self, count: usize) -> (usize, SyncEvent<'a>) {
+        self.skipped_bytes += count as u64;
+        (count, SyncEvent::Skipped(count))
+    }
+
+    /// Are there `lock_count` sync bytes at the format's stride?
+    /// `None` if there is not enough data to tell.
+    fn check(&self, format: PacketFormat, data: &[u8]) -> Option<bool> {
+        let size = format.packet_size();
+        let offset = format.packet_offset();
+        for k in 0..self.lock_count {
+            match data.get(offset + k * size) {
+                Some(&SYNC_BYTE) => {}
+                Some(_) => return Some(false),
+                None => return None,
+            }
+        }
+        Some(true)
+    }
+}
+
+impl Default for Synchroniser {
+    fn default() -> Self {
+        Synchroniser::new(DEFAULT_LOCK_COUNT, DEFAULT_MAX_MISSES)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpeg::format::{m2ts_packet, M2TS_PACKET_SIZE, RS_PACKET_SIZE};
+    use crate::mpeg::packet::{self, PACKET_SIZE};
+    use crate::mpeg::reed_solomon::rs_encode_packet;
+
+    /// Feed all of `data` through, collecting the events
+    fn run<'a>(sync: &mut Synchroniser, data: &'a [u8], events: &mut [Option<SyncEvent<'a>>]) -> usize {
+        let mut pos = 0;
+        let mut count = 0;
+        loop {
+            let (used, event) = sync.next(&data[pos..]);
+            if event == SyncEvent::NeedMore {
+                return count;
+            }
+            events[count] = Some(event);
+            count += 1;
+            pos += used;
+        }
+    }
+
+    fn ts_stream(junk: usize, packets: usize) -> [u8; 2048] {
+        let mut data = [0u8; 2048];
+        for (i, chunk) in data[junk..junk + packets * PACKET_SIZE]
+            .chunks_exact_mut(PACKET_SIZE)
+            .enumerate()
+        {
+            let mut pkt = packet::null_packet();
+            packet::set_pid(&mut pkt, i as u16);
+            chunk.copy_from_slice(&pkt);
+        }
+        data
+    }
+
+    #[test]
+    fn test_lock_aligned() {
+        let data = ts_stream(0, 4);
+        let mut sync = Synchroniser::default();
+        let mut events = [None; 16];
+        let count = run(&mut sync, &data[..PACKET_SIZE * 4], &mut events);
+        assert_eq!(count, 5);
+        assert_eq!(events[0], Some(SyncEvent::Locked(PacketFormat::Ts)));
+        assert_eq!(events[1], Some(SyncEvent::Packet(&data[..PACKET_SIZE])));
+        assert_eq!(events[4], Some(SyncEvent::Packet(&data[PACKET_SIZE * 3..PACKET_SIZE * 4])));
+        assert_eq!(sync.skipped_bytes(), 0);
+    }
+
+    #[test]
+    fn test_lock_mid_packet() {
+        let data = ts_stream(57, 4);
+        let mut sync = Synchroniser::default();
+        let mut events = [None; 16];
+        let count = run(&mut sync, &data[..57 + PACKET_SIZE * 4], &mut events);
+        assert_eq!(events[0], Some(SyncEvent::Skipped(57)));
+        assert_eq!(events[1], Some(SyncEvent::Locked(PacketFormat::Ts)));
+        assert_eq!(count, 6);
+        assert_eq!(sync.skipped_bytes(), 57);
+    }
+
+    #[test]
+    fn test_false_sync_in_junk() {
+        let mut data = ts_stream(20, 4);
+        data[3] = SYNC_BYTE;
+        let mut sync = Synchroniser::default();
+        let mut events = [None; 16];
+        run(&mut sync, &data[..20 + PACKET_SIZE * 4], &mut events);
+        assert_eq!(events[0], Some(SyncEvent::Skipped(20)));
+        assert_eq!(events[1], Some(SyncEvent::Locked(PacketFormat::Ts)));
+    }
+
+    #[test]
+    fn test_need_more_before_lock() {
+        let data = ts_stream(0, 2);
+        let mut sync = Synchroniser::default();
+        assert_eq!(sync.next(&data[..PACKET_SIZE * 2]), (0, SyncEvent::NeedMore));
+        assert_eq!(sync.next(&[]), (0, SyncEvent::NeedMore));
+        assert_eq!(sync.locked_format(), None);
+    }
+
+    #[test]
+    fn test_flywheel_and_loss() {
+        let mut data = ts_stream(0, 8);
+        data[PACKET_SIZE * 3] = 0;
+        for k in 5..8 {
+            data[PACKET_SIZE * k] = 0;
+        }
+        let mut sync = Synchroniser::new(3, 2);
+        let mut events = [None; 32];
+        run(&mut sync, &data[..PACKET_SIZE * 8], &mut events);
+        assert_eq!(events[0], Some(SyncEvent::Locked(PacketFormat::Ts)));
+        assert!(matches!(events[3], Some(SyncEvent::Packet(_))));
+        assert!(matches!(events[4], Some(SyncEvent::Missed(_))));
+        assert!(matches!(events[5], Some(SyncEvent::Packet(_))));
+        assert!(matches!(events[6], Some(SyncEvent::Missed(_))));
+        assert!(matches!(events[7], Some(SyncEvent::Missed(_))));
+        assert_eq!(events[8], Some(SyncEvent::Lost));
+        // the last four bytes could still start an M2TS packet
+        assert_eq!(events[9], Some(SyncEvent::Skipped(PACKET_SIZE - 4)));
+        assert_eq!(sync.sync_losses(), 1);
+        assert_eq!(sync.locked_format(), None);
+    }
+
+    #[test]
+    fn test_lock_m2ts() {
+        let mut data = [0u8; M2TS_PACKET_SIZE * 4 + 10];
+        for (i, chunk) in data[10..].chunks_exact_mut(M2TS_PACKET_SIZE).enumerate() {
+            chunk.copy_from_slice(&m2ts_packet(&packet::null_packet(), 0, i as u32 * 1000));
+        }
+        let mut sync = Synchroniser::default();
+        let mut events = [None; 16];
+        run(&mut sync, &data, &mut events);
+        assert_eq!(events[0], Some(SyncEvent::Skipped(10)));
+        assert_eq!(events[1], Some(SyncEvent::Locked(PacketFormat::M2ts)));
+        assert_eq!(events[2], Some(SyncEvent::Packet(&data[10..10 + M2TS_PACKET_SIZE])));
+    }
+
+    #[test]
+    fn test_lock_rs() {
+        let mut data = [0u8; RS_PACKET_SIZE * 4];
+        for chunk in data.chunks_exact_mut(RS_PACKET_SIZE) {
+            chunk.copy_from_slice(&rs_encode_packet(&packet::null_packet()));
+        }
+        let mut sync = Synchroniser::default();
+        assert_eq!(sync.next(&data), (0, SyncEvent::Locked(PacketFormat::Rs)));
+        assert_eq!(sync.next(&data), (RS_PACKET_SIZE, SyncEvent::Packet(&data[..RS_PACKET_SIZE])));
+    }
+
+    #[test]
+    fn test_with_format() {
+        let data = ts_stream(0, 4);
+        let mut sync = Synchroniser::default().with_format(PacketFormat::Rs);
+        let (used, event) = sync.next(&data[..PACKET_SIZE * 4]);
+        assert!(matches!(event, SyncEvent::Skipped(_)));
+        assert!(used > 0);
+    }
+}
@@ -0,0 +1,454 @@
+#[cfg(feature = "serde")]
+use core::convert::TryFrom;
+use core::fmt;
+use core::ops::Range;
+
+use super::crc::CRC_SIZE;
+use super::descriptor::{write_descriptor, Descriptors, DESCRIPTOR_HEADER_SIZE, DESCRIPTOR_MAX_DATA};
+use super::packet::SECTION_MAX_SIZE;
+use super::section::{Section, LONG_SECTION_HEADER_SIZE};
+use super::section_builder::{SectionBuffer, SectionBuilder};
+use crate::error::TsError;
+
+/// Largest PSI section (PAT, PMT, CAT, TSDT): section_length is at most 1021
+pub const PSI_SECTION_MAX_SIZE: usize = 1024;
+/// Most descriptor bytes a table made of one descriptor loop can hold
+pub const TABLE_MAX_DESCRIPTORS: usize = 4096;
+
+/// table_id_extension written by tables that reserve it, and ignored on parse
+pub(crate) const RESERVED_TABLE_ID_EXTENSION: u16 = 0xffff;
+
+const SECTION_MAX_DATA: usize = PSI_SECTION_MAX_SIZE - LONG_SECTION_HEADER_SIZE - CRC_SIZE;
+
+/// Check a long-form section of the given table and view it
+pub(crate) fn parse_table_section(bytes: &[u8], table_id: u8) -> Result<Section<'_>, TsError> {
+    let section = Section::new(bytes)?;
+    if section.table_id() != table_id {
+        return Err(TsError::BadTableId(section.table_id()));
+    }
+    if !section.section_syntax_indicator() {
+        return Err(TsError::Reserved("section_syntax_indicator"));
+    }
+    section.verify_crc()?;
+    Ok(section)
+}
+
+/// Get a long-form section's section_number and last_section_number,
+/// checking the first is not past the second
+pub(crate) fn section_numbers(section: &Section<'_>) -> Result<(u8, u8), TsError> {
+    let number = section.section_number().unwrap_or(0);
+    let last = section.last_section_number().unwrap_or(0);
+    if number > last {
+        return Err(TsError::BadSectionNumber(number));
+    }
+    Ok((number, last))
+}
+
+/// A list of up to `N` entries stored inline, for tables without allocation.
+#[derive(Clone, Copy)]
+pub(crate) struct FixedVec<T: Copy + Default, const N: usize> {
+    items: [T; N],
+    len: usize,
+}
+
+impl<T: Copy + Default, const N: usize> FixedVec<T, N> {
+    pub(crate) fn new() -> Self {
+        FixedVec {
+            items: [T::default(); N],
+            len: 0,
+        }
+    }
+
+    pub(crate) fn as_slice(&self) -> &[T] {
+        &self.items[..self.len]
+    }
+
+    pub(crate) fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.items[..self.len]
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(crate) fn push(&mut self, item: T) -> Result<(), TsError> {
+        if self.len == N {
+            return Err(TsError::TooManyEntries(N));
+        }
+        self.items[self.len] = item;
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Replace `remove` entries at `index` with `insert`
+    pub(crate) fn splice(&mut self, index: usize, remove: usize, insert: &[T]) -> Result<(), TsError> {
+        let len = self.len - remove + insert.len();
+        if len > N {
+            return Err(TsError::TooManyEntries(N));
+        }
+        self.items.copy_within(index + remove..self.len, index + insert.len());
+        self.items[index..index + insert.len()].copy_from_slice(insert);
+        self.len = len;
+        Ok(())
+    }
+
+    pub(crate) fn remove(&mut self, index: usize) -> T {
+        let item = self.items[index];
+        self.items.copy_within(index + 1..self.len, index);
+        self.len -= 1;
+        item
+    }
+}
+
+impl<T: Copy + Default, const N: usize> Default for FixedVec<T, N> {
+    fn default() -> Self {
+        FixedVec::new()
+    }
+}
+
+impl<T: Copy + Default + fmt::Debug, const N: usize> fmt::Debug for FixedVec<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.as_slice()).finish()
+    }
+}
+
+impl<T: Copy + Default + PartialEq, const N: usize> PartialEq for FixedVec<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<T: Copy + Default + Eq, const N: usize> Eq for FixedVec<T, N> {}
+
+#[cfg(feature = "serde")]
+impl<T: Copy + Default + serde::Serialize, const N: usize> serde::Serialize for FixedVec<T, N> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.as_slice())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T, const N: usize> serde::Deserialize<'de> for FixedVec<T, N>
+where
+    T: Copy + Default + serde::Deserialize<'de>,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FixedVecVisitor<T, const N: usize>(core::marker::PhantomData<T>);
+
+        impl<'de, T, const N: usize> serde::de::Visitor<'de> for FixedVecVisitor<T, N>
+        where
+            T: Copy + Default + serde::Deserialize<'de>,
+        {
+            type Value = FixedVec<T, N>;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "a sequence of at most {} entries", N)
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut list = FixedVec::new();
+                while let Some(item) = seq.next_element()? {
+                    list.push(item)
+                        .map_err(|_| serde::de::Error::invalid_length(N + 1, &self))?;
+                }
+                Ok(list)
+            }
+        }
+
+        deserializer.deserialize_seq(FixedVecVisitor(core::marker::PhantomData))
+    }
+}
+
+/// A multi-section table being gathered: the sections not yet seen, and how
+/// many entries each one seen so far holds, so that sections arriving out of
+/// order are put back in section_number order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct SectionAssembly {
+    missing: [u32; 8],
+    last: u8,
+    sizes: [u16; 256],
+}
+
+impl SectionAssembly {
+    /// Expect sections 0 to `last`
+    pub(crate) fn new(last: u8) -> Self {
+        let mut assembly = SectionAssembly {
+            missing: [0; 8],
+            last,
+            sizes: [0; 256],
+        };
+        for n in 0..=last {
+            assembly.missing[n as usize / 32] |= 1 << (n % 32);
+        }
+        assembly
+    }
+
+    /// Check a section's numbers from `section_numbers` against the table,
+    /// returning whether it is still missing
+    pub(crate) fn check(&self, number: u8, last: u8) -> Result<bool, TsError> {
+        if last != self.last {
+            return Err(TsError::InconsistentSection);
+        }
+        Ok(self.missing[number as usize / 32] & 1 << (number % 32) != 0)
+    }
+
+    /// Get where the entries of section `number` go: after those of every
+    /// earlier section already seen
+    pub(crate) fn offset(&self, number: u8) -> usize {
+        self.sizes[..number as usize].iter().map(|&size| size as usize).sum()
+    }
+
+    /// Mark section `number` as seen, holding `size` entries
+    pub(crate) fn mark_seen(&mut self, number: u8, size: usize) {
+        self.missing[number as usize / 32] &= !(1 << (number % 32));
+        self.sizes[number as usize] = size as u16;
+    }
+
+    pub(crate) fn is_complete(&self) -> bool {
+        self.missing.iter().all(|&word| word == 0)
+    }
+}
+
+/// A table whose sections carry nothing but one descriptor loop, split
+/// across sections at descriptor boundaries. The CAT, TSDT and IPMP control
+/// information wrap this with their table_id and table_id_extension.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "DescriptorTableRecord"))]
+pub(crate) struct DescriptorTable {
+    version_number: u8,
+    current_next_indicator: bool,
+    descriptors: FixedVec<u8, TABLE_MAX_DESCRIPTORS>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    assembly: Option<SectionAssembly>,
+}
+
+/// A deserialized `DescriptorTable` whose loop has not been checked yet
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct DescriptorTableRecord {
+    version_number: u8,
+    current_next_indicator: bool,
+    descriptors: FixedVec<u8, TABLE_MAX_DESCRIPTORS>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<DescriptorTableRecord> for DescriptorTable {
+    type Error = TsError;
+
+    fn try_from(record: DescriptorTableRecord) -> Result<Self, TsError> {
+        let mut table = DescriptorTable::new();
+        table.set_version_number(record.version_number);
+        table.set_current_next_indicator(record.current_next_indicator);
+        table.add_descriptor_loop(record.descriptors.as_slice())?;
+        Ok(table)
+    }
+}
+
+impl DescriptorTable {
+    pub(crate) fn new() -> Self {
+        DescriptorTable {
+            version_number: 0,
+            current_next_indicator: true,
+            descriptors: FixedVec::new(),
+            assembly: None,
+        }
+    }
+
+    pub(crate) fn parse(bytes: &[u8], table_id: u8) -> Result<Self, TsError> {
+        let section = parse_table_section(bytes, table_id)?;
+        let mut table = DescriptorTable::new();
+        table.version_number = section.version_number().unwrap_or(0);
+        table.current_next_indicator = section.current_next_indicator().unwrap_or(true);
+        let (number, last) = section_numbers(&section)?;
+        table.assembly = Some(SectionAssembly::new(last));
+        table.add_section_loop(number, section.data())?;
+        Ok(table)
+    }
+
+    /// Add another section, which must carry `table_id_extension` if the
+    /// table uses it
+    pub(crate) fn add_section(
+        &mut self,
+        bytes: &[u8],
+        table_id: u8,
+        table_id_extension: Option<u16>,
+    ) -> Result<(), TsError> {
+        let section = parse_table_section(bytes, table_id)?;
+        if table_id_extension.is_some_and(|extension| section.table_id_extension() != Some(extension))
+            || section.version_number() != Some(self.version_number)
+            || section.current_next_indicator() != Some(self.current_next_indicator)
+        {
+            return Err(TsError::InconsistentSection);
+        }
+        let (number, last) = section_numbers(&section)?;
+        match self.assembly.as_ref() {
+            Some(assembly) if assembly.check(number, last)? => self.add_section_loop(number, section.data()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Insert the descriptor loop of section `number` after those of the
+    /// earlier sections
+    fn add_section_loop(&mut self, number: u8, descriptors: &[u8]) -> Result<(), TsError> {
+        Descriptors::new(descriptors)?;
+        let offset = self.assembly.as_ref().map_or(self.descriptors.len(), |a| a.offset(number));
+        self.descriptors.splice(offset, 0, descriptors)?;
+        if let Some(assembly) = self.assembly.as_mut() {
+            assembly.mark_seen(number, descriptors.len());
+            if assembly.is_complete() {
+                self.assembly = None;
+            }
+        }
+        Ok(())
+    }
+
+    /// Append a whole descriptor loop, checking it first
+    pub(crate) fn add_descriptor_loop(&mut self, descriptors: &[u8]) -> Result<(), TsError> {
+        Descriptors::new(descriptors)?;
+        let end = self.descriptors.len();
+        self.descriptors.splice(end, 0, descriptors)
+    }
+
+    pub(crate) fn add_descriptor(&mut self, tag: u8, data: &[u8]) -> Result<(), TsError> {
+        let mut descriptor = [0u8; DESCRIPTOR_HEADER_SIZE + DESCRIPTOR_MAX_DATA];
+        let len = write_descriptor(&mut descriptor, tag, data)?;
+        self.add_descriptor_loop(&descriptor[..len])
+    }
+
+    pub(crate) fn clear_descriptors(&mut self) {
+        self.descriptors = FixedVec::new();
+    }
+
+    pub(crate) fn is_complete(&self) -> bool {
+        self.assembly.is_none()
+    }
+
+    pub(crate) fn version_number(&self) -> u8 {
+        self.version_number
+    }
+
+    pub(crate) fn set_version_number(&mut self, version: u8) {
+        self.version_number = version & 0x1f;
+    }
+
+    pub(crate) fn current_next_indicator(&self) -> bool {
+        self.current_next_indicator
+    }
+
+    pub(crate) fn set_current_next_indicator(&mut self, current: bool) {
+        self.current_next_indicator = current;
+    }
+
+    pub(crate) fn descriptor_loop(&self) -> &[u8] {
+        self.descriptors.as_slice()
+    }
+
+    pub(crate) fn descriptors(&self) -> Descriptors<'_> {
+        Descriptors::new_unchecked(self.descriptors.as_slice())
+    }
+
+    /// Split the loop into sections at descriptor boundaries, returning the
+    /// number of sections and the bytes of section `number`
+    fn split(&self, number: usize) -> (usize, Range<usize>) {
+        let bytes = self.descriptors.as_slice();
+        let (mut count, mut start, mut pos) = (1, 0, 0);
+        let mut range = 0..0;
+        while pos < bytes.len() {
+            let size = DESCRIPTOR_HEADER_SIZE + bytes[pos + 1] as usize;
+            if pos + size - start > SECTION_MAX_DATA {
+                if count - 1 == number {
+                    range = start..pos;
+                }
+                count += 1;
+                start = pos;
+            }
+            pos += size;
+        }
+        if count - 1 == number {
+            range = start..pos;
+        }
+        (count, range)
+    }
+
+    pub(crate) fn section_count(&self) -> usize {
+        self.split(0).0
+    }
+
+    pub(crate) fn write_section(
+        &self,
+        table_id: u8,
+        table_id_extension: u16,
+        number: usize,
+        out: &mut [u8],
+    ) -> Result<usize, TsError> {
+        let (count, range) = self.split(number);
+        if number >= count {
+            return Err(TsError::TooManyEntries(count));
+        }
+        SectionBuilder::new(table_id)
+            .table_id_extension(table_id_extension)
+            .version_number(self.version_number)
+            .current_next_indicator(self.current_next_indicator)
+            .section_number(number as u8)
+            .last_section_number((count - 1) as u8)
+            .data(&self.descriptors.as_slice()[range])
+            .write(out)
+    }
+
+    pub(crate) fn build_section(
+        &self,
+        table_id: u8,
+        table_id_extension: u16,
+        number: usize,
+    ) -> Result<(SectionBuffer, usize), TsError> {
+        let mut section: SectionBuffer = [0xff; SECTION_MAX_SIZE];
+        let len = self.write_section(table_id, table_id_extension, number, &mut section)?;
+        Ok((section, len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_vec() {
+        let mut list: FixedVec<u16, 3> = FixedVec::new();
+        list.push(1).unwrap();
+        list.push(2).unwrap();
+        list.push(3).unwrap();
+        assert_eq!(list.push(4), Err(TsError::TooManyEntries(3)));
+        assert_eq!(list.remove(0), 1);
+        assert_eq!(list.as_slice(), &[2, 3]);
+        list.as_mut_slice()[0] = 9;
+        assert_eq!(std::format!("{:?}", list), "[9, 3]");
+        assert_eq!(list.len(), 2);
+        list.splice(1, 1, &[4, 5]).unwrap();
+        assert_eq!(list.as_slice(), &[9, 4, 5]);
+        list.splice(0, 2, &[]).unwrap();
+        assert_eq!(list.as_slice(), &[5]);
+        assert_eq!(list.splice(0, 0, &[1, 2, 3]), Err(TsError::TooManyEntries(3)));
+    }
+
+    #[test]
+    fn test_section_assembly() {
+        let mut assembly = SectionAssembly::new(2);
+        assert_eq!(assembly.check(2, 2), Ok(true));
+        assembly.mark_seen(2, 7);
+        assert_eq!(assembly.check(2, 2), Ok(false));
+        assert_eq!(assembly.check(0, 3), Err(TsError::InconsistentSection));
+        assert_eq!(assembly.offset(0), 0);
+        assembly.mark_seen(0, 5);
+        assert_eq!(assembly.offset(1), 5);
+        assert_eq!(assembly.offset(2), 5);
+        assert!(!assembly.is_complete());
+        assembly.mark_seen(1, 3);
+        assert_eq!(assembly.offset(2), 8);
+        assert!(assembly.is_complete());
+        assert!(!SectionAssembly::new(255).is_complete());
+        let mut single = SectionAssembly::new(0);
+        single.mark_seen(0, 0);
+        assert!(single.is_complete());
+    }
+}
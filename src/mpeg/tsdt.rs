@@ -0,0 +1,199 @@
+use super::descriptor::Descriptors;
+use super::section_builder::SectionBuffer;
+use super::table::{DescriptorTable, RESERVED_TABLE_ID_EXTENSION, TABLE_MAX_DESCRIPTORS};
+use crate::error::TsError;
+
+/// table_id of a TS_description_section
+pub const TSDT_TABLE_ID: u8 = 0x03;
+/// Most descriptor bytes a `Tsdt` can hold
+pub const TSDT_MAX_DESCRIPTORS: usize = TABLE_MAX_DESCRIPTORS;
+
+/// A transport stream description table: descriptors that apply to the
+/// whole stream, possibly spread over several sections.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Tsdt {
+    table: DescriptorTable,
+}
+
+impl Tsdt {
+    /// Start an empty, current TSDT at version 0
+    pub fn new() -> Self {
+        Tsdt {
+            table: DescriptorTable::new(),
+        }
+    }
+
+    /// Parse one TS_description_section, checking its CRC_32 and descriptor
+    /// loop. If it is one of several, add the others with `add_section`.
+    pub fn parse(bytes: &[u8]) -> Result<Self, TsError> {
+        Ok(Tsdt {
+            table: DescriptorTable::parse(bytes, TSDT_TABLE_ID)?,
+        })
+    }
+
+    /// Add another section of the same table, ignoring one already seen
+    pub fn add_section(&mut self, bytes: &[u8]) -> Result<(), TsError> {
+        self.table.add_section(bytes, TSDT_TABLE_ID, None)
+    }
+
+    /// Have all the sections of the table been seen?
+    pub fn is_complete(&self) -> bool {
+        self.table.is_complete()
+    }
+
+    /// Get the version_number
+    pub fn version_number(&self) -> u8 {
+        self.table.version_number()
+    }
+
+    /// Set the version_number. Max: 31
+    pub fn set_version_number(&mut self, version: u8) {
+        self.table.set_version_number(version);
+    }
+
+    /// Get the current_next_indicator
+    pub fn current_next_indicator(&self) -> bool {
+        self.table.current_next_indicator()
+    }
+
+    /// Set the current_next_indicator
+    pub fn set_current_next_indicator(&mut self, current: bool) {
+        self.table.set_current_next_indicator(current);
+    }
+
+    /// Get the raw descriptor loop
+    pub fn descriptor_loop(&self) -> &[u8] {
+        self.table.descriptor_loop()
+    }
+
+    /// Iterate over the descriptors
+    pub fn descriptors(&self) -> Descriptors<'_> {
+        self.table.descriptors()
+    }
+
+    /// Append a descriptor
+    pub fn add_descriptor(&mut self, tag: u8, data: &[u8]) -> Result<(), TsError> {
+        self.table.add_descriptor(tag, data)
+    }
+
+    /// Remove every descriptor
+    pub fn clear_descriptors(&mut self) {
+        self.table.clear_descriptors();
+    }
+
+    /// Get the number of sections needed to carry the table
+    pub fn section_count(&self) -> usize {
+        self.table.section_count()
+    }
+
+    /// Write section `number` of the table to the start of `out`, returning
+    /// its length
+    pub fn write_section(&self, number: usize, out: &mut [u8]) -> Result<usize, TsError> {
+        self.table.write_section(TSDT_TABLE_ID, RESERVED_TABLE_ID_EXTENSION, number, out)
+    }
+
+    /// Build section `number` of the table, returning it with its length
+    pub fn build_section(&self, number: usize) -> Result<(SectionBuffer, usize), TsError> {
+        self.table.build_section(TSDT_TABLE_ID, RESERVED_TABLE_ID_EXTENSION, number)
+    }
+}
+
+impl Default for Tsdt {
+    fn default() -> Self {
+        Tsdt::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpeg::packetizer::SectionPacketizer;
+    use crate::mpeg::pid::Pid;
+    use crate::mpeg::section::SectionAssembler;
+
+    #[test]
+    fn test_build_and_parse() {
+        let mut tsdt = Tsdt::new();
+        tsdt.set_version_number(2);
+        tsdt.add_descriptor(0x05, b"HDMV").unwrap();
+        tsdt.add_descriptor(0x81, &[0x01, 0x02]).unwrap();
+
+        let (section, len) = tsdt.build_section(0).unwrap();
+        assert_eq!(&section[..8], &[0x03, 0xb0, len as u8 - 3, 0xff, 0xff, 0xc5, 0x00, 0x00]);
+        let parsed = Tsdt::parse(&section[..len]).unwrap();
+        assert!(parsed.is_complete());
+        assert_eq!(parsed, tsdt);
+        let mut descriptors = parsed.descriptors();
+        assert_eq!(descriptors.next().unwrap().data(), b"HDMV");
+        assert_eq!(descriptors.next().unwrap().tag(), 0x81);
+        assert!(descriptors.next().is_none());
+        assert_eq!(Tsdt::new().build_section(0).unwrap().1, 12);
+    }
+
+    #[test]
+    fn test_on_pid() {
+        let mut tsdt = Tsdt::new();
+        let data = [0x20; 250];
+        for tag in 0x80..0x88 {
+            tsdt.add_descriptor(tag, &data).unwrap();
+        }
+        assert_eq!(tsdt.section_count(), 2);
+        let (first, first_len) = tsdt.build_section(0).unwrap();
+        let (second, second_len) = tsdt.build_section(1).unwrap();
+        // sent out of order; the loops still join in section_number order
+        let sections = [&second[..second_len], &first[..first_len]];
+
+        let pid = Pid::TSDT.value();
+        let mut packetizer = SectionPacketizer::new(pid);
+        let mut assembler = SectionAssembler::new(pid).verify_crc(true);
+        let mut parsed: Option<Tsdt> = None;
+        for pkt in packetizer.packetize(&sections).unwrap() {
+            assembler.push(&pkt).unwrap();
+            while let Some(section) = assembler.next_section() {
+                let section = section.unwrap();
+                match parsed.as_mut() {
+                    Some(tsdt) => tsdt.add_section(section).unwrap(),
+                    None => parsed = Some(Tsdt::parse(section).unwrap()),
+                }
+            }
+        }
+        let parsed = parsed.unwrap();
+        assert!(parsed.is_complete());
+        assert_eq!(parsed.descriptors().count(), 8);
+        assert_eq!(parsed, tsdt);
+    }
+
+    #[test]
+    fn test_bad_sections() {
+        let (section, len) = Tsdt::new().build_section(0).unwrap();
+        let mut cat = [0u8; 12];
+        cat.copy_from_slice(&section[..len]);
+        cat[0] = 0x01;
+        assert_eq!(Tsdt::parse(&cat), Err(TsError::BadTableId(0x01)));
+        assert_eq!(Tsdt::new().add_descriptor(0x80, &[0; 256]), Err(TsError::BadLength(256)));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let mut tsdt = Tsdt::new();
+        tsdt.add_descriptor(0x05, b"HDMV").unwrap();
+        let json = serde_json::to_string(&tsdt).unwrap();
+        assert_eq!(
+            json,
+            r#"{"version_number":0,"current_next_indicator":true,"descriptors":[5,4,72,68,77,86]}"#
+        );
+        assert_eq!(serde_json::from_str::<Tsdt>(&json).unwrap(), tsdt);
+
+        for descriptors in ["[9]", "[9,5,0]"].iter() {
+            let json = std::format!(
+                r#"{{"version_number":0,"current_next_indicator":true,"descriptors":{}}}"#,
+                descriptors
+            );
+            let err = serde_json::from_str::<Tsdt>(&json).unwrap_err();
+            assert!(std::format!("{}", err).contains("descriptor 0x09 truncated"));
+        }
+    }
+}